@@ -0,0 +1,56 @@
+//! Parsing speed of each way to read a save. Run with `cargo bench -p deltakit`.
+//!
+//! Tools like `deltalog` and `deltabisect` parse one save per commit, so parsing has to stay
+//! fast enough for histories with thousands of commits.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use deltakit::savefile::SaveData;
+use std::hint::black_box;
+
+const FIXTURES: [(i32, &[u8]); 2] = [
+    (1, include_bytes!("../fixtures/filech1_0")),
+    (2, include_bytes!("../fixtures/filech2_0")),
+];
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (chapter, bytes) in FIXTURES {
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("read", chapter), bytes, |b, bytes| {
+            b.iter(|| {
+                let content = std::str::from_utf8(bytes).unwrap();
+                let lines = content.lines().collect::<Vec<_>>();
+                SaveData::read(chapter, black_box(&lines)).unwrap().level
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("from_bytes", chapter), bytes, |b, bytes| {
+            b.iter(|| SaveData::from_bytes(chapter, black_box(bytes)).unwrap().level)
+        });
+        group.bench_with_input(BenchmarkId::new("from_reader", chapter), bytes, |b, bytes| {
+            b.iter(|| SaveData::from_reader(chapter, black_box(bytes)).unwrap().level)
+        });
+    }
+    group.finish();
+}
+
+/// A history scan: parsing the same slot at many commits.
+fn history(c: &mut Criterion) {
+    let revisions = (0..200)
+        .map(|i| SaveData::builder(2).dark_dollars(i).build().write().into_bytes())
+        .collect::<Vec<_>>();
+
+    c.bench_function("history/200 revisions", |b| {
+        b.iter(|| {
+            let mut total = 0;
+            for content in &revisions {
+                let save = SaveData::from_bytes(2, black_box(content)).unwrap();
+                total += save.dark_dollars;
+            }
+            total
+        })
+    });
+}
+
+criterion_group!(benches, parse, history);
+criterion_main!(benches);
@@ -0,0 +1,230 @@
+//! Builds saves from scratch, for tests and tools that need a save without a file to read.
+//!
+//! ```
+//! use deltakit::gamedata::flags::Flag;
+//! use deltakit::gamedata::party_members::PartyMember;
+//! use deltakit::savefile::SaveData;
+//!
+//! let save = SaveData::builder(2)
+//!     .party(&[PartyMember::KRIS, PartyMember::SUSIE])
+//!     .flag(Flag::SpamtonNeoFight, 1.0)
+//!     .build();
+//! assert!(save.validate().is_empty());
+//! ```
+
+use crate::gamedata::armors::ArmorId;
+use crate::gamedata::flags::Flag;
+use crate::gamedata::items::ItemId;
+use crate::gamedata::key_items::KeyItemId;
+use crate::gamedata::party_members::PartyMember;
+use crate::gamedata::rooms::{RoomIndex, start_room};
+use crate::gamedata::weapons::WeaponId;
+use crate::savefile::{LightworldStats, SaveData, Stats};
+use std::time::Duration;
+
+/// Builds a [`SaveData`] with fluent setters, starting from a valid but mostly empty save.
+#[derive(Debug, Clone)]
+pub struct SaveBuilder<'a> {
+    save: SaveData<'a>,
+}
+
+/// Copies `values` into the start of `slots` and clears the rest.
+///
+/// Panics if there are more values than slots.
+fn fill<T: Copy + Default>(slots: &mut [T], values: &[T], what: &str) {
+    assert!(
+        values.len() <= slots.len(),
+        "{} {what} don't fit in {} slots",
+        values.len(),
+        slots.len()
+    );
+    slots.fill(T::default());
+    slots[..values.len()].copy_from_slice(values);
+}
+
+impl<'a> SaveBuilder<'a> {
+    /// A valid save of the given chapter with Kris alone in the party, at level 1, in the
+    /// chapter's first room. It isn't what a new game saves: every party member's stats are
+    /// zero until set with [`stats`](SaveBuilder::stats).
+    pub fn new(chapter: i32) -> Self {
+        let mut save = SaveData::empty(chapter);
+        save.true_name = "Kris".into();
+        save.party[0] = PartyMember::KRIS;
+        save.level = 1;
+        save.max_tension.set(250.0);
+        save.lightworld_stats = LightworldStats {
+            lv: 1,
+            hp: 20,
+            max_hp: 20,
+            attack: 10,
+            defense: 10,
+            ..LightworldStats::default()
+        };
+        save.room_id = start_room(chapter);
+
+        SaveBuilder { save }
+    }
+
+    pub fn name(mut self, name: &'a str) -> Self {
+        self.save.true_name = name.into();
+        self
+    }
+
+    /// Sets the party, up to three members.
+    pub fn party(mut self, members: &[PartyMember]) -> Self {
+        fill(&mut self.save.party, members, "party members");
+        self
+    }
+
+    /// Changes the stats of a party member. Stat blocks are indexed by character ID.
+    ///
+    /// Panics if the chapter has no stat block for the member, like Noelle in chapter 1.
+    pub fn stats(mut self, member: PartyMember, edit: impl FnOnce(&mut Stats)) -> Self {
+        let index = usize::try_from(member.0).expect("party members start at 1");
+        let Some(stats) = self.save.stats.get_mut(index) else {
+            panic!(
+                "chapter {} has no stat block for party member {}",
+                self.save.chapter, member.0
+            );
+        };
+        edit(stats);
+        self
+    }
+
+    /// Equips a party member, updating the item stats the game caches for the equipment.
+    pub fn equip(self, member: PartyMember, weapon: WeaponId, armors: [ArmorId; 2]) -> Self {
+        self.stats(member, |stats| {
+            stats.weapon = weapon;
+            [stats.armor1, stats.armor2] = armors;
+            let bonuses = stats.expected_item_bonuses();
+            for (item_stats, bonus) in stats.item_stats.iter_mut().zip(bonuses) {
+                let bonus = bonus.unwrap_or_default();
+                item_stats.attack = bonus.attack;
+                item_stats.defense = bonus.defense;
+                item_stats.magic = bonus.magic;
+            }
+        })
+    }
+
+    pub fn inventory(mut self, items: &[ItemId]) -> Self {
+        fill(&mut self.save.inventory, items, "items");
+        self
+    }
+
+    pub fn key_items(mut self, key_items: &[KeyItemId]) -> Self {
+        fill(&mut self.save.key_items, key_items, "key items");
+        self
+    }
+
+    /// Sets the unequipped weapons.
+    pub fn weapons(mut self, weapons: &[WeaponId]) -> Self {
+        fill(&mut self.save.weapons, weapons, "weapons");
+        self
+    }
+
+    /// Sets the unequipped armors.
+    pub fn armors(mut self, armors: &[ArmorId]) -> Self {
+        fill(&mut self.save.armors, armors, "armors");
+        self
+    }
+
+    /// Sets the storage. Chapter 1 has none, so this does nothing there.
+    pub fn storage(mut self, items: &[ItemId]) -> Self {
+        if let Some(storage) = &mut self.save.storage {
+            fill(storage, items, "items");
+        }
+        self
+    }
+
+    pub fn flag(self, flag: Flag, value: f64) -> Self {
+        self.raw_flag(flag.index(), value)
+    }
+
+    /// Sets a flag by index, for flags that aren't in the [flag registry](Flag).
+    pub fn raw_flag(mut self, index: usize, value: f64) -> Self {
+        self.save.flags[index].set(value);
+        self
+    }
+
+    pub fn dark_dollars(mut self, dark_dollars: i32) -> Self {
+        self.save.dark_dollars = dark_dollars;
+        self
+    }
+
+    pub fn level(mut self, level: i32) -> Self {
+        self.save.level = level;
+        self
+    }
+
+    pub fn darkworld(mut self, is_darkworld: bool) -> Self {
+        self.save.is_darkworld = is_darkworld;
+        self
+    }
+
+    pub fn room(mut self, room_id: RoomIndex) -> Self {
+        self.save.room_id = room_id;
+        self
+    }
+
+    pub fn plot_value(mut self, plot_value: f64) -> Self {
+        self.save.plot_value.set(plot_value);
+        self
+    }
+
+    pub fn time_played(mut self, time_played: Duration) -> Self {
+        self.save.set_time_played(time_played);
+        self
+    }
+
+    pub fn build(self) -> SaveData<'a> {
+        self.save
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_saves() {
+        for (chapter, line_count) in [(1, 10318), (2, 3055), (3, 3055), (4, 3055)] {
+            let save = SaveData::builder(chapter).build();
+            assert_eq!(save.validate(), vec![], "chapter {chapter}");
+
+            let written = save.to_lines();
+            assert_eq!(written.len(), line_count);
+            let written_lines = written.iter().map(|l| l.as_str()).collect::<Vec<_>>();
+            assert_eq!(SaveData::read(chapter, &written_lines).unwrap().to_lines(), written);
+        }
+    }
+
+    #[test]
+    fn test_setters() {
+        let save = SaveData::builder(2)
+            .party(&[PartyMember::KRIS, PartyMember::NOELLE])
+            .stats(PartyMember::NOELLE, |stats| stats.max_hp = 90)
+            .inventory(&[ItemId(1), ItemId(2)])
+            .flag(Flag::ShadowCrystalCh2, 1.0)
+            .room(RoomIndex(20084))
+            .build();
+
+        assert_eq!(save.party[1], PartyMember::NOELLE);
+        assert_eq!(save.party[2], PartyMember::default());
+        assert_eq!(save.stats[4].max_hp, 90);
+        assert_eq!(save.inventory[1], ItemId(2));
+        assert_eq!(save.shadow_crystals(), 1);
+        assert_eq!(save.room_name(), RoomIndex(20084).name());
+    }
+
+    #[test]
+    #[should_panic(expected = "4 party members don't fit in 3 slots")]
+    fn test_too_many_values() {
+        SaveData::builder(1).party(&[PartyMember::KRIS; 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "chapter 1 has no stat block for party member 4")]
+    fn test_missing_stat_block() {
+        SaveData::builder(1).stats(PartyMember::NOELLE, |stats| stats.max_hp = 90);
+    }
+}
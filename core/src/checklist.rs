@@ -0,0 +1,332 @@
+//! Tracks the collectibles and milestones of a chapter, for planning 100% runs.
+//!
+//! Each chapter has a checklist definition in `core/checklists`. Entries name what to check
+//! with the names from the gamedata tables:
+//!
+//! ```toml
+//! chapter = 1
+//!
+//! [[entries]]
+//! category = "Weapons"
+//! weapon = "Devilsknife"
+//!
+//! [[entries]]
+//! category = "Optional fights"
+//! name = "Jevil"
+//! flag = "jevil_fight"
+//! ```
+//!
+//! An entry checks one of `flag`, `weapon`, `armor`, `key_item`, `item` or `query`, a
+//! [query](crate::query) expression that is true once the entry is done.
+
+use crate::gamedata::UnknownNameError;
+use crate::gamedata::armors::ArmorId;
+use crate::gamedata::flags::Flag;
+use crate::gamedata::items::ItemId;
+use crate::gamedata::key_items::KeyItemId;
+use crate::gamedata::weapons::WeaponId;
+use crate::query::{Expr, QueryError};
+use crate::savefile::SaveData;
+use compact_str::{CompactString, ToCompactString};
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+
+const BUILTIN_CHECKLISTS: [&str; 4] = [
+    include_str!("../checklists/chapter1.toml"),
+    include_str!("../checklists/chapter2.toml"),
+    include_str!("../checklists/chapter3.toml"),
+    include_str!("../checklists/chapter4.toml"),
+];
+
+#[derive(Debug, Error)]
+pub enum ChecklistError {
+    #[error("failed to parse checklist: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("entry {index}: expected exactly one of flag, weapon, armor, key_item, item or query")]
+    InvalidEntry { index: usize },
+    #[error("entry {index}: {source}")]
+    UnknownName {
+        index: usize,
+        source: UnknownNameError,
+    },
+    #[error("entry {index}: invalid query: {source}")]
+    Query { index: usize, source: QueryError },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChecklistFile {
+    chapter: i32,
+    #[serde(default)]
+    entries: Vec<EntryFile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EntryFile {
+    category: String,
+    name: Option<String>,
+    flag: Option<String>,
+    weapon: Option<String>,
+    armor: Option<String>,
+    key_item: Option<String>,
+    item: Option<String>,
+    query: Option<String>,
+}
+
+/// What makes a checklist entry done.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Requirement {
+    Flag(Flag),
+    /// Held or equipped by anyone.
+    Weapon(WeaponId),
+    /// Held or equipped by anyone.
+    Armor(ArmorId),
+    KeyItem(KeyItemId),
+    /// In the inventory or the storage.
+    Item(ItemId),
+    Query(Expr),
+}
+
+impl Requirement {
+    pub fn is_met(&self, save: &SaveData) -> bool {
+        match self {
+            Requirement::Flag(flag) => save.flag(*flag).is_set(),
+            Requirement::Weapon(weapon) => {
+                save.weapons.contains(weapon)
+                    || save.stats.iter().any(|stats| stats.weapon == *weapon)
+            }
+            Requirement::Armor(armor) => {
+                save.armors.contains(armor)
+                    || save
+                        .stats
+                        .iter()
+                        .any(|stats| stats.armor1 == *armor || stats.armor2 == *armor)
+            }
+            Requirement::KeyItem(key_item) => save.key_items.contains(key_item),
+            Requirement::Item(item) => {
+                save.inventory.contains(item)
+                    || save.storage.as_ref().is_some_and(|storage| storage.contains(item))
+            }
+            // Queries that can't be evaluated on this save count as not done
+            Requirement::Query(expr) => expr.matches(save).unwrap_or(false),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChecklistEntry {
+    pub category: CompactString,
+    pub name: CompactString,
+    pub requirement: Requirement,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checklist {
+    pub chapter: i32,
+    pub entries: Vec<ChecklistEntry>,
+}
+
+impl Checklist {
+    /// Parses a checklist definition, resolving the names in it.
+    pub fn parse(content: &str) -> Result<Self, ChecklistError> {
+        let file: ChecklistFile = toml::from_str(content)?;
+
+        let entries = file
+            .entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, entry)| parse_entry(index, entry))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Checklist {
+            chapter: file.chapter,
+            entries,
+        })
+    }
+
+    /// The built-in checklist of a chapter.
+    pub fn builtin(chapter: i32) -> Option<Self> {
+        let content = BUILTIN_CHECKLISTS.get(usize::try_from(chapter).ok()?.checked_sub(1)?)?;
+        Some(Checklist::parse(content).expect("built-in checklists are valid"))
+    }
+
+    pub fn check(&self, save: &SaveData) -> ChecklistReport {
+        ChecklistReport {
+            chapter: self.chapter,
+            entries: self
+                .entries
+                .iter()
+                .map(|entry| EntryStatus {
+                    category: entry.category.clone(),
+                    name: entry.name.clone(),
+                    done: entry.requirement.is_met(save),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn parse_entry(index: usize, entry: EntryFile) -> Result<ChecklistEntry, ChecklistError> {
+    let unknown_name = |source| ChecklistError::UnknownName { index, source };
+    let EntryFile {
+        category,
+        name,
+        flag,
+        weapon,
+        armor,
+        key_item,
+        item,
+        query,
+    } = entry;
+
+    let (requirement, default_name) = match (flag, weapon, armor, key_item, item, query) {
+        (Some(flag), None, None, None, None, None) => {
+            let flag = flag.parse::<Flag>().map_err(unknown_name)?;
+            (Requirement::Flag(flag), flag.to_compact_string())
+        }
+        (None, Some(weapon), None, None, None, None) => {
+            let weapon = weapon.parse::<WeaponId>().map_err(unknown_name)?;
+            (Requirement::Weapon(weapon), weapon.to_compact_string())
+        }
+        (None, None, Some(armor), None, None, None) => {
+            let armor = armor.parse::<ArmorId>().map_err(unknown_name)?;
+            (Requirement::Armor(armor), armor.to_compact_string())
+        }
+        (None, None, None, Some(key_item), None, None) => {
+            let key_item = key_item.parse::<KeyItemId>().map_err(unknown_name)?;
+            (Requirement::KeyItem(key_item), key_item.to_compact_string())
+        }
+        (None, None, None, None, Some(item), None) => {
+            let item = item.parse::<ItemId>().map_err(unknown_name)?;
+            (Requirement::Item(item), item.to_compact_string())
+        }
+        (None, None, None, None, None, Some(query)) => {
+            let expr =
+                Expr::parse(&query).map_err(|source| ChecklistError::Query { index, source })?;
+            (Requirement::Query(expr), query.into())
+        }
+        _ => return Err(ChecklistError::InvalidEntry { index }),
+    };
+
+    Ok(ChecklistEntry {
+        category: category.into(),
+        name: name.map_or(default_name, CompactString::from),
+        requirement,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct EntryStatus {
+    pub category: CompactString,
+    pub name: CompactString,
+    pub done: bool,
+}
+
+/// Which entries of a checklist a save has done.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ChecklistReport {
+    pub chapter: i32,
+    pub entries: Vec<EntryStatus>,
+}
+
+impl ChecklistReport {
+    pub fn done_count(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.done).count()
+    }
+
+    /// Share of entries done, from 0 to 100.
+    pub fn percentage(&self) -> f64 {
+        if self.entries.is_empty() {
+            return 100.0;
+        }
+        self.done_count() as f64 * 100.0 / self.entries.len() as f64
+    }
+
+    pub fn missing(&self) -> impl Iterator<Item = &EntryStatus> {
+        self.entries.iter().filter(|entry| !entry.done)
+    }
+}
+
+impl Display for ChecklistReport {
+    /// Lists the entries by category, in definition order.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Chapter {} checklist: {}/{} ({:.0}%)",
+            self.chapter,
+            self.done_count(),
+            self.entries.len(),
+            self.percentage()
+        )?;
+
+        let mut categories: Vec<&str> = Vec::new();
+        for entry in &self.entries {
+            if !categories.contains(&entry.category.as_str()) {
+                categories.push(&entry.category);
+            }
+        }
+
+        for category in categories {
+            let entries = self.entries.iter().filter(|entry| entry.category == category);
+            let done = entries.clone().filter(|entry| entry.done).count();
+            writeln!(f, "---------------")?;
+            writeln!(f, "{} ({}/{}):", category, done, entries.clone().count())?;
+            for entry in entries {
+                writeln!(f, "[{}] {}", if entry.done { "x" } else { " " }, entry.name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_checklists() {
+        for chapter in 1..=4 {
+            let checklist = Checklist::builtin(chapter).unwrap();
+            assert_eq!(checklist.chapter, chapter);
+        }
+        assert_eq!(Checklist::builtin(5), None);
+    }
+
+    #[test]
+    fn test_check() {
+        let lines = vec!["0"; 3055];
+        let mut save = SaveData::read(2, &lines).unwrap();
+        save.stats[0].weapon = WeaponId(16); // MechaSaber
+        save.flags[Flag::ShadowCrystalCh2.index()].set(1.0);
+
+        let checklist = Checklist::builtin(2).unwrap();
+        let report = checklist.check(&save);
+        assert_eq!(report.done_count(), 2);
+        assert!(report.missing().any(|entry| entry.name == "Spamton NEO"));
+        assert!(report.to_string().contains("[x] MechaSaber\n"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let checklist = Checklist::parse(indoc::indoc! {r#"
+            chapter = 1
+
+            [[entries]]
+            category = "Weapons"
+            weapon = "Wood Blade"
+            armor = "Amber Card"
+        "#});
+        assert!(matches!(checklist, Err(ChecklistError::InvalidEntry { index: 0 })));
+
+        let checklist = Checklist::parse(indoc::indoc! {r#"
+            chapter = 1
+
+            [[entries]]
+            category = "Weapons"
+            weapon = "Butter Knife"
+        "#});
+        assert!(matches!(checklist, Err(ChecklistError::UnknownName { index: 0, .. })));
+    }
+}
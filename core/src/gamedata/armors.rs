@@ -1,7 +1,17 @@
+use super::equipment::{EVERYONE, KRIS, NOELLE, RALSEI, StatBonus, SUSIE};
+use super::party_members::PartyMember;
+use super::registry::{Table, registry};
+use super::{GameContext, LookupError};
 use compact_str::{CompactString, ToCompactString, format_compact};
 
-// Extracted from gml_GlobalScript_scr_armorinfo using UndertaleModTool
 pub fn try_get_armor_name(armor_id: i32) -> Option<&'static str> {
+    registry()
+        .name(Table::Armors, armor_id)
+        .or_else(|| builtin_armor_name(armor_id))
+}
+
+// Extracted from gml_GlobalScript_scr_armorinfo using UndertaleModTool
+pub(super) fn builtin_armor_name(armor_id: i32) -> Option<&'static str> {
     match armor_id {
         0 => Some("---"),
         1 => Some("Amber Card"),
@@ -40,9 +50,336 @@ pub fn try_get_armor_name(armor_id: i32) -> Option<&'static str> {
     }
 }
 
+/// The chapter a armor first appears in.
+fn armor_chapter(armor_id: i32) -> i32 {
+    if let Some(chapter) = registry().chapter(Table::Armors, armor_id) {
+        return chapter;
+    }
+
+    match armor_id {
+        8..=22 => 2,
+        23..=27 => 3,
+        50.. => 4,
+        _ => 1,
+    }
+}
+
+pub fn try_get_armor_name_in(
+    armor_id: i32,
+    context: &GameContext,
+) -> Result<&'static str, LookupError> {
+    let name = try_get_armor_name(armor_id);
+    context.check(ArmorId::KIND, armor_id, name, armor_chapter(armor_id))
+}
+
 pub fn display_armor(armor_id: i32) -> CompactString {
     let armor_name = try_get_armor_name(armor_id);
     armor_name
         .map(|n| n.to_compact_string())
         .unwrap_or_else(|| format_compact!("Armor {}", armor_id))
 }
+
+id_type!(
+    /// A dark world armor.
+    ArmorId,
+    "armor",
+    try_get_armor_name,
+    try_get_armor_name_in,
+    display_armor
+);
+
+impl ArmorId {
+    /// Returns the armor's metadata, or `None` for empty slots and armors without
+    /// known metadata.
+    pub fn info(self) -> Option<&'static ArmorInfo> {
+        try_get_armor_info(self.0)
+    }
+
+    /// Stat changes applied while this armor is equipped.
+    pub fn bonus(self) -> StatBonus {
+        self.info().map(|info| info.bonus).unwrap_or_default()
+    }
+
+    /// Whether the given party member is willing to wear this armor.
+    pub fn can_equip(self, member: PartyMember) -> bool {
+        self.is_empty() || self.info().is_some_and(|info| info.equippable_by.contains(&member))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ArmorInfo {
+    pub id: i32,
+    pub bonus: StatBonus,
+    pub equippable_by: &'static [PartyMember],
+    /// Shop value of the armor, shops buy it back for half of it.
+    pub value: i32,
+    /// Passive ability shown in the equip menu.
+    pub ability: Option<&'static str>,
+    pub description: &'static str,
+}
+
+pub fn try_get_armor_info(armor_id: i32) -> Option<&'static ArmorInfo> {
+    ARMORS.iter().find(|info| info.id == armor_id)
+}
+
+// Stats and descriptions entered by hand from the in-game equip and shop menus. They haven't been
+// checked against a dump of scr_armorinfo yet. `test_tables_match_info_scripts` checks them once
+// the dumps are in `research/iteminfo`.
+static ARMORS: &[ArmorInfo] = &[
+    ArmorInfo {
+        id: 1,
+        bonus: StatBonus::new(0, 1, 0),
+        equippable_by: EVERYONE,
+        value: 50,
+        ability: None,
+        description: "A thin square charm that sticks to you, increasing defense.",
+    },
+    ArmorInfo {
+        id: 2,
+        bonus: StatBonus::new(0, 1, 0),
+        equippable_by: EVERYONE,
+        value: 50,
+        ability: None,
+        description: "A bracelet with dice on it. Not very lucky.",
+    },
+    ArmorInfo {
+        id: 3,
+        bonus: StatBonus::new(0, 1, 0),
+        equippable_by: &[KRIS, RALSEI, NOELLE],
+        value: 100,
+        ability: Some("Graze area +"),
+        description: "A cute hair ribbon that widens your graze area.",
+    },
+    ArmorInfo {
+        id: 4,
+        bonus: StatBonus::new(0, 2, 0),
+        equippable_by: &[KRIS, RALSEI, NOELLE],
+        value: 100,
+        ability: None,
+        description: "A crinkly hair ribbon that slightly increases defense.",
+    },
+    ArmorInfo {
+        id: 5,
+        bonus: StatBonus::new(1, 2, 0),
+        equippable_by: &[KRIS, SUSIE],
+        value: 100,
+        ability: None,
+        description: "Heavy iron shackles that protect the wearer.",
+    },
+    ArmorInfo {
+        id: 6,
+        bonus: StatBonus::new(0, 2, 1),
+        equippable_by: EVERYONE,
+        value: 100,
+        ability: None,
+        description: "A golden token in the shape of a mouse.",
+    },
+    ArmorInfo {
+        id: 7,
+        bonus: StatBonus::new(2, 2, 2),
+        equippable_by: EVERYONE,
+        value: 0,
+        ability: None,
+        description: "A jester's tail that sharpens every stat.",
+    },
+    ArmorInfo {
+        id: 8,
+        bonus: StatBonus::new(0, 2, 0),
+        equippable_by: EVERYONE,
+        value: 200,
+        ability: Some("$ +5%"),
+        description: "A shiny card that attracts money.",
+    },
+    ArmorInfo {
+        id: 9,
+        bonus: StatBonus::new(0, 3, 0),
+        equippable_by: &[KRIS, RALSEI, NOELLE],
+        value: 300,
+        ability: Some("Graze area +"),
+        description: "A pair of ribbons that widen your graze area.",
+    },
+    ArmorInfo {
+        id: 10,
+        bonus: StatBonus::new(0, 2, 0),
+        equippable_by: EVERYONE,
+        value: 150,
+        ability: None,
+        description: "A wristband that glows in the dark.",
+    },
+    ArmorInfo {
+        id: 11,
+        bonus: StatBonus::new(0, 3, 0),
+        equippable_by: EVERYONE,
+        value: 250,
+        ability: None,
+        description: "Armor made of interlocking rings.",
+    },
+    ArmorInfo {
+        id: 12,
+        bonus: StatBonus::new(0, 2, 1),
+        equippable_by: &[KRIS, RALSEI, NOELLE],
+        value: 250,
+        ability: None,
+        description: "A bowtie that lets you shoot a bit faster.",
+    },
+    ArmorInfo {
+        id: 13,
+        bonus: StatBonus::new(2, 1, 0),
+        equippable_by: EVERYONE,
+        value: 250,
+        ability: None,
+        description: "A band covered in spikes.",
+    },
+    ArmorInfo {
+        id: 14,
+        bonus: StatBonus::new(0, 2, 0),
+        equippable_by: EVERYONE,
+        value: 300,
+        ability: Some("Graze time +"),
+        description: "A watch that makes grazing last longer.",
+    },
+    ArmorInfo {
+        id: 15,
+        bonus: StatBonus::new(0, 2, 0),
+        equippable_by: EVERYONE,
+        value: 300,
+        ability: Some("TP +"),
+        description: "A bow that charges tension faster.",
+    },
+    ArmorInfo {
+        id: 16,
+        bonus: StatBonus::new(0, 0, 0),
+        equippable_by: &[RALSEI],
+        value: 0,
+        ability: None,
+        description: "A stylish mannequin. Ralsei insists on wearing it.",
+    },
+    ArmorInfo {
+        id: 17,
+        bonus: StatBonus::new(0, 2, 0),
+        equippable_by: EVERYONE,
+        value: 0,
+        ability: None,
+        description: "A band of dark gold.",
+    },
+    ArmorInfo {
+        id: 18,
+        bonus: StatBonus::new(0, 1, 1),
+        equippable_by: &[KRIS, RALSEI, NOELLE],
+        value: 0,
+        ability: Some("Grazing"),
+        description: "A light mantle that makes you nimble.",
+    },
+    ArmorInfo {
+        id: 19,
+        bonus: StatBonus::new(3, 3, 0),
+        equippable_by: &[KRIS, SUSIE],
+        value: 0,
+        ability: None,
+        description: "Spiked shackles for the fearless.",
+    },
+    ArmorInfo {
+        id: 20,
+        bonus: StatBonus::new(0, 1, 1),
+        equippable_by: EVERYONE,
+        value: 0,
+        ability: None,
+        description: "A worn bowtie with a faint fragrance.",
+    },
+    ArmorInfo {
+        id: 21,
+        bonus: StatBonus::new(0, 5, 5),
+        equippable_by: EVERYONE,
+        value: 0,
+        ability: Some("$ +30%"),
+        description: "Glasses that make every deal a big one.",
+    },
+    ArmorInfo {
+        id: 22,
+        bonus: StatBonus::new(0, 3, 1),
+        equippable_by: EVERYONE,
+        value: 0,
+        ability: None,
+        description: "A pin that belonged to royalty.",
+    },
+    ArmorInfo {
+        id: 23,
+        bonus: StatBonus::new(0, 4, 0),
+        equippable_by: EVERYONE,
+        value: 0,
+        ability: None,
+        description: "A mantle woven from shadows.",
+    },
+    ArmorInfo {
+        id: 24,
+        bonus: StatBonus::new(0, 3, 0),
+        equippable_by: EVERYONE,
+        value: 0,
+        ability: None,
+        description: "A heavy magnetic stone.",
+    },
+    ArmorInfo {
+        id: 25,
+        bonus: StatBonus::new(0, 3, 0),
+        equippable_by: EVERYONE,
+        value: 0,
+        ability: None,
+        description: "A gingerbread shield. Smells nice.",
+    },
+    ArmorInfo {
+        id: 26,
+        bonus: StatBonus::new(0, 3, 1),
+        equippable_by: &[KRIS, RALSEI, NOELLE],
+        value: 0,
+        ability: None,
+        description: "A ribbon as blue as the sky.",
+    },
+    ArmorInfo {
+        id: 27,
+        bonus: StatBonus::new(0, 3, 0),
+        equippable_by: EVERYONE,
+        value: 0,
+        ability: None,
+        description: "A flashy tie from the TV world.",
+    },
+    ArmorInfo {
+        id: 50,
+        bonus: StatBonus::new(0, 4, 0),
+        equippable_by: EVERYONE,
+        value: 0,
+        ability: None,
+        description: "A wafer-thin guard. Surprisingly sturdy.",
+    },
+    ArmorInfo {
+        id: 51,
+        bonus: StatBonus::new(0, 2, 3),
+        equippable_by: EVERYONE,
+        value: 0,
+        ability: None,
+        description: "A band that hums with magic.",
+    },
+    ArmorInfo {
+        id: 52,
+        bonus: StatBonus::new(2, 2, 0),
+        equippable_by: EVERYONE,
+        value: 0,
+        ability: None,
+        description: "A band that makes you feel strong.",
+    },
+    ArmorInfo {
+        id: 53,
+        bonus: StatBonus::new(0, 3, 2),
+        equippable_by: &[KRIS, RALSEI, NOELLE],
+        value: 0,
+        ability: None,
+        description: "A ribbon fit for a princess.",
+    },
+    ArmorInfo {
+        id: 54,
+        bonus: StatBonus::new(0, 3, 0),
+        equippable_by: EVERYONE,
+        value: 0,
+        ability: None,
+        description: "A widow's golden ring.",
+    },
+];
@@ -0,0 +1,51 @@
+//! Data shared by the weapon and armor tables.
+
+use super::party_members::PartyMember;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, AddAssign};
+
+/// Stat changes applied while a piece of equipment is worn.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct StatBonus {
+    pub attack: i32,
+    pub defense: i32,
+    pub magic: i32,
+}
+
+impl StatBonus {
+    pub const fn new(attack: i32, defense: i32, magic: i32) -> Self {
+        StatBonus { attack, defense, magic }
+    }
+}
+
+impl Display for StatBonus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AT {} DF {} MG {}", self.attack, self.defense, self.magic)
+    }
+}
+
+impl Add for StatBonus {
+    type Output = StatBonus;
+
+    fn add(self, other: StatBonus) -> StatBonus {
+        StatBonus::new(
+            self.attack + other.attack,
+            self.defense + other.defense,
+            self.magic + other.magic,
+        )
+    }
+}
+
+impl AddAssign for StatBonus {
+    fn add_assign(&mut self, other: StatBonus) {
+        *self = *self + other;
+    }
+}
+
+pub(crate) const KRIS: PartyMember = PartyMember::KRIS;
+pub(crate) const SUSIE: PartyMember = PartyMember::SUSIE;
+pub(crate) const RALSEI: PartyMember = PartyMember::RALSEI;
+pub(crate) const NOELLE: PartyMember = PartyMember::NOELLE;
+
+/// Everyone who can join the party.
+pub(crate) const EVERYONE: &[PartyMember] = &[KRIS, SUSIE, RALSEI, NOELLE];
@@ -0,0 +1,220 @@
+//! Known entries of `global.flag`, the big array of story and progress flags saved by the game.
+
+use super::UnknownNameError;
+use crate::real::GmReal;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Flag {
+    JevilFight,
+    SpamtonNeoFight,
+    WeirdRouteProgress,
+    LancerConCount,
+    ShadowCrystalCh1,
+    ShadowCrystalCh2,
+    ShadowCrystalCh3,
+    ShadowCrystalCh4,
+}
+
+/// How a flag's value should be read.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FlagKind {
+    /// 0 or 1.
+    Bool,
+    /// A whole number of something.
+    Count,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FlagInfo {
+    pub flag: Flag,
+    pub index: usize,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub kind: FlagKind,
+    /// The chapter that sets this flag. Later chapters carry it over.
+    pub chapter: i32,
+}
+
+static FLAGS: &[FlagInfo] = &[
+    FlagInfo {
+        flag: Flag::JevilFight,
+        index: 241,
+        name: "jevil_fight",
+        description: "How the fight with Jevil ended, 0 if it didn't happen",
+        kind: FlagKind::Count,
+        chapter: 1,
+    },
+    FlagInfo {
+        flag: Flag::SpamtonNeoFight,
+        index: 309,
+        name: "spamton_neo_fight",
+        description: "How the fight with Spamton NEO ended, 0 if it didn't happen",
+        kind: FlagKind::Count,
+        chapter: 2,
+    },
+    FlagInfo {
+        flag: Flag::WeirdRouteProgress,
+        index: 915,
+        name: "weird_route_progress",
+        description: "Progress of the Snowgrave route",
+        kind: FlagKind::Count,
+        chapter: 2,
+    },
+    FlagInfo {
+        flag: Flag::LancerConCount,
+        index: 1099,
+        name: "lancer_con_count",
+        description: "Number of LancerCon key items held",
+        kind: FlagKind::Count,
+        chapter: 3,
+    },
+    FlagInfo {
+        flag: Flag::ShadowCrystalCh1,
+        index: 1646,
+        name: "shadow_crystal_ch1",
+        description: "Got the chapter 1 ShadowCrystal",
+        kind: FlagKind::Bool,
+        chapter: 1,
+    },
+    FlagInfo {
+        flag: Flag::ShadowCrystalCh2,
+        index: 1647,
+        name: "shadow_crystal_ch2",
+        description: "Got the chapter 2 ShadowCrystal",
+        kind: FlagKind::Bool,
+        chapter: 2,
+    },
+    FlagInfo {
+        flag: Flag::ShadowCrystalCh3,
+        index: 1648,
+        name: "shadow_crystal_ch3",
+        description: "Got the chapter 3 ShadowCrystal",
+        kind: FlagKind::Bool,
+        chapter: 3,
+    },
+    FlagInfo {
+        flag: Flag::ShadowCrystalCh4,
+        index: 1649,
+        name: "shadow_crystal_ch4",
+        description: "Got the chapter 4 ShadowCrystal",
+        kind: FlagKind::Bool,
+        chapter: 4,
+    },
+];
+
+/// Flags counted by `scr_get_total_shadow_crystal_amount`, in chapter order.
+pub const SHADOW_CRYSTALS: [Flag; 4] = [
+    Flag::ShadowCrystalCh1,
+    Flag::ShadowCrystalCh2,
+    Flag::ShadowCrystalCh3,
+    Flag::ShadowCrystalCh4,
+];
+
+impl Flag {
+    pub const KIND: &'static str = "flag";
+
+    pub fn info(self) -> &'static FlagInfo {
+        FLAGS
+            .iter()
+            .find(|info| info.flag == self)
+            .expect("every flag has an entry in FLAGS")
+    }
+
+    pub fn index(self) -> usize {
+        self.info().index
+    }
+
+    pub fn name(self) -> &'static str {
+        self.info().name
+    }
+
+    /// Reads the flag from a save's raw flag values.
+    pub fn read(self, flags: &[GmReal]) -> FlagValue {
+        let info = self.info();
+        let value = flags.get(info.index).map_or(0.0, GmReal::value);
+        match info.kind {
+            FlagKind::Bool => FlagValue::Bool(value != 0.0),
+            FlagKind::Count => FlagValue::Count(value as i32),
+        }
+    }
+}
+
+/// Returns what is known about the flag at an index.
+pub fn try_get_flag_info(index: usize) -> Option<&'static FlagInfo> {
+    FLAGS.iter().find(|info| info.index == index)
+}
+
+/// All known flags, ordered by index.
+pub fn known_flags() -> &'static [FlagInfo] {
+    FLAGS
+}
+
+impl Display for Flag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Flag {
+    type Err = UnknownNameError;
+
+    /// Parses a flag name like `shadow_crystal_ch1`, or a flag index.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let info = match s.parse::<usize>() {
+            Ok(index) => try_get_flag_info(index),
+            Err(_) => FLAGS.iter().find(|info| info.name.eq_ignore_ascii_case(s)),
+        };
+
+        info.map(|info| info.flag).ok_or_else(|| UnknownNameError {
+            kind: Self::KIND,
+            name: s.into(),
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FlagValue {
+    Bool(bool),
+    Count(i32),
+}
+
+impl FlagValue {
+    /// Whether the flag holds anything other than its default.
+    pub fn is_set(self) -> bool {
+        match self {
+            FlagValue::Bool(value) => value,
+            FlagValue::Count(value) => value != 0,
+        }
+    }
+}
+
+impl Display for FlagValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FlagValue::Bool(value) => write!(f, "{value}"),
+            FlagValue::Count(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags() {
+        let mut flags = vec![GmReal::ZERO; 2500];
+        flags[1647].set(1.0);
+        flags[1099].set(2.0);
+
+        assert_eq!(Flag::ShadowCrystalCh1.read(&flags), FlagValue::Bool(false));
+        assert_eq!(Flag::ShadowCrystalCh2.read(&flags), FlagValue::Bool(true));
+        assert_eq!(Flag::LancerConCount.read(&flags), FlagValue::Count(2));
+        assert_eq!("Shadow_Crystal_Ch2".parse(), Ok(Flag::ShadowCrystalCh2));
+        assert_eq!("1099".parse(), Ok(Flag::LancerConCount));
+        assert!(known_flags().is_sorted_by_key(|info| info.index));
+    }
+}
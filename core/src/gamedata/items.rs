@@ -1,7 +1,15 @@
+use super::registry::{Table, registry};
+use super::{GameContext, LookupError};
 use compact_str::{CompactString, ToCompactString, format_compact};
 
-// Extracted from gml_GlobalScript_scr_iteminfo using UndertaleModTool
 pub fn try_get_item_name(item_id: i32) -> Option<&'static str> {
+    registry()
+        .name(Table::Items, item_id)
+        .or_else(|| builtin_item_name(item_id))
+}
+
+// Extracted from gml_GlobalScript_scr_iteminfo using UndertaleModTool
+pub(super) fn builtin_item_name(item_id: i32) -> Option<&'static str> {
     match item_id {
         0 => Some("---"),
         1 => Some("Dark Candy"),
@@ -51,9 +59,389 @@ pub fn try_get_item_name(item_id: i32) -> Option<&'static str> {
     }
 }
 
+/// The chapter a item first appears in.
+fn item_chapter(item_id: i32) -> i32 {
+    if let Some(chapter) = registry().chapter(Table::Items, item_id) {
+        return chapter;
+    }
+
+    match item_id {
+        16..=33 => 2,
+        34..=39 => 3,
+        60.. => 4,
+        _ => 1,
+    }
+}
+
+pub fn try_get_item_name_in(
+    item_id: i32,
+    context: &GameContext,
+) -> Result<&'static str, LookupError> {
+    let name = try_get_item_name(item_id);
+    context.check(ItemId::KIND, item_id, name, item_chapter(item_id))
+}
+
 pub fn display_item(item_id: i32) -> CompactString {
     let item_name = try_get_item_name(item_id);
     item_name
         .map(|n| n.to_compact_string())
         .unwrap_or_else(|| format_compact!("Item {}", item_id))
 }
+
+id_type!(
+    /// An item in the dark world inventory or storage.
+    ItemId,
+    "item",
+    try_get_item_name,
+    try_get_item_name_in,
+    display_item
+);
+
+impl ItemId {
+    /// Returns the item's metadata, or `None` for empty slots and unknown items.
+    pub fn info(self) -> Option<&'static ItemInfo> {
+        try_get_item_info(self.0)
+    }
+}
+
+/// What using an item does.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ItemEffect {
+    None,
+    /// Restores HP. Some items heal differently depending on who eats them, this is the base.
+    Heal(i32),
+    /// Revives fallen party members with a percentage of their max HP.
+    Revive(i32),
+    /// Raises TP by a percentage.
+    Tension(i32),
+}
+
+/// Who an item can be used on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ItemTarget {
+    /// Can't be used from the menu.
+    None,
+    Ally,
+    Party,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ItemInfo {
+    pub id: i32,
+    pub effect: ItemEffect,
+    pub target: ItemTarget,
+    /// Shop value of the item, shops buy it back for half of it.
+    pub value: i32,
+    pub description: &'static str,
+}
+
+pub fn try_get_item_info(item_id: i32) -> Option<&'static ItemInfo> {
+    ITEMS.iter().find(|info| info.id == item_id)
+}
+
+// Effects and descriptions entered by hand from the in-game item and shop menus. They haven't
+// been checked against a dump of scr_iteminfo yet. `test_tables_match_info_scripts` checks them
+// once the dumps are in `research/iteminfo`.
+static ITEMS: &[ItemInfo] = &[
+    ItemInfo {
+        id: 1,
+        effect: ItemEffect::Heal(40),
+        target: ItemTarget::Ally,
+        value: 25,
+        description: "Heals 40HP. A red-and-black star that tastes like marshmallows.",
+    },
+    ItemInfo {
+        id: 2,
+        effect: ItemEffect::Revive(100),
+        target: ItemTarget::Ally,
+        value: 200,
+        description: "Heals a fallen ally to MAX HP. A minty green crystal.",
+    },
+    ItemInfo {
+        id: 3,
+        effect: ItemEffect::None,
+        target: ItemTarget::None,
+        value: 150,
+        description: "A shimmering shard. Its value increases each Chapter.",
+    },
+    ItemInfo {
+        id: 4,
+        effect: ItemEffect::None,
+        target: ItemTarget::None,
+        value: 0,
+        description: "Ralsei's handmade book full of how-to's on battle.",
+    },
+    ItemInfo {
+        id: 5,
+        effect: ItemEffect::Heal(20),
+        target: ItemTarget::Ally,
+        value: 0,
+        description: "Heals 20HP. Broken cake. Slightly squashed.",
+    },
+    ItemInfo {
+        id: 6,
+        effect: ItemEffect::Heal(160),
+        target: ItemTarget::Party,
+        value: 0,
+        description: "Heals the team 160HP. This cake will make your taste buds spin!",
+    },
+    ItemInfo {
+        id: 7,
+        effect: ItemEffect::Heal(80),
+        target: ItemTarget::Party,
+        value: 0,
+        description: "Heals the team 80HP. A cake that spins. Tasty, but dizzying.",
+    },
+    ItemInfo {
+        id: 8,
+        effect: ItemEffect::Heal(70),
+        target: ItemTarget::Ally,
+        value: 70,
+        description: "Heals 70HP. A dark, mysterious burger.",
+    },
+    ItemInfo {
+        id: 9,
+        effect: ItemEffect::Heal(50),
+        target: ItemTarget::Ally,
+        value: 10,
+        description: "Heals 50HP. A cookie shaped like Lancer's face.",
+    },
+    ItemInfo {
+        id: 10,
+        effect: ItemEffect::Heal(90),
+        target: ItemTarget::Ally,
+        value: 120,
+        description: "Heals 90HP. An enormous salad. Well, it's healthy.",
+    },
+    ItemInfo {
+        id: 11,
+        effect: ItemEffect::Heal(160),
+        target: ItemTarget::Party,
+        value: 0,
+        description: "Heals the team 160HP. A sandwich that can be split into thirds.",
+    },
+    ItemInfo {
+        id: 12,
+        effect: ItemEffect::Heal(30),
+        target: ItemTarget::Ally,
+        value: 80,
+        description: "Heals ??HP. Hearts, don't it? Its taste varies by who eats it.",
+    },
+    ItemInfo {
+        id: 13,
+        effect: ItemEffect::Heal(80),
+        target: ItemTarget::Ally,
+        value: 120,
+        description: "Heals 80HP. A diamond-shaped chocolate.",
+    },
+    ItemInfo {
+        id: 14,
+        effect: ItemEffect::Heal(500),
+        target: ItemTarget::Ally,
+        value: 0,
+        description: "Heals 500HP. Someone's favorite sandwich.",
+    },
+    ItemInfo {
+        id: 15,
+        effect: ItemEffect::Heal(50),
+        target: ItemTarget::Ally,
+        value: 0,
+        description: "Heals 50HP. A creamy sauce made by Rouxls.",
+    },
+    ItemInfo {
+        id: 16,
+        effect: ItemEffect::Heal(80),
+        target: ItemTarget::Ally,
+        value: 60,
+        description: "Heals 80HP. A bagel with a musical taste.",
+    },
+    ItemInfo {
+        id: 17,
+        effect: ItemEffect::None,
+        target: ItemTarget::None,
+        value: 0,
+        description: "A mannequin. Looks like it could be worn.",
+    },
+    ItemInfo {
+        id: 18,
+        effect: ItemEffect::Heal(50),
+        target: ItemTarget::Ally,
+        value: 0,
+        description: "Heals 50HP. Tea brewed by Kris. It's their favorite.",
+    },
+    ItemInfo {
+        id: 19,
+        effect: ItemEffect::Heal(50),
+        target: ItemTarget::Ally,
+        value: 0,
+        description: "Heals 50HP. Tea brewed by Noelle. It's her favorite.",
+    },
+    ItemInfo {
+        id: 20,
+        effect: ItemEffect::Heal(50),
+        target: ItemTarget::Ally,
+        value: 0,
+        description: "Heals 50HP. Tea brewed by Ralsei. It's his favorite.",
+    },
+    ItemInfo {
+        id: 21,
+        effect: ItemEffect::Heal(50),
+        target: ItemTarget::Ally,
+        value: 0,
+        description: "Heals 50HP. Tea brewed by Susie. It's her favorite.",
+    },
+    ItemInfo {
+        id: 22,
+        effect: ItemEffect::Heal(60),
+        target: ItemTarget::Ally,
+        value: 100,
+        description: "Heals 60HP, twice. Double-decker, double the fun.",
+    },
+    ItemInfo {
+        id: 23,
+        effect: ItemEffect::Heal(120),
+        target: ItemTarget::Ally,
+        value: 100,
+        description: "Heals 120HP. A white candy that glows softly.",
+    },
+    ItemInfo {
+        id: 24,
+        effect: ItemEffect::Heal(100),
+        target: ItemTarget::Ally,
+        value: 100,
+        description: "Heals 100HP. A juice with a questionable name.",
+    },
+    ItemInfo {
+        id: 25,
+        effect: ItemEffect::Heal(30),
+        target: ItemTarget::Party,
+        value: 60,
+        description: "Heals the team 30HP. Spaghetti written in code.",
+    },
+    ItemInfo {
+        id: 26,
+        effect: ItemEffect::Heal(100),
+        target: ItemTarget::Ally,
+        value: 200,
+        description: "Heals 100HP. A cookie shaped like a coffee cup.",
+    },
+    ItemInfo {
+        id: 27,
+        effect: ItemEffect::Tension(32),
+        target: ItemTarget::Party,
+        value: 50,
+        description: "Raises TP by 32%.",
+    },
+    ItemInfo {
+        id: 28,
+        effect: ItemEffect::Tension(50),
+        target: ItemTarget::Party,
+        value: 100,
+        description: "Raises TP by 50%.",
+    },
+    ItemInfo {
+        id: 29,
+        effect: ItemEffect::Tension(100),
+        target: ItemTarget::Party,
+        value: 200,
+        description: "Raises TP to the max.",
+    },
+    ItemInfo {
+        id: 30,
+        effect: ItemEffect::Revive(25),
+        target: ItemTarget::Party,
+        value: 120,
+        description: "Revives all fallen allies with 25% HP.",
+    },
+    ItemInfo {
+        id: 31,
+        effect: ItemEffect::Revive(100),
+        target: ItemTarget::Party,
+        value: 250,
+        description: "Revives all fallen allies with MAX HP.",
+    },
+    ItemInfo {
+        id: 32,
+        effect: ItemEffect::Heal(-50),
+        target: ItemTarget::Ally,
+        value: 0,
+        description: "Hurts 50HP. Tastes of poison.",
+    },
+    ItemInfo {
+        id: 33,
+        effect: ItemEffect::None,
+        target: ItemTarget::None,
+        value: 0,
+        description: "A dollar with a dog on it. Worth something to someone.",
+    },
+    ItemInfo {
+        id: 34,
+        effect: ItemEffect::Heal(80),
+        target: ItemTarget::Party,
+        value: 0,
+        description: "Heals the team 80HP. A dinner served on a TV tray.",
+    },
+    ItemInfo {
+        id: 35,
+        effect: ItemEffect::None,
+        target: ItemTarget::None,
+        value: 0,
+        description: "It's not an egg.",
+    },
+    ItemInfo {
+        id: 36,
+        effect: ItemEffect::Heal(1),
+        target: ItemTarget::Ally,
+        value: 0,
+        description: "Heals 1HP. A soda that lost its fizz.",
+    },
+    ItemInfo {
+        id: 37,
+        effect: ItemEffect::Heal(40),
+        target: ItemTarget::Ally,
+        value: 0,
+        description: "Heals 40HP. Slop from the TV world.",
+    },
+    ItemInfo {
+        id: 38,
+        effect: ItemEffect::Heal(200),
+        target: ItemTarget::Party,
+        value: 0,
+        description: "Heals the team 200HP. An executive buffet.",
+    },
+    ItemInfo {
+        id: 39,
+        effect: ItemEffect::Heal(150),
+        target: ItemTarget::Party,
+        value: 0,
+        description: "Heals the team 150HP. A deluxe dinner.",
+    },
+    ItemInfo {
+        id: 60,
+        effect: ItemEffect::Heal(150),
+        target: ItemTarget::Ally,
+        value: 0,
+        description: "Heals 150HP. A sweet from an ancient age.",
+    },
+    ItemInfo {
+        id: 61,
+        effect: ItemEffect::Heal(100),
+        target: ItemTarget::Party,
+        value: 0,
+        description: "Heals the team 100HP. A tea that sings.",
+    },
+    ItemInfo {
+        id: 62,
+        effect: ItemEffect::Heal(200),
+        target: ItemTarget::Ally,
+        value: 0,
+        description: "Heals 200HP. A crimson elixir.",
+    },
+    ItemInfo {
+        id: 63,
+        effect: ItemEffect::Heal(150),
+        target: ItemTarget::Ally,
+        value: 0,
+        description: "Heals 150HP. A tear that tastes bitter.",
+    },
+];
@@ -1,7 +1,15 @@
+use super::registry::{Table, registry};
+use super::{GameContext, LookupError};
 use compact_str::{CompactString, ToCompactString, format_compact};
 
-// Extracted from gml_GlobalScript_scr_keyiteminfo using UndertaleModTool
 pub fn try_get_key_item_name(key_item_id: i32) -> Option<&'static str> {
+    registry()
+        .name(Table::KeyItems, key_item_id)
+        .or_else(|| builtin_key_item_name(key_item_id))
+}
+
+// Extracted from gml_GlobalScript_scr_keyiteminfo using UndertaleModTool
+pub(super) fn builtin_key_item_name(key_item_id: i32) -> Option<&'static str> {
     match key_item_id {
         0 => Some("---"),
         1 => Some("Cell Phone"),
@@ -17,24 +25,54 @@ pub fn try_get_key_item_name(key_item_id: i32) -> Option<&'static str> {
         11 => Some("LoadedDisk"),
         12 => Some("KeyGen"),
         // Amount counted using scr_get_total_shadow_crystal_amount(),
-        // counting flags set to one: 1646, 1647, 1648, 1649, up to
-        // the current chapter.
+        // see SaveData::shadow_crystals().
         13 => Some("ShadowCrystal"),
         14 => Some("Starwalker"),
         15 => Some("PureCrystal"),
         16 => Some("OddController"),
         17 => Some("BackstagePass"),
         18 => Some("TripTicket"),
-        19 => Some("LancerCon"), // Amount in global flag 1099
+        19 => Some("LancerCon"), // Amount in Flag::LancerConCount
         30 => Some("SheetMusic"),
         31 => Some("ClaimbClaws"),
         _ => None,
     }
 }
 
+/// The chapter a key item first appears in.
+fn key_item_chapter(key_item_id: i32) -> i32 {
+    if let Some(chapter) = registry().chapter(Table::KeyItems, key_item_id) {
+        return chapter;
+    }
+
+    match key_item_id {
+        8..=12 | 14 | 15 => 2,
+        16..=19 => 3,
+        30.. => 4,
+        _ => 1,
+    }
+}
+
+pub fn try_get_key_item_name_in(
+    key_item_id: i32,
+    context: &GameContext,
+) -> Result<&'static str, LookupError> {
+    let name = try_get_key_item_name(key_item_id);
+    context.check(KeyItemId::KIND, key_item_id, name, key_item_chapter(key_item_id))
+}
+
 pub fn display_key_item(key_item_id: i32) -> CompactString {
     let key_item_name = try_get_key_item_name(key_item_id);
     key_item_name
         .map(|n| n.to_compact_string())
         .unwrap_or_else(|| format_compact!("Key Item {}", key_item_id))
 }
+
+id_type!(
+    /// A key item.
+    KeyItemId,
+    "key item",
+    try_get_key_item_name,
+    try_get_key_item_name_in,
+    display_key_item
+);
@@ -1,7 +1,15 @@
+use super::registry::{Table, registry};
+use super::{GameContext, LookupError};
 use compact_str::{CompactString, ToCompactString, format_compact};
 
-// Extracted from gml_GlobalScript_scr_litemname using UndertaleModTool
 pub fn try_get_lightworld_item_name(item_id: i32) -> Option<&'static str> {
+    registry()
+        .name(Table::LightworldItems, item_id)
+        .or_else(|| builtin_lightworld_item_name(item_id))
+}
+
+// Extracted from gml_GlobalScript_scr_litemname using UndertaleModTool
+pub(super) fn builtin_lightworld_item_name(item_id: i32) -> Option<&'static str> {
     match item_id {
         0 => Some("---"),
         1 => Some("Hot Chocolate"),
@@ -26,9 +34,40 @@ pub fn try_get_lightworld_item_name(item_id: i32) -> Option<&'static str> {
     }
 }
 
+/// The chapter a lightworld item first appears in.
+fn lightworld_item_chapter(item_id: i32) -> i32 {
+    if let Some(chapter) = registry().chapter(Table::LightworldItems, item_id) {
+        return chapter;
+    }
+
+    match item_id {
+        12..=16 => 2,
+        17 => 3,
+        18.. => 4,
+        _ => 1,
+    }
+}
+
+pub fn try_get_lightworld_item_name_in(
+    item_id: i32,
+    context: &GameContext,
+) -> Result<&'static str, LookupError> {
+    let name = try_get_lightworld_item_name(item_id);
+    context.check(LightworldItemId::KIND, item_id, name, lightworld_item_chapter(item_id))
+}
+
 pub fn display_lightworld_item(item_id: i32) -> CompactString {
     let item_name = try_get_lightworld_item_name(item_id);
     item_name
         .map(|n| n.to_compact_string())
         .unwrap_or_else(|| format_compact!("LW Item {}", item_id))
 }
+
+id_type!(
+    /// An item in the light world inventory, also used for light world equipment.
+    LightworldItemId,
+    "light world item",
+    try_get_lightworld_item_name,
+    try_get_lightworld_item_name_in,
+    display_lightworld_item
+);
@@ -1,11 +1,204 @@
+use crate::real::GmReal;
+use compact_str::CompactString;
 use regex::Regex;
+#[cfg(test)]
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+#[error("unknown {kind} {name}")]
+pub struct UnknownNameError {
+    pub kind: &'static str,
+    pub name: CompactString,
+}
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum LookupError {
+    #[error("unknown {kind} {id}")]
+    Unknown { kind: &'static str, id: i32 },
+    #[error("{kind} {id} does not exist in chapter {chapter}")]
+    NotInChapter {
+        kind: &'static str,
+        id: i32,
+        chapter: i32,
+    },
+}
+
+/// Number of chapters released so far.
+pub(crate) const CHAPTER_COUNT: usize = 4;
+
+/// What the game knows when it shows a name: the chapter being played and, if available, the
+/// save's flags.
+#[derive(Debug, Copy, Clone)]
+pub struct GameContext<'a> {
+    pub chapter: i32,
+    pub flags: Option<&'a [GmReal]>,
+}
+
+impl<'a> GameContext<'a> {
+    pub fn new(chapter: i32) -> Self {
+        GameContext { chapter, flags: None }
+    }
+
+    /// The latest chapter, without flags. Used where no save gives a chapter, like [`Display`].
+    ///
+    /// [`Display`]: std::fmt::Display
+    pub fn latest() -> Self {
+        GameContext::new(CHAPTER_COUNT as i32)
+    }
+
+    pub fn with_flags(self, flags: &'a [GmReal]) -> Self {
+        GameContext {
+            flags: Some(flags),
+            ..self
+        }
+    }
+
+    /// Reads a flag, if the flags are known and the index is in range.
+    pub fn flag(&self, index: usize) -> Option<f64> {
+        self.flags.and_then(|flags| flags.get(index)).map(GmReal::value)
+    }
+
+    /// Checks a context-free name against the chapter an ID was introduced in.
+    pub fn check(
+        &self,
+        kind: &'static str,
+        id: i32,
+        name: Option<&'static str>,
+        introduced_in: i32,
+    ) -> Result<&'static str, LookupError> {
+        let name = name.ok_or(LookupError::Unknown { kind, id })?;
+
+        if self.chapter < introduced_in {
+            return Err(LookupError::NotInChapter {
+                kind,
+                id,
+                chapter: self.chapter,
+            });
+        }
+
+        Ok(name)
+    }
+}
+
+/// Defines a newtype over a raw `i32` game ID.
+///
+/// Names are looked up in a [`GameContext`] by `$lookup_in`, `$lookup` is the name from the
+/// tables without checking the chapter. It displays using the name in the latest chapter and
+/// parses from either the number or a name.
+macro_rules! id_type {
+    ($(#[$meta:meta])* $name:ident, $kind:literal, $lookup:path, $lookup_in:path, $display:path) => {
+        $(#[$meta])*
+        #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(pub i32);
+
+        impl $name {
+            pub const KIND: &'static str = $kind;
+
+            /// Whether this is an empty slot.
+            pub fn is_empty(self) -> bool {
+                self.0 == 0
+            }
+
+            /// The name shown by the game in the given context, or why this ID can't appear there.
+            pub fn name(
+                self,
+                context: &$crate::gamedata::GameContext,
+            ) -> Result<&'static str, $crate::gamedata::LookupError> {
+                $lookup_in(self.0, context)
+            }
+
+            /// The name from the tables, without checking the chapter or resolving names that
+            /// depend on it. Prefer [`name`](Self::name) when the chapter is known.
+            pub fn name_any_chapter(self) -> Option<&'static str> {
+                $lookup(self.0)
+            }
+
+            /// The name shown in the given context, or the same fallback as [`Display`] for IDs
+            /// that can't appear there.
+            ///
+            /// [`Display`]: std::fmt::Display
+            pub fn display_in(
+                self,
+                context: &$crate::gamedata::GameContext,
+            ) -> compact_str::CompactString {
+                match self.name(context) {
+                    Ok(name) => name.into(),
+                    Err(_) => $display(self.0),
+                }
+            }
+        }
+
+        impl From<i32> for $name {
+            fn from(id: i32) -> Self {
+                $name(id)
+            }
+        }
+
+        impl From<$name> for i32 {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&self.display_in(&$crate::gamedata::GameContext::latest()))
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = $crate::gamedata::UnknownNameError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                if let Ok(id) = s.trim().parse::<i32>() {
+                    return Ok($name(id));
+                }
+
+                let latest = $crate::gamedata::GameContext::latest();
+                $crate::gamedata::find_id_by_name(s.trim(), |id| $lookup_in(id, &latest).ok())
+                    .or_else(|| $crate::gamedata::find_id_by_name(s.trim(), $lookup))
+                    .map($name)
+                    .ok_or_else(|| $crate::gamedata::UnknownNameError {
+                        kind: $kind,
+                        name: s.into(),
+                    })
+            }
+        }
+
+        impl $crate::serialize::Serializable for $name {
+            fn serialize(&self) -> compact_str::CompactString {
+                $crate::serialize::Serializable::serialize(&self.0)
+            }
+        }
+
+        impl $crate::visit::Visit for $name {
+            fn visit_with(
+                &self,
+                path: &mut $crate::visit::FieldPath,
+                visitor: &mut dyn $crate::visit::Visitor,
+            ) {
+                let value = $crate::visit::FieldValue::Id {
+                    kind: $kind,
+                    id: self.0,
+                    name: self.name(&$crate::gamedata::GameContext::latest()).ok(),
+                };
+                visitor.visit_value(path, value);
+            }
+        }
+    };
+}
 
 pub mod armors;
+pub mod equipment;
+pub mod flags;
 pub mod items;
 pub mod key_items;
 pub mod lightworld_items;
 pub mod party_members;
 pub mod phone_numbers;
+pub mod recruits;
+pub mod registry;
 pub mod rooms;
 pub mod spells;
 pub mod weapons;
@@ -23,3 +216,213 @@ pub fn parse_filename(path: &str) -> (i32, i32) {
         None => (0, 0),
     }
 }
+
+// Highest ID used by any of the built-in ID-based tables (phone numbers start at 201).
+const MAX_ID: i32 = 255;
+
+/// Finds the ID whose name in the given table matches `name`, ignoring ASCII case.
+pub fn find_id_by_name(name: &str, lookup: impl Fn(i32) -> Option<&'static str>) -> Option<i32> {
+    let max_id = registry::registry().max_id().map_or(MAX_ID, |id| id.max(MAX_ID));
+    (0..=max_id).find(|&id| lookup(id).is_some_and(|n| n.eq_ignore_ascii_case(name)))
+}
+
+/// Completion saves are written to the slot number plus this offset when a chapter is finished.
+pub const COMPLETION_SLOT_OFFSET: i32 = 3;
+
+/// Number of save slots shown on the file select screen.
+pub const SLOT_COUNT: i32 = 3;
+
+/// Whether the slot number (as returned by [`parse_filename`]) belongs to a completion save,
+/// which the next chapter imports its starting state from.
+pub fn is_completion_slot(slot: i32) -> bool {
+    (COMPLETION_SLOT_OFFSET..COMPLETION_SLOT_OFFSET + SLOT_COUNT).contains(&slot)
+}
+
+/// Returns the completion slot written when finishing a chapter played in the given slot.
+pub fn completion_slot_for(slot: i32) -> Option<i32> {
+    (0..SLOT_COUNT)
+        .contains(&slot)
+        .then_some(slot + COMPLETION_SLOT_OFFSET)
+}
+
+/// Returns the regular slot a completion save was written from.
+pub fn slot_for_completion(completion_slot: i32) -> Option<i32> {
+    is_completion_slot(completion_slot).then_some(completion_slot - COMPLETION_SLOT_OFFSET)
+}
+
+/// Assignments made under each `case N:` of a decompiled `scr_*info` script, with the right
+/// hand side as written.
+#[cfg(test)]
+fn script_cases(script: &str) -> HashMap<i32, HashMap<&str, &str>> {
+    let mut cases: HashMap<i32, HashMap<&str, &str>> = HashMap::new();
+    let mut current = None;
+
+    for line in script.lines().map(str::trim) {
+        if let Some(id) = line.strip_prefix("case ").and_then(|rest| rest.strip_suffix(':')) {
+            current = id.trim().parse().ok();
+            continue;
+        }
+        let (Some(id), Some((variable, value))) = (current, line.split_once(" = ")) else { continue };
+        cases.entry(id).or_default().insert(variable.trim(), value.trim_end_matches(';').trim());
+    }
+
+    cases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_parse_filename() {
+        assert_eq!(parse_filename("saves/filech2_4"), (2, 4));
+        assert_eq!(parse_filename("filech2_4.bak"), (0, 0));
+    }
+
+    #[test]
+    fn test_completion_slots() {
+        assert!(!is_completion_slot(2));
+        assert!(is_completion_slot(3));
+        assert!(is_completion_slot(5));
+        assert!(!is_completion_slot(6));
+        assert_eq!(completion_slot_for(1), Some(4));
+        assert_eq!(completion_slot_for(4), None);
+        assert_eq!(slot_for_completion(5), Some(2));
+        assert_eq!(slot_for_completion(0), None);
+    }
+
+    #[test]
+    fn test_id_from_str() {
+        use items::ItemId;
+
+        assert_eq!("3".parse::<ItemId>(), Ok(ItemId(3)));
+        assert_eq!("glowshard".parse::<ItemId>(), Ok(ItemId(3)));
+        assert_eq!(ItemId(3).to_string(), "Glowshard");
+        assert_eq!(
+            "Glowshard".parse::<weapons::WeaponId>(),
+            Err(UnknownNameError { kind: "weapon", name: "Glowshard".into() })
+        );
+    }
+
+    #[test]
+    fn test_equipment_info() {
+        use armors::ArmorId;
+        use equipment::StatBonus;
+        use party_members::PartyMember;
+        use weapons::WeaponId;
+
+        let devilsknife = WeaponId(7);
+        assert!(devilsknife.can_equip(PartyMember::SUSIE));
+        assert!(!devilsknife.can_equip(PartyMember::KRIS));
+        assert!(WeaponId(0).can_equip(PartyMember::KRIS));
+        assert_eq!(ArmorId(7).bonus() + ArmorId(1).bonus(), StatBonus::new(2, 3, 2));
+        assert_eq!(ArmorId(0).bonus(), StatBonus::default());
+
+        // Metadata can be missing for a known ID, but never exists for an unknown one
+        assert!(WeaponId(26).info().is_none());
+        for id in 1..=MAX_ID {
+            assert!(items::try_get_item_info(id).is_none() || items::try_get_item_name(id).is_some());
+            assert!(weapons::try_get_weapon_info(id).is_none() || weapons::try_get_weapon_name(id).is_some());
+            assert!(armors::try_get_armor_info(id).is_none() || armors::try_get_armor_name(id).is_some());
+        }
+    }
+
+    /// Checks the hand entered tables against the scripts in `research/iteminfo`, for the
+    /// chapters that have been exported. See the README there.
+    #[test]
+    fn test_tables_match_info_scripts() {
+        use equipment::StatBonus;
+        use party_members::PartyMember;
+
+        let cases = script_cases("case 5:\n    weaponnametemp = stringsetloc(\"Spookysword\", \"x\");\n    value = 200;\n");
+        assert_eq!(cases[&5]["value"], "200");
+        assert_eq!(rooms::string_literal(cases[&5]["weaponnametemp"]), Some("Spookysword"));
+
+        let research = Path::new(env!("CARGO_MANIFEST_DIR")).join("../research/iteminfo");
+        let number = |case: &HashMap<&str, &str>, variable: &str| {
+            case.get(variable).and_then(|value| value.parse::<f64>().ok()).unwrap_or(0.0) as i32
+        };
+        let equippable_by = |case: &HashMap<&str, &str>, prefix: &str| {
+            (1..=CHAPTER_COUNT as i32)
+                .filter(|n| number(case, &format!("{prefix}char{n}temp")) == 1)
+                .map(PartyMember)
+                .collect::<Vec<_>>()
+        };
+
+        for chapter in 1..=CHAPTER_COUNT {
+            let read = |script: &str| {
+                std::fs::read_to_string(research.join(format!("chapter{chapter}/{script}.gml"))).ok()
+            };
+
+            if let Some(script) = read("scr_weaponinfo") {
+                for (id, case) in script_cases(&script) {
+                    let name = case.get("weaponnametemp").and_then(|name| rooms::string_literal(name));
+                    assert_eq!(weapons::builtin_weapon_name(id), name, "chapter {chapter} weapon {id}");
+                    let Some(info) = weapons::try_get_weapon_info(id) else { continue };
+                    let bonus = StatBonus::new(
+                        number(&case, "weaponattemp"),
+                        number(&case, "weapondftemp"),
+                        number(&case, "weaponmagtemp"),
+                    );
+                    assert_eq!(info.bonus, bonus, "chapter {chapter} weapon {id}");
+                    assert_eq!(info.value, number(&case, "value"), "chapter {chapter} weapon {id}");
+                    assert_eq!(info.equippable_by, equippable_by(&case, "weapon"), "chapter {chapter} weapon {id}");
+                }
+            }
+
+            if let Some(script) = read("scr_armorinfo") {
+                for (id, case) in script_cases(&script) {
+                    let name = case.get("armornametemp").and_then(|name| rooms::string_literal(name));
+                    assert_eq!(armors::builtin_armor_name(id), name, "chapter {chapter} armor {id}");
+                    let Some(info) = armors::try_get_armor_info(id) else { continue };
+                    let bonus = StatBonus::new(
+                        number(&case, "armorattemp"),
+                        number(&case, "armordftemp"),
+                        number(&case, "armormagtemp"),
+                    );
+                    assert_eq!(info.bonus, bonus, "chapter {chapter} armor {id}");
+                    assert_eq!(info.value, number(&case, "value"), "chapter {chapter} armor {id}");
+                    assert_eq!(info.equippable_by, equippable_by(&case, "armor"), "chapter {chapter} armor {id}");
+                }
+            }
+
+            if let Some(script) = read("scr_iteminfo") {
+                for (id, case) in script_cases(&script) {
+                    let name = case.get("itemnametemp").and_then(|name| rooms::string_literal(name));
+                    assert_eq!(items::builtin_item_name(id), name, "chapter {chapter} item {id}");
+                    let Some(info) = items::try_get_item_info(id) else { continue };
+                    assert_eq!(info.value, number(&case, "value"), "chapter {chapter} item {id}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup_in_chapter() {
+        use party_members::PartyMember;
+        use rooms::RoomIndex;
+        use spells::SpellId;
+
+        let chapter_1 = GameContext::new(1);
+        let chapter_2 = GameContext::new(2);
+        let chapter_3 = GameContext::new(3);
+
+        assert_eq!(
+            PartyMember::NOELLE.name(&chapter_1),
+            Err(LookupError::NotInChapter { kind: "party member", id: 4, chapter: 1 })
+        );
+        assert_eq!(PartyMember::NOELLE.name(&chapter_3), Ok("Noelle"));
+        assert_eq!(SpellId(11).name(&chapter_2), Ok("* Heal"));
+        assert!(SpellId(11).name(&chapter_1).is_err());
+        assert_eq!(items::ItemId(500).name(&chapter_3), Err(LookupError::Unknown { kind: "item", id: 500 }));
+        assert_eq!(RoomIndex(10283).name_in(&chapter_1), Ok("Kris's Room"));
+        assert!(RoomIndex(10283).name_in(&chapter_3).is_err());
+
+        // Without a save, IDs display and parse as in the latest chapter
+        assert_eq!(SpellId(11).name_any_chapter(), Some("* Heal"));
+        assert_eq!(SpellId(11).to_string(), "* Heal");
+        assert_eq!("* Heal".parse::<SpellId>(), Ok(SpellId(11)));
+        assert_eq!(PartyMember::NOELLE.display_in(&chapter_1), "Noelle");
+    }
+}
@@ -1,4 +1,14 @@
+use super::registry::{Table, registry};
+use super::{GameContext, LookupError};
+use compact_str::{CompactString, ToCompactString, format_compact};
+
 pub fn try_get_party_member_name(index: i32) -> Option<&'static str> {
+    registry()
+        .name(Table::PartyMembers, index)
+        .or_else(|| builtin_party_member_name(index))
+}
+
+pub(super) fn builtin_party_member_name(index: i32) -> Option<&'static str> {
     match index {
         1 => Some("Kris"),
         2 => Some("Susie"),
@@ -11,3 +21,46 @@ pub fn try_get_party_member_name(index: i32) -> Option<&'static str> {
 pub fn get_party_member(index: usize) -> Option<&'static str> {
     try_get_party_member_name(index as i32)
 }
+
+/// The chapter a party member first appears in.
+fn party_member_chapter(index: i32) -> i32 {
+    if let Some(chapter) = registry().chapter(Table::PartyMembers, index) {
+        return chapter;
+    }
+
+    match index {
+        4 => 2,
+        _ => 1,
+    }
+}
+
+pub fn try_get_party_member_name_in(
+    index: i32,
+    context: &GameContext,
+) -> Result<&'static str, LookupError> {
+    let name = try_get_party_member_name(index);
+    context.check(PartyMember::KIND, index, name, party_member_chapter(index))
+}
+
+pub fn display_party_member(index: i32) -> CompactString {
+    let member_name = try_get_party_member_name(index);
+    member_name
+        .map(|n| n.to_compact_string())
+        .unwrap_or_else(|| format_compact!("Member {}", index))
+}
+
+id_type!(
+    /// A party member, as stored in the party slots. `0` means an empty slot.
+    PartyMember,
+    "party member",
+    try_get_party_member_name,
+    try_get_party_member_name_in,
+    display_party_member
+);
+
+impl PartyMember {
+    pub const KRIS: PartyMember = PartyMember(1);
+    pub const SUSIE: PartyMember = PartyMember(2);
+    pub const RALSEI: PartyMember = PartyMember(3);
+    pub const NOELLE: PartyMember = PartyMember(4);
+}
@@ -1,7 +1,15 @@
+use super::registry::{Table, registry};
+use super::{GameContext, LookupError};
 use compact_str::{CompactString, ToCompactString, format_compact};
 
-// Extracted from gml_GlobalScript_scr_phonename using UndertaleModTool
 pub fn try_get_phone_number_name(number_id: i32) -> Option<&'static str> {
+    registry()
+        .name(Table::PhoneNumbers, number_id)
+        .or_else(|| builtin_phone_number_name(number_id))
+}
+
+// Extracted from gml_GlobalScript_scr_phonename using UndertaleModTool
+pub(super) fn builtin_phone_number_name(number_id: i32) -> Option<&'static str> {
     match number_id {
         201 => Some("Call Home"),     // Sometimes as "Call Toriel"
         202 => Some("Sans's Number"), // Sometimes as "Not Sans's Number"
@@ -9,9 +17,28 @@ pub fn try_get_phone_number_name(number_id: i32) -> Option<&'static str> {
     }
 }
 
+pub fn try_get_phone_number_name_in(
+    number_id: i32,
+    context: &GameContext,
+) -> Result<&'static str, LookupError> {
+    let name = try_get_phone_number_name(number_id);
+    // The phone exists since chapter 1
+    let chapter = registry().chapter(Table::PhoneNumbers, number_id).unwrap_or(1);
+    context.check(PhoneNumberId::KIND, number_id, name, chapter)
+}
+
 pub fn display_phone_number(number_id: i32) -> CompactString {
     let phone_name = try_get_phone_number_name(number_id);
     phone_name
         .map(|n| n.to_compact_string())
         .unwrap_or_else(|| format_compact!("Phone #{}", number_id))
 }
+
+id_type!(
+    /// A contact in the light world phone.
+    PhoneNumberId,
+    "phone number",
+    try_get_phone_number_name,
+    try_get_phone_number_name_in,
+    display_phone_number
+);
@@ -0,0 +1,216 @@
+//! Enemies that can be recruited, and how far along their recruitment is.
+//!
+//! Each enemy's progress is kept in flag `600 + enemy_id`. Sparing or pacifying adds
+//! `1 / required` to it, so it reaches 1 once the enemy is recruited. Defeating the enemy
+//! instead sets it to -1 and loses the recruit for good.
+//!
+//! The table and the flag mapping were entered by hand from the in-game recruit menu and
+//! haven't been checked against `scr_recruit_info` yet, see `research/recruits/README.md`.
+
+use crate::real::GmReal;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq)]
+pub struct RecruitInfo {
+    pub enemy_id: usize,
+    pub name: &'static str,
+    pub chapter: i32,
+    /// How many times the enemy must be spared to be recruited.
+    pub required: u32,
+}
+
+impl RecruitInfo {
+    pub fn flag_index(&self) -> usize {
+        600 + self.enemy_id
+    }
+
+    /// Reads this enemy's recruit status from a save's flags.
+    pub fn status(&self, flags: &[GmReal]) -> RecruitStatus {
+        let progress = flags.get(self.flag_index()).map_or(0.0, GmReal::value);
+        RecruitStatus::from_progress(progress, self.required)
+    }
+}
+
+static RECRUITS: &[RecruitInfo] = &[
+    RecruitInfo {
+        enemy_id: 31,
+        name: "Shadowguy",
+        chapter: 3,
+        required: 3,
+    },
+    RecruitInfo {
+        enemy_id: 32,
+        name: "Pippins",
+        chapter: 3,
+        required: 4,
+    },
+    RecruitInfo {
+        enemy_id: 33,
+        name: "Zapper",
+        chapter: 3,
+        required: 2,
+    },
+    RecruitInfo {
+        enemy_id: 34,
+        name: "Ribbick",
+        chapter: 3,
+        required: 4,
+    },
+    RecruitInfo {
+        enemy_id: 35,
+        name: "Shuttah",
+        chapter: 3,
+        required: 1,
+    },
+    RecruitInfo {
+        enemy_id: 41,
+        name: "Bibliox",
+        chapter: 4,
+        required: 4,
+    },
+    RecruitInfo {
+        enemy_id: 42,
+        name: "Mizzle",
+        chapter: 4,
+        required: 3,
+    },
+    RecruitInfo {
+        enemy_id: 43,
+        name: "Wicabel",
+        chapter: 4,
+        required: 4,
+    },
+    RecruitInfo {
+        enemy_id: 44,
+        name: "Winglade",
+        chapter: 4,
+        required: 3,
+    },
+    RecruitInfo {
+        enemy_id: 45,
+        name: "Guei",
+        chapter: 4,
+        required: 4,
+    },
+    RecruitInfo {
+        enemy_id: 46,
+        name: "Balthizard",
+        chapter: 4,
+        required: 4,
+    },
+    RecruitInfo {
+        enemy_id: 47,
+        name: "Organikk",
+        chapter: 4,
+        required: 4,
+    },
+];
+
+/// The recruitable enemies of a chapter.
+pub fn recruits_in(chapter: i32) -> impl Iterator<Item = &'static RecruitInfo> {
+    RECRUITS.iter().filter(move |info| info.chapter == chapter)
+}
+
+pub fn try_get_recruit_info(enemy_id: usize) -> Option<&'static RecruitInfo> {
+    RECRUITS.iter().find(|info| info.enemy_id == enemy_id)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RecruitStatus {
+    None,
+    Partial { count: u32, required: u32 },
+    Recruited,
+    Lost,
+}
+
+impl RecruitStatus {
+    pub fn from_progress(progress: f64, required: u32) -> Self {
+        if progress < 0.0 {
+            RecruitStatus::Lost
+        } else {
+            // Progress is a sum of fractions like 1/3, so round instead of truncating.
+            match (progress * required as f64).round() as u32 {
+                0 => RecruitStatus::None,
+                count if count >= required => RecruitStatus::Recruited,
+                count => RecruitStatus::Partial { count, required },
+            }
+        }
+    }
+
+    /// Whether the status can still change by playing on.
+    pub fn is_final(self) -> bool {
+        matches!(self, RecruitStatus::Recruited | RecruitStatus::Lost)
+    }
+}
+
+impl Display for RecruitStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecruitStatus::None => f.write_str("---"),
+            RecruitStatus::Partial { count, required } => write!(f, "{count}/{required}"),
+            RecruitStatus::Recruited => f.write_str("Recruited"),
+            RecruitStatus::Lost => f.write_str("Lost"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recruit_status() {
+        let pippins = try_get_recruit_info(32).unwrap();
+        let mut flags = vec![GmReal::ZERO; 2500];
+        assert_eq!(pippins.status(&flags), RecruitStatus::None);
+
+        flags[632].set(0.5);
+        assert_eq!(
+            pippins.status(&flags),
+            RecruitStatus::Partial {
+                count: 2,
+                required: 4
+            }
+        );
+
+        flags[632].set(1.0);
+        assert_eq!(pippins.status(&flags), RecruitStatus::Recruited);
+        flags[632].set(-1.0);
+        assert_eq!(pippins.status(&flags), RecruitStatus::Lost);
+
+        assert_eq!(RecruitStatus::from_progress(0.6666, 3).to_string(), "2/3");
+        assert_eq!(RecruitStatus::from_progress(0.9999, 3), RecruitStatus::Recruited);
+    }
+
+    /// Checks the table against the `scr_recruit_info` exports in `research/recruits`, for the
+    /// chapters that have one.
+    #[test]
+    fn test_table_matches_recruit_info() {
+        let research = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../research/recruits");
+
+        for chapter in 1..=crate::gamedata::CHAPTER_COUNT as i32 {
+            let path = research.join(format!("chapter{chapter}_scr_recruit_info.gml"));
+            let Ok(script) = std::fs::read_to_string(path) else { continue };
+
+            let mut found = Vec::new();
+            for (id, case) in crate::gamedata::script_cases(&script) {
+                let name = case
+                    .iter()
+                    .filter(|(variable, _)| variable.ends_with("name"))
+                    .find_map(|(_, value)| crate::gamedata::rooms::string_literal(value));
+                let Some(name) = name else { continue };
+                let required = case.get("recruitcount").and_then(|count| count.parse::<f64>().ok());
+
+                let info = try_get_recruit_info(id as usize);
+                assert_eq!(info.map(|info| info.name), Some(name), "chapter {chapter} enemy {id}");
+                assert_eq!(info.map(|info| info.required as f64), required, "chapter {chapter} enemy {id}");
+                found.push(id as usize);
+            }
+
+            for info in recruits_in(chapter) {
+                assert!(found.contains(&info.enemy_id), "chapter {chapter} has no enemy {}", info.enemy_id);
+            }
+        }
+    }
+}
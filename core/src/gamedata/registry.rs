@@ -0,0 +1,362 @@
+//! Gamedata packs loaded at runtime, layered over the built-in tables.
+//!
+//! A pack is a TOML or JSON file adding or overriding names, for example for a new chapter:
+//!
+//! ```toml
+//! chapter = 5
+//!
+//! [items]
+//! 64 = "StarCookie"
+//!
+//! [rooms]
+//! 12 = "room_krisroom"
+//!
+//! [room_names]
+//! room_krisroom = "Kris's Room"
+//! ```
+//!
+//! Packs are read from `$XDG_CONFIG_HOME/deltakit/gamedata` (or `~/.config/deltakit/gamedata`)
+//! by [`crate::init`], in file name order, so later files win.
+
+use super::rooms::RoomIndex;
+use log::{debug, warn};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum PackError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("failed to parse {path}: {source}")]
+    Toml {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("failed to parse {path}: {source}")]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("{path} defines rooms but no chapter")]
+    MissingChapter { path: PathBuf },
+}
+
+/// The ID-based tables a pack can extend.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Table {
+    Items,
+    KeyItems,
+    Weapons,
+    Armors,
+    Spells,
+    PartyMembers,
+    LightworldItems,
+    PhoneNumbers,
+}
+
+impl Table {
+    /// Whether the built-in table already names this ID.
+    fn has_builtin(self, id: i32) -> bool {
+        let lookup = match self {
+            Table::Items => super::items::builtin_item_name,
+            Table::KeyItems => super::key_items::builtin_key_item_name,
+            Table::Weapons => super::weapons::builtin_weapon_name,
+            Table::Armors => super::armors::builtin_armor_name,
+            Table::Spells => super::spells::builtin_spell_name,
+            Table::PartyMembers => super::party_members::builtin_party_member_name,
+            Table::LightworldItems => super::lightworld_items::builtin_lightworld_item_name,
+            Table::PhoneNumbers => super::phone_numbers::builtin_phone_number_name,
+        };
+        lookup(id).is_some()
+    }
+}
+
+/// The contents of one pack file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GamedataPack {
+    /// Chapter the pack's new entries first appear in. Entries that rename a built-in ID keep
+    /// the chapter of the built-in one. Required when the pack has rooms.
+    pub chapter: Option<i32>,
+    #[serde(deserialize_with = "id_map")]
+    pub items: BTreeMap<i32, String>,
+    #[serde(deserialize_with = "id_map")]
+    pub key_items: BTreeMap<i32, String>,
+    #[serde(deserialize_with = "id_map")]
+    pub weapons: BTreeMap<i32, String>,
+    #[serde(deserialize_with = "id_map")]
+    pub armors: BTreeMap<i32, String>,
+    #[serde(deserialize_with = "id_map")]
+    pub spells: BTreeMap<i32, String>,
+    #[serde(deserialize_with = "id_map")]
+    pub party_members: BTreeMap<i32, String>,
+    #[serde(deserialize_with = "id_map")]
+    pub lightworld_items: BTreeMap<i32, String>,
+    #[serde(deserialize_with = "id_map")]
+    pub phone_numbers: BTreeMap<i32, String>,
+    /// Internal room names by their index in the pack's chapter.
+    #[serde(deserialize_with = "id_map")]
+    pub rooms: BTreeMap<i32, String>,
+    /// Save menu names by internal room name.
+    pub room_names: BTreeMap<String, String>,
+}
+
+/// Deserializes a map keyed by IDs. TOML and JSON keys are always strings.
+fn id_map<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BTreeMap<i32, String>, D::Error> {
+    BTreeMap::<String, String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(id, name)| match id.trim().parse::<i32>() {
+            Ok(id) => Ok((id, name)),
+            Err(_) => Err(D::Error::custom(format!("invalid ID `{id}`"))),
+        })
+        .collect()
+}
+
+impl GamedataPack {
+    /// Reads a pack, choosing the format by extension: `.json` is JSON, anything else TOML.
+    pub fn read(path: &Path) -> Result<Self, PackError> {
+        let content = std::fs::read_to_string(path).map_err(|source| PackError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        let pack: GamedataPack = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content).map_err(|source| PackError::Json {
+                path: path.to_owned(),
+                source,
+            })?
+        } else {
+            toml::from_str(&content).map_err(|source| PackError::Toml {
+                path: path.to_owned(),
+                source,
+            })?
+        };
+
+        if pack.chapter.is_none() && !pack.rooms.is_empty() {
+            return Err(PackError::MissingChapter {
+                path: path.to_owned(),
+            });
+        }
+
+        Ok(pack)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Entry {
+    name: &'static str,
+    chapter: Option<i32>,
+}
+
+/// Names from all loaded packs.
+///
+/// Lookups hand out `&'static str` like the built-in tables, so the names are leaked when a
+/// pack is added. Packs are loaded once per process, so this is bounded.
+#[derive(Debug, Default)]
+pub struct Registry {
+    tables: HashMap<Table, HashMap<i32, Entry>>,
+    /// Internal room names by full room ID.
+    rooms: BTreeMap<i32, &'static str>,
+    room_names: HashMap<(i32, &'static str), &'static str>,
+}
+
+fn leak(s: String) -> &'static str {
+    Box::leak(s.into_boxed_str())
+}
+
+impl Registry {
+    /// Adds a pack's entries, overriding earlier ones with the same ID.
+    ///
+    /// The pack's chapter is only recorded for IDs the built-in tables don't have, so renaming
+    /// an item doesn't change which chapters it exists in.
+    pub fn add(&mut self, pack: GamedataPack) {
+        let chapter = pack.chapter;
+        let tables = [
+            (Table::Items, pack.items),
+            (Table::KeyItems, pack.key_items),
+            (Table::Weapons, pack.weapons),
+            (Table::Armors, pack.armors),
+            (Table::Spells, pack.spells),
+            (Table::PartyMembers, pack.party_members),
+            (Table::LightworldItems, pack.lightworld_items),
+            (Table::PhoneNumbers, pack.phone_numbers),
+        ];
+
+        for (table, entries) in tables {
+            let entries_by_id = self.tables.entry(table).or_default();
+            for (id, name) in entries {
+                let name = leak(name);
+                let chapter = chapter.filter(|_| !table.has_builtin(id));
+                entries_by_id.insert(id, Entry { name, chapter });
+            }
+        }
+
+        if let Some(chapter) = chapter {
+            for (index, room) in pack.rooms {
+                self.rooms.insert(chapter * 10000 + index, leak(room));
+            }
+        }
+
+        // Room names apply to the pack's chapter, or to every chapter if it has none.
+        for (room, name) in pack.room_names {
+            let room = leak(room);
+            self.room_names.insert((chapter.unwrap_or(0), room), leak(name));
+        }
+    }
+
+    /// Loads every `.toml` and `.json` file in a directory, in file name order.
+    pub fn load_dir(dir: &Path) -> Result<Self, PackError> {
+        let entries = std::fs::read_dir(dir).map_err(|source| PackError::Io {
+            path: dir.to_owned(),
+            source,
+        })?;
+
+        let mut paths = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml" || ext == "json"))
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        let mut registry = Registry::default();
+        for path in paths {
+            debug!("Loading gamedata pack {}", path.display());
+            registry.add(GamedataPack::read(&path)?);
+        }
+        Ok(registry)
+    }
+
+    pub fn name(&self, table: Table, id: i32) -> Option<&'static str> {
+        self.entry(table, id).map(|entry| entry.name)
+    }
+
+    /// The chapter a pack entry was introduced in, if its pack declared one and the ID isn't
+    /// a built-in one.
+    pub fn chapter(&self, table: Table, id: i32) -> Option<i32> {
+        self.entry(table, id).and_then(|entry| entry.chapter)
+    }
+
+    /// The highest ID any pack defines, in any table.
+    pub fn max_id(&self) -> Option<i32> {
+        self.tables.values().flat_map(|table| table.keys()).max().copied()
+    }
+
+    fn entry(&self, table: Table, id: i32) -> Option<&Entry> {
+        self.tables.get(&table)?.get(&id)
+    }
+
+    /// The internal name of a room by its full room ID.
+    pub fn room(&self, room_id: i32) -> Option<&'static str> {
+        self.rooms.get(&room_id).copied()
+    }
+
+    /// The save menu name of an internal room name in the given chapter.
+    pub fn room_name(&self, chapter: i32, room: &str) -> Option<&'static str> {
+        self.room_names
+            .get(&(chapter, room))
+            .or_else(|| self.room_names.get(&(0, room)))
+            .copied()
+    }
+
+    /// Finds a pack room by its internal name, ignoring ASCII case.
+    pub fn find_room(&self, room: &str) -> Option<RoomIndex> {
+        self.rooms
+            .iter()
+            .find(|(_, r)| r.eq_ignore_ascii_case(room))
+            .map(|(&id, _)| RoomIndex(id))
+    }
+}
+
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+/// Returns the installed registry, or an empty one if no packs were loaded.
+pub fn registry() -> &'static Registry {
+    REGISTRY.get_or_init(Registry::default)
+}
+
+/// Installs the registry used by all gamedata lookups. Fails if lookups already happened or a
+/// registry was installed before.
+pub fn install(registry: Registry) -> Result<(), Registry> {
+    REGISTRY.set(registry)
+}
+
+/// The directory packs are loaded from by default.
+pub fn default_pack_dir() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("deltakit").join("gamedata"))
+}
+
+/// Loads and installs the packs from [`default_pack_dir`], if it exists.
+pub fn load_default() {
+    let Some(dir) = default_pack_dir().filter(|dir| dir.is_dir()) else {
+        return;
+    };
+
+    match Registry::load_dir(&dir) {
+        Ok(registry) => {
+            if install(registry).is_err() {
+                warn!("Gamedata packs were loaded too late and are ignored");
+            }
+        }
+        Err(err) => warn!("Ignoring gamedata packs: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_pack() {
+        let pack: GamedataPack = toml::from_str(
+            r#"
+            chapter = 5
+
+            [items]
+            64 = "StarCookie"
+            1 = "Darker Candy"
+
+            [rooms]
+            12 = "room_ch5_start"
+
+            [room_names]
+            room_ch5_start = "Somewhere New"
+            "#,
+        )
+        .unwrap();
+
+        let mut registry = Registry::default();
+        registry.add(pack);
+
+        assert_eq!(registry.name(Table::Items, 64), Some("StarCookie"));
+        assert_eq!(registry.name(Table::Items, 1), Some("Darker Candy"));
+        assert_eq!(registry.chapter(Table::Items, 64), Some(5));
+        // Dark Candy is renamed, but still exists from chapter 1
+        assert_eq!(registry.chapter(Table::Items, 1), None);
+        assert_eq!(registry.max_id(), Some(64));
+        assert_eq!(registry.room(50012), Some("room_ch5_start"));
+        assert_eq!(registry.room_name(5, "room_ch5_start"), Some("Somewhere New"));
+        assert_eq!(registry.find_room("ROOM_CH5_START"), Some(RoomIndex(50012)));
+    }
+
+    #[test]
+    fn test_json_pack() {
+        let pack: GamedataPack =
+            serde_json::from_str(r#"{ "weapons": { "55": "NewBlade" } }"#).unwrap();
+
+        let mut registry = Registry::default();
+        registry.add(pack);
+
+        assert_eq!(registry.name(Table::Weapons, 55), Some("NewBlade"));
+        assert_eq!(registry.chapter(Table::Weapons, 55), None);
+    }
+}
@@ -1,888 +1,341 @@
+//! Room tables for every chapter, read from the CSV files in `research/rooms`.
+//!
+//! Each chapter has two files, see `research/rooms/README.md` for where they come from:
+//! - `chapterN_rooms.csv` (`index,room`) lists the rooms by the index stored in saves.
+//! - `chapterN_roomnames.csv` (`room,name`) has the save menu names from `scr_roomname` that
+//!   are known so far, keyed by the internal room name. Most rooms don't have one yet.
+//!
+//! [`RoomTable::import_script`] reads the names straight from `scr_roomname` as decompiled by
+//! UndertaleModTool instead, so an export can replace the CSV once it's checked in.
+//!
+//! Rooms are grouped into areas by the prefix of their internal name, see [`try_get_room_area`].
+
+use crate::gamedata::registry::registry;
+use crate::gamedata::{CHAPTER_COUNT, GameContext, LookupError, UnknownNameError};
+use crate::serialize::Serializable;
 use compact_str::{CompactString, format_compact};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use thiserror::Error;
 
-// Extracted from scr_get_room_list in gml_GlobalScript_scr_get_room_by_id using UndertaleModTool
-pub fn try_get_room_id(room_index: i32) -> Option<&'static str> {
-    match room_index {
-        // region Chapter 1 rooms
-        10283 => Some("room_krisroom"),
-        10284 => Some("room_krishallway"),
-        10285 => Some("room_torroom"),
-        10286 => Some("room_torhouse"),
-        10287 => Some("room_torbathroom"),
-        10288 => Some("room_town_krisyard"),
-        10289 => Some("room_town_northwest"),
-        10290 => Some("room_town_north"),
-        10291 => Some("room_beach"),
-        10292 => Some("room_town_mid"),
-        10293 => Some("room_town_apartments"),
-        10294 => Some("room_town_south"),
-        10295 => Some("room_town_school"),
-        10296 => Some("room_town_church"),
-        10297 => Some("room_graveyard"),
-        10298 => Some("room_town_shelter"),
-        10299 => Some("room_hospital_lobby"),
-        10300 => Some("room_hospital_hallway"),
-        10301 => Some("room_hospital_rudy"),
-        10302 => Some("room_hospital_room2"),
-        10303 => Some("room_diner"),
-        10304 => Some("room_townhall"),
-        10305 => Some("room_flowershop_1f"),
-        10306 => Some("room_flowershop_2f"),
-        10307 => Some("room_library"),
-        10308 => Some("room_alphysalley"),
-        10309 => Some("room_torielclass"),
-        10310 => Some("room_schoollobby"),
-        10311 => Some("room_alphysclass"),
-        10312 => Some("room_schooldoor"),
-        10313 => Some("room_insidecloset"),
-        10314 => Some("room_school_unusedroom"),
-        10315 => Some("room_dark1"),
-        10316 => Some("room_dark1a"),
-        10317 => Some("room_dark2"),
-        10318 => Some("room_dark3"),
-        10319 => Some("room_dark3a"),
-        10320 => Some("room_dark_wobbles"),
-        10321 => Some("room_dark_eyepuzzle"),
-        10322 => Some("room_dark7"),
-        10323 => Some("room_dark_chase1"),
-        10324 => Some("room_dark_chase2"),
-        10325 => Some("room_castle_outskirts"),
-        10326 => Some("room_castle_town"),
-        10327 => Some("room_castle_front"),
-        10328 => Some("room_castle_tutorial"),
-        10329 => Some("room_castle_darkdoor"),
-        10330 => Some("room_field_start"),
-        10331 => Some("room_field_forest"),
-        10332 => Some("room_field1"),
-        10333 => Some("room_field2"),
-        10334 => Some("room_field2A"),
-        10335 => Some("room_field_topchef"),
-        10336 => Some("room_field_puzzle1"),
-        10337 => Some("room_field_maze"),
-        10338 => Some("room_field_puzzle2"),
-        10339 => Some("room_field_getsusie"),
-        10340 => Some("room_field_shop1"),
-        10341 => Some("room_field_puzzletutorial"),
-        10342 => Some("room_field3"),
-        10343 => Some("room_field_boxpuzzle"),
-        10344 => Some("room_field4"),
-        10345 => Some("room_field_secret1"),
-        10346 => Some("room_field_checkers4"),
-        10347 => Some("room_field_checkers2"),
-        10348 => Some("room_field_checkers6"),
-        10349 => Some("room_field_checkers3"),
-        10350 => Some("room_field_checkers1"),
-        10351 => Some("room_field_checkers5"),
-        10352 => Some("room_field_checkers7"),
-        10353 => Some("room_field_checkersboss"),
-        10354 => Some("room_forest_savepoint1"),
-        10355 => Some("room_forest_area0"),
-        10356 => Some("room_forest_area1"),
-        10357 => Some("room_forest_area2"),
-        10358 => Some("room_forest_area2A"),
-        10359 => Some("room_forest_puzzle1"),
-        10360 => Some("room_forest_beforeclover"),
-        10361 => Some("room_forest_area3A"),
-        10362 => Some("room_forest_area3"),
-        10363 => Some("room_forest_savepoint2"),
-        10364 => Some("room_forest_smith"),
-        10365 => Some("room_forest_area4"),
-        10366 => Some("room_forest_dancers1"),
-        10367 => Some("room_forest_secret1"),
-        10368 => Some("room_forest_thrashmaker"),
-        10369 => Some("room_forest_starwalker"),
-        10370 => Some("room_forest_area5"),
-        10371 => Some("room_forest_savepoint_relax"),
-        10372 => Some("room_forest_maze1"),
-        10373 => Some("room_forest_maze_deadend"),
-        10374 => Some("room_forest_maze_susie"),
-        10375 => Some("room_forest_maze2"),
-        10376 => Some("room_forest_maze_deadend2"),
-        10377 => Some("room_forest_savepoint3"),
-        10378 => Some("room_forest_fightsusie"),
-        10379 => Some("room_forest_afterthrash2"),
-        10380 => Some("room_forest_afterthrash3"),
-        10381 => Some("room_forest_afterthrash4"),
-        10382 => Some("room_forest_castleview"),
-        10383 => Some("room_forest_chase1"),
-        10384 => Some("room_forest_chase2"),
-        10385 => Some("room_forest_castlefront"),
-        10386 => Some("room_cc_prison_cells"),
-        10387 => Some("room_cc_prisonlancer"),
-        10388 => Some("room_cc_prison_to_elevator"),
-        10389 => Some("room_cc_prison2"),
-        10390 => Some("room_cc_prisonelevator"),
-        10391 => Some("room_cc_elevator"),
-        10392 => Some("room_cc_prison_prejoker"),
-        10393 => Some("room_cc_joker"),
-        10394 => Some("room_cc_entrance"),
-        10395 => Some("room_cc_1f"),
-        10396 => Some("room_cc_rudinn"),
-        10397 => Some("room_cc_2f"),
-        10398 => Some("room_cc_rurus1"),
-        10399 => Some("room_cc_3f"),
-        10400 => Some("room_cc_hathy"),
-        10401 => Some("room_cc_4f"),
-        10402 => Some("room_cc_rurus2"),
-        10403 => Some("room_cc_clover"),
-        10404 => Some("room_cc_5f"),
-        10405 => Some("room_cc_lancer"),
-        10406 => Some("room_cc_6f"),
-        10407 => Some("room_cc_throneroom"),
-        10408 => Some("room_cc_preroof"),
-        10409 => Some("room_cc_kingbattle"),
-        10410 => Some("room_cc_prefountain"),
-        10411 => Some("room_cc_fountain"),
-        10422 => Some("room_empty"),
-        10423 => Some("room_man"),
-        // endregion
-        // region Chapter 2 rooms
-        20001 => Some("PLACE_DOGCHECK2"),
-        20002 => Some("room_intro_ch2"),
-        20003 => Some("room_dw_mansion_fountain"),
-        20004 => Some("room_dw_mansion_prefountain"),
-        20005 => Some("room_debug_choicer_light"),
-        20006 => Some("room_debug_smallface"),
-        20007 => Some("room_debug_battleBalloon"),
-        20008 => Some("room_debug_smallface_dark"),
-        20009 => Some("room_debug_choicer_dark"),
-        20010 => Some("room_gms_debug_failsafe"),
-        20011 => Some("ROOM_INITIALIZE"),
-        20012 => Some("room_title_placeholder"),
-        20013 => Some("room_battletest"),
-        20014 => Some("room_cutscene_tester"),
-        20015 => Some("room_sound_tester"),
-        20016 => Some("room_sprite_tester"),
-        20017 => Some("room_gif_tester"),
-        20018 => Some("room_bullettest"),
-        20019 => Some("room_teacup_demoauto"),
-        20020 => Some("room_teacup_demobullets"),
-        20021 => Some("room_shaun_puzzle"),
-        20022 => Some("room_GMS2_test"),
-        20023 => Some("room_cutscene_tester_b"),
-        20024 => Some("room_debug_color"),
-        20025 => Some("room_debug_battle"),
-        20026 => Some("room_debug_loc"),
-        20027 => Some("PLACE_CONTACT"),
-        20028 => Some("room_krisroom"),
-        20029 => Some("room_krishallway"),
-        20030 => Some("room_torroom"),
-        20031 => Some("room_torhouse"),
-        20032 => Some("room_torbathroom"),
-        20033 => Some("room_town_krisyard"),
-        20034 => Some("room_town_northwest"),
-        20035 => Some("room_town_north"),
-        20036 => Some("room_beach"),
-        20037 => Some("room_town_mid"),
-        20038 => Some("room_town_apartments"),
-        20039 => Some("room_town_south"),
-        20040 => Some("room_town_school"),
-        20041 => Some("room_town_church"),
-        20042 => Some("room_graveyard"),
-        20043 => Some("room_town_shelter"),
-        20044 => Some("room_hospital_lobby"),
-        20045 => Some("room_hospital_hallway"),
-        20046 => Some("room_hospital_rudy"),
-        20047 => Some("room_hospital_room2"),
-        20048 => Some("room_diner"),
-        20049 => Some("room_townhall"),
-        20050 => Some("room_flowershop_1f"),
-        20051 => Some("room_flowershop_2f"),
-        20052 => Some("room_library"),
-        20053 => Some("room_alphysalley"),
-        20054 => Some("room_lw_computer_lab"),
-        20055 => Some("room_lw_library_upstairs"),
-        20056 => Some("room_lw_police"),
-        20057 => Some("room_lw_conbini"),
-        20058 => Some("room_lw_icee_pizza"),
-        20059 => Some("room_torielclass"),
-        20060 => Some("room_schoollobby"),
-        20061 => Some("room_alphysclass"),
-        20062 => Some("room_schooldoor"),
-        20063 => Some("room_insidecloset"),
-        20064 => Some("room_school_unusedroom"),
-        20065 => Some("room_castle_town"),
-        20066 => Some("room_castle_tutorial"),
-        20067 => Some("room_dw_castle_west_cliff_old"),
-        20068 => Some("room_dw_castle_east_door"),
-        20069 => Some("room_dw_castle_west_cliff"),
-        20070 => Some("room_dw_castle_area_1"),
-        20071 => Some("room_dw_castle_area_2"),
-        20072 => Some("room_dw_castle_area_2_transformed"),
-        20073 => Some("room_dw_ralsei_castle_front"),
-        20074 => Some("room_dw_castle_restaurant"),
-        20075 => Some("room_dw_castle_cafe"),
-        20076 => Some("room_dw_castle_dojo"),
-        20077 => Some("room_dw_ralsei_castle_1f"),
-        20078 => Some("room_dw_ralsei_castle_2f"),
-        20079 => Some("room_dw_castle_dungeon"),
-        20080 => Some("room_dw_castle_rooms_hallway"),
-        20081 => Some("room_dw_castle_rooms_kris"),
-        20082 => Some("room_dw_castle_rooms_susie"),
-        20083 => Some("room_dw_castle_rooms_lancer"),
-        20084 => Some("room_dw_cyber_intro_1"),
-        20085 => Some("room_dw_cyber_intro_connector"),
-        20086 => Some("room_dw_cyber_intro_2"),
-        20087 => Some("room_dw_cyber_rhythm_slide"),
-        20088 => Some("room_dw_cyber_savepoint"),
-        20089 => Some("room_dw_cyber_battle_maze_1"),
-        20090 => Some("room_dw_cyber_music_bullet"),
-        20091 => Some("room_dw_cyber_tasque_battle"),
-        20092 => Some("room_dw_cyber_keyboard_puzzle_1"),
-        20093 => Some("room_dw_cyber_queen_boxing"),
-        20094 => Some("room_dw_cyber_musical_door"),
-        20095 => Some("room_dw_cyber_maze_virokun"),
-        20096 => Some("room_dw_cyber_keyboard_puzzle_2"),
-        20097 => Some("room_dw_cyber_battle_maze_2"),
-        20098 => Some("room_dw_cyber_music_final"),
-        20099 => Some("room_dw_cyber_musical_shop"),
-        20100 => Some("room_dw_cyber_teacup_final"),
-        20101 => Some("room_dw_cyber_rollercoaster"),
-        20102 => Some("room_dw_cyber_maze_fireworks"),
-        20103 => Some("room_dw_cyber_maze_tasque"),
-        20104 => Some("room_dw_cyber_maze_queenscreen"),
-        20105 => Some("room_dw_cyber_viro_ring"),
-        20106 => Some("room_dw_cyber_post_music_boss_slide"),
-        20107 => Some("room_dw_cyber_keyboard_puzzle_3"),
-        20108 => Some("room_dw_cyber_battle_maze_3"),
-        20109 => Some("room_dw_cyber_teacup_2"),
-        20110 => Some("room_dw_cyber_shaunsmusicalbullettunnel"),
-        20111 => Some("room_dw_cyber_maze_rhythm"),
-        20112 => Some("room_dw_cyber_escalator_slide"),
-        20113 => Some("room_dw_cyber_nuberts_treasure"),
-        20114 => Some("room_dw_cyber_music_fight"),
-        20115 => Some("room_dw_cyber_keyboardexample"),
-        20116 => Some("room_dw_city_prototype_01"),
-        20117 => Some("room_dw_city_prototype_02"),
-        20118 => Some("room_dw_city_spamton_shop_exterior"),
-        20119 => Some("room_dw_city_spamton_house"),
-        20120 => Some("room_dw_city_intro"),
-        20121 => Some("room_dw_city_split"),
-        20122 => Some("room_dw_city_entrance"),
-        20123 => Some("room_dw_city_traffic_1"),
-        20124 => Some("room_dw_city_roadblock"),
-        20125 => Some("room_dw_city_hacker"),
-        20126 => Some("room_dw_city_mice"),
-        20127 => Some("room_dw_city_big_1"),
-        20128 => Some("room_dw_city_traffic_2"),
-        20129 => Some("room_dw_city_big_2"),
-        20130 => Some("room_dw_city_queen_drunk"),
-        20131 => Some("room_dw_city_savepoint"),
-        20132 => Some("room_dw_city_big_3"),
-        20133 => Some("room_dw_city_traffic_3"),
-        20134 => Some("room_dw_city_mice2"),
-        20135 => Some("room_dw_city_cheesemaze"),
-        20136 => Some("room_dw_city_mice3"),
-        20137 => Some("room_dw_city_poppup"),
-        20138 => Some("room_dw_city_berdly"),
-        20139 => Some("room_dw_city_traffic_4"),
-        20140 => Some("room_dw_city_spamton_alley"),
-        20141 => Some("room_dw_city_monologue"),
-        20142 => Some("room_dw_city_baseball"),
-        20143 => Some("room_dw_city_postbaseball_1"),
-        20144 => Some("room_dw_city_postbaseball_2"),
-        20145 => Some("room_dw_city_postbaseball_3"),
-        20146 => Some("room_dw_city_mansion_front"),
-        20147 => Some("room_dw_city_susie_ralsei_fun_1"),
-        20148 => Some("room_dw_city_mirrorfriend"),
-        20149 => Some("room_dw_city_treasure"),
-        20150 => Some("room_dw_city_dog_traffic"),
-        20151 => Some("room_dw_city_man"),
-        20152 => Some("room_dw_city_moss"),
-        20153 => Some("room_dw_city_big_3_backup_2exits"),
-        20154 => Some("room_dw_city_traffic_3_2Entrances"),
-        20155 => Some("room_dw_city_cheese"),
-        20156 => Some("room_dw_city_carnival"),
-        20157 => Some("room_dw_city_noelle_fight_intro"),
-        20158 => Some("room_dw_city_spamton_shop_interior"),
-        20159 => Some("room_dw_city_monologue_old"),
-        20160 => Some("room_dw_mansion_krisroom"),
-        20161 => Some("room_dw_mansion_susieroom"),
-        20162 => Some("room_dw_mansion_lightner_hallway"),
-        20163 => Some("room_dw_mansion_darkbulb_1"),
-        20164 => Some("room_dw_mansion_darkbulb_2"),
-        20165 => Some("room_dw_mansion_darkbulb_3"),
-        20166 => Some("room_dw_mansion_dining_a"),
-        20167 => Some("room_dw_mansion_entrance"),
-        20168 => Some("room_dw_mansion_fire_paintings"),
-        20169 => Some("room_dw_mansion_single_pot"),
-        20170 => Some("room_dw_mansion_potBalance"),
-        20171 => Some("room_dw_mansion_tasquePaintings"),
-        20172 => Some("room_dw_mansion_traffic"),
-        20173 => Some("room_dw_mansion_east_1f_e"),
-        20174 => Some("room_dw_mansion_east_1f_secret"),
-        20175 => Some("room_dw_mansion_east_teacup"),
-        20176 => Some("room_dw_mansion_east_teacup_4"),
-        20177 => Some("room_dw_mansion_east_teacup_3"),
-        20178 => Some("room_dw_mansion_east_teacup_2"),
-        20179 => Some("room_dw_mansion_b_entrance"),
-        20180 => Some("room_dw_mansion_b_stairs"),
-        20181 => Some("room_dw_mansion_b_central"),
-        20182 => Some("room_dw_mansion_b_west_1f"),
-        20183 => Some("room_dw_mansion_b_west_1f_a"),
-        20184 => Some("room_dw_mansion_b_west_1f_b"),
-        20185 => Some("room_dw_mansion_b_west_2f"),
-        20186 => Some("room_dw_mansion_b_east"),
-        20187 => Some("room_dw_mansion_b_east_a"),
-        20188 => Some("room_dw_mansion_b_east_b"),
-        20189 => Some("room_dw_mansion_b_east_transformed"),
-        20190 => Some("room_dw_mansion_east_2f_a"),
-        20191 => Some("room_dw_mansion_east_2f_transformed_new"),
-        20192 => Some("room_dw_mansion_east_2f_shortcut"),
-        20193 => Some("room_dw_mansion_kitchen"),
-        20194 => Some("room_dw_mansion_east_2f_c"),
-        20195 => Some("room_dw_mansion_east_2f_c_a"),
-        20196 => Some("room_dw_mansion_east_2f_d"),
-        20197 => Some("room_dw_mansion_east_3f"),
-        20198 => Some("room_dw_mansion_east_3f_projection"),
-        20199 => Some("room_dw_mansion_east_3f_toilet"),
-        20200 => Some("room_dw_mansion_acid_tunnel"),
-        20201 => Some("room_dw_mansion_acid_tunnel_puzzle_entrance"),
-        20202 => Some("room_dw_mansion_acid_tunnel_loop_rouxls"),
-        20203 => Some("room_dw_mansion_acid_tunnel_exit"),
-        20204 => Some("room_dw_mansion_east_4f_b"),
-        20205 => Some("room_dw_mansion_east_4f_c"),
-        20206 => Some("room_dw_mansion_east_4f_d"),
-        20207 => Some("room_dw_mansion_top"),
-        20208 => Some("room_dw_mansion_top_post"),
-        20209 => Some("room_dw_mansion_ferris_wheel"),
-        20210 => Some("room_dw_mansion_ferris_wheel_post"),
-        20211 => Some("room_dw_mansion_noelle_room"),
-        20212 => Some("room_dw_mansion_bridges"),
-        20213 => Some("room_dw_mansion_bridges_funny"),
-        20214 => Some("room_dw_mansion_mouseLottery"),
-        20215 => Some("room_dw_mansion_hands"),
-        20216 => Some("room_dw_mansion_dining3"),
-        20217 => Some("room_dw_mansion_dininghall"),
-        20218 => Some("room_dw_mansion_dining_storage"),
-        20219 => Some("room_dw_mansion_east_1f_b"),
-        20220 => Some("room_dw_mansion_east_2f_c_b"),
-        20221 => Some("room_dw_mansion_traffic_original"),
-        20222 => Some("room_dw_mansion_east_1f_a"),
-        20223 => Some("room_dw_mansion_east_2f_teacup"),
-        20224 => Some("room_dw_mansion_east_4f_e"),
-        20225 => Some("room_dw_mansion_east_4f_a"),
-        20226 => Some("room_dw_mansion_east_2f_ufo_old"),
-        20227 => Some("room_dw_mansion_east_1f_d"),
-        20228 => Some("room_dw_mansion_east_1f_c"),
-        20229 => Some("room_dw_mansion_sparks"),
-        20230 => Some("room_dw_mansion_acid_tunnel_old"),
-        20231 => Some("room_dw_mansion_top_post_old"),
-        20232 => Some("room_dw_mansion_elevator"),
-        20233 => Some("PLACE_DOG"),
-        20234 => Some("room_legend"),
-        20235 => Some("room_legend_neo"),
-        20236 => Some("room_shop1"),
-        20237 => Some("room_shop_ch2_music"),
-        20238 => Some("room_shop_ch2_swatch"),
-        20239 => Some("room_shop_ch2_spamton"),
-        20240 => Some("room_gameover"),
-        20241 => Some("PLACE_LOGO"),
-        20242 => Some("PLACE_FAILURE"),
-        20243 => Some("PLACE_NAMING_JIKKEN"),
-        20244 => Some("PLACE_MENU"),
-        20245 => Some("room_ed"),
-        20246 => Some("room_empty"),
-        20247 => Some("room_DARKempty"),
-        20248 => Some("room_DARKbase_GMS2"),
-        20249 => Some("room_dw_cyber_battle_maze_2_old"),
-        20250 => Some("room_dw_cyber_keyboard_puzzle_1_old"),
-        20251 => Some("room_dw_cyber_tasque_battle_og"),
-        20252 => Some("room_dw_cyber_savepoint_original"),
-        20253 => Some("room_dw_cyber_battle_maze_1_Original"),
-        20254 => Some("room_dw_cyber_music_bullet_original"),
-        20255 => Some("room_dw_cyber_maze_virokun_backuo"),
-        20256 => Some("room_dw_cyber_battle_maze_2_toby"),
-        20257 => Some("room_dw_city_big_1_original"),
-        20258 => Some("room_dw_city_traffic_2_old"),
-        20259 => Some("room_dw_city_big_2_OG"),
-        20260 => Some("room_dw_city_mice2_og"),
-        20261 => Some("room_dw_mansion_east_teacup_4_old"),
-        20262 => Some("room_dw_cyber_teacup_1"),
-        20263 => Some("room_dw_cyber_viromaze2"),
-        20264 => Some("room_dw_city_traffic_5_old"),
-        20265 => Some("room_dw_mansion_dining_storage_old"),
-        20266 => Some("room_dw_cyber_virovirokun_fight"),
-        20267 => Some("room_dw_mansion_east_2f_d_backup"),
-        20268 => Some("room_dw_city_traffic_3_backup"),
-        20269 => Some("room_cc_lancer"),
-        20270 => Some("room_cc_clover"),
-        20271 => Some("room_cc_fountain"),
-        20272 => Some("room_dw_city_big_3_og"),
-        20273 => Some("room_dw_mansion_bridgesold"),
-        20274 => Some("room_dw_city_sidewayscars"),
-        20275 => Some("room_transformation_sequence"),
-        20276 => Some("room_dw_mansion_gigaqueen"),
-        20277 => Some("room_musical_sync_test"),
-        // endregion
-        // region Chapter 3 rooms
-        30094 => Some("room_dw_tv_cutscene1g"),
-        30095 => Some("room_dw_rhythm"),
-        30097 => Some("room_dw_couch_overworld_intro"),
-        30098 => Some("room_dw_couch_overworld_01"),
-        30099 => Some("room_dw_couch_overworld_02"),
-        30100 => Some("room_dw_couch_overworld_03"),
-        30101 => Some("room_dw_couch_overworld_04"),
-        30102 => Some("room_dw_couch_overworld_05"),
-        30103 => Some("room_dw_nondescript_room"),
-        30104 => Some("room_dw_nondescript_field"),
-        30105 => Some("room_dw_nondescript_hallway"),
-        30106 => Some("room_dw_nondescript_classroom"),
-        30107 => Some("room_dw_backstage"),
-        30108 => Some("room_dw_snow_zone"),
-        30109 => Some("room_town_krisyard_dark"),
-        30111 => Some("room_board_1"),
-        30114 => Some("room_board_dungeon_2"),
-        30115 => Some("room_board_dungeon_3"),
-        30116 => Some("room_board_preshadowmantle"),
-        30117 => Some("room_shadowmantle"),
-        30118 => Some("room_board_prepostshadowmantle"),
-        30119 => Some("room_board_postshadowmantle"),
-        30120 => Some("room_dw_ch3_man"),
-        30123 => Some("room_susiezilla"),
-        30124 => Some("room_board_intro"),
-        30126 => Some("room_torhouse_sepia"),
-        30130 => Some("room_ch3_gameshowroom"),
-        30131 => Some("room_shootout"),
-        30133 => Some("room_board_preshadowmantle_repeat"),
-        30134 => Some("room_dw_green_room"),
-        30135 => Some("room_board_1_sword"),
-        30136 => Some("room_board_sword_intro"),
-        30137 => Some("room_board_1_sword_trees"),
-        30138 => Some("room_dw_changing_room"),
-        30139 => Some("room_dw_console_room"),
-        30140 => Some("room_board_2"),
-        30141 => Some("room_dw_tv_closet"),
-        30143 => Some("room_board_3"),
-        30146 => Some("room_dw_b3bs_interstitial"),
-        30147 => Some("room_board_2_sword"),
-        30149 => Some("room_board_empty"),
-        30150 => Some("room_dw_b3bs_rouxls_lanina"),
-        30152 => Some("room_dw_b3bs_rouxls_boss"),
-        30153 => Some("room_dw_b3bs_cheaterpippins"),
-        30154 => Some("room_dw_b3bs_idcardpuzzle"),
-        30155 => Some("room_dw_b3bs_intro"),
-        30156 => Some("room_dw_b3bs_zapper_a"),
-        30157 => Some("room_dw_b3bs_rabbick_a"),
-        30158 => Some("room_dw_b3bs_lancerget"),
-        30159 => Some("room_dw_b3bs_cooltrashy"),
-        30160 => Some("room_dw_b3bs_zapper_c"),
-        30161 => Some("room_dw_b3bs_rabbick_b"),
-        30162 => Some("room_dw_b3bs_sadshadowguys"),
-        30163 => Some("room_dw_b3bs_zapper_b"),
-        30165 => Some("room_dw_susiezilla"),
-        30166 => Some("room_dw_inbetween"),
-        30167 => Some("room_dw_teevie_preview"),
-        30168 => Some("room_dw_chef"),
-        30169 => Some("room_dw_puzzlecloset_1"),
-        30170 => Some("room_dw_puzzlecloset_2"),
-        30173 => Some("room_dw_chef_empty"),
-        30174 => Some("room_dw_susiezilla_empty"),
-        30175 => Some("room_dw_rhythm_empty"),
-        30176 => Some("room_board_3b"),
-        30178 => Some("room_dw_b3bs_jail1"),
-        30179 => Some("room_dw_b3bs_shop"),
-        30180 => Some("room_dw_b3bs_mysterypuzzle"),
-        30181 => Some("room_dw_b3bs_watercooler"),
-        30182 => Some("room_dw_b3bs_jail2"),
-        30183 => Some("room_dw_b3bs_zapper_d"),
-        30184 => Some("room_dw_b3bs_extrapuzzle"),
-        30185 => Some("room_dw_b3bs_bibliox"),
-        30187 => Some("room_board_3_sword"),
-        30188 => Some("room_dw_couch_points"),
-        30189 => Some("room_dw_couch_overworld_intro_left"),
-        30190 => Some("room_dw_couch_video"),
-        30191 => Some("room_dw_snow_zone_battle"),
-        30193 => Some("room_dw_puzzlecloset_0"),
-        30194 => Some("room_board_gsa02_b0"),
-        30195 => Some("room_dw_tv_curtain"),
-        30196 => Some("room_dw_teevie_intro"),
-        30197 => Some("room_dw_teevie_large_01"),
-        30198 => Some("room_dw_teevie_large_02"),
-        30199 => Some("room_dw_teevie_cowboy_zone_01_intro"),
-        30200 => Some("room_dw_teevie_cowboy_zone_01_after"),
-        30201 => Some("room_dw_teevie_watercooler"),
-        30202 => Some("room_dw_teevie_susiezilla"),
-        30203 => Some("room_dw_teevie_cowboy_zone_02_intro"),
-        30204 => Some("room_dw_teevie_cowboy_zone_02_after"),
-        30205 => Some("room_dw_teevie_shadow_guys"),
-        30206 => Some("room_dw_teevie_stealth_c"),
-        30207 => Some("room_dw_teevie_stealth"),
-        30208 => Some("room_dw_teevie_failure_cage"),
-        30209 => Some("room_dw_teevie_rhythm"),
-        30210 => Some("room_dw_teevie_maze_points"),
-        30211 => Some("room_dw_teevie_maze_chef"),
-        30212 => Some("room_dw_teevie_maze_final"),
-        30213 => Some("room_dw_teevie_maze"),
-        30214 => Some("room_dw_teevie_chef"),
-        30215 => Some("room_dw_teevie_cutscene_final"),
-        30216 => Some("room_dw_teevie_ribbick"),
-        30217 => Some("room_dw_teevie_preview_south"),
-        30218 => Some("room_dw_b3bs_camerareminder"),
-        30219 => Some("room_dw_puzzlecloset_3"),
-        30220 => Some("room_dw_teevie_sams"),
-        30221 => Some("room_dw_teevie_bonus_zone"),
-        30222 => Some("room_dw_teevie_audiencepits"),
-        30224 => Some("room_dw_teevie_lightmaze"),
-        30225 => Some("room_dw_teevie_maze_quiz"),
-        30226 => Some("room_dw_ranking_hub"),
-        30227 => Some("room_dw_ranking_a"),
-        30228 => Some("room_dw_ranking_b"),
-        30229 => Some("room_dw_ranking_c"),
-        30230 => Some("room_dw_ranking_z"),
-        30231 => Some("room_dw_ranking_z_hallway"),
-        30232 => Some("room_dw_teevie_ribbicks_a"),
-        30233 => Some("room_dw_teevie_ribbicks_b"),
-        30234 => Some("room_dw_teevie_susiebridge"),
-        30235 => Some("room_dw_teevie_shuttahmaze"),
-        30236 => Some("room_dw_teevie_stealth_d"),
-        30237 => Some("room_dw_teevie_dust"),
-        30238 => Some("room_dw_teevie_dust_south"),
-        30239 => Some("room_ch3_gacharoom_unknown"),
-        30240 => Some("room_dw_ranking_t"),
-        30241 => Some("room_dw_puzzlecloset_1a"),
-        30242 => Some("room_dw_rhythm_countdown"),
-        30243 => Some("room_dw_snow_zone_east_door"),
-        30244 => Some("room_dw_inbetweenhall"),
-        // endregion
-        // region Chapter 4 rooms
-        40014 => Some("room_krisroom"),
-        40015 => Some("room_krishallway"),
-        40016 => Some("room_torroom"),
-        40017 => Some("room_torhouse"),
-        40018 => Some("room_torbathroom"),
-        40019 => Some("room_town_krisyard"),
-        40020 => Some("room_town_northwest"),
-        40021 => Some("room_town_north"),
-        40022 => Some("room_beach"),
-        40023 => Some("room_town_mid"),
-        40024 => Some("room_town_apartments"),
-        40025 => Some("room_town_south"),
-        40026 => Some("room_town_school"),
-        40027 => Some("room_town_church"),
-        40028 => Some("room_graveyard"),
-        40029 => Some("room_town_shelter"),
-        40030 => Some("room_hospital_lobby"),
-        40031 => Some("room_hospital_hallway"),
-        40032 => Some("room_hospital_rudy"),
-        40033 => Some("room_hospital_room2"),
-        40034 => Some("room_diner"),
-        40035 => Some("room_townhall"),
-        40036 => Some("room_flowershop_1f"),
-        40037 => Some("room_flowershop_2f"),
-        40038 => Some("room_library"),
-        40039 => Some("room_alphysalley"),
-        40040 => Some("room_lw_computer_lab"),
-        40041 => Some("room_lw_library_upstairs"),
-        40042 => Some("room_lw_police"),
-        40043 => Some("room_lw_conbini"),
-        40044 => Some("room_lw_icee_pizza"),
-        40045 => Some("room_lw_church_entrance"),
-        40046 => Some("room_lw_church_main"),
-        40047 => Some("room_lw_noellehouse_main"),
-        40048 => Some("room_lw_noellehouse_kitchen"),
-        40049 => Some("room_lw_noellehouse_bathroom"),
-        40050 => Some("room_lw_noellehouse_keyroom"),
-        40051 => Some("room_lw_noellehouse_noelle"),
-        40052 => Some("room_lw_noellehouse_dess"),
-        40053 => Some("room_lw_noellehouse_closet"),
-        40054 => Some("room_torielclass"),
-        40055 => Some("room_schoollobby"),
-        40056 => Some("room_alphysclass"),
-        40057 => Some("room_schooldoor"),
-        40058 => Some("room_insidecloset"),
-        40059 => Some("room_school_unusedroom"),
-        40060 => Some("room_castle_tutorial"),
-        40061 => Some("room_dw_castle_east_door"),
-        40062 => Some("room_dw_castle_west_cliff"),
-        40063 => Some("room_dw_castle_area_1"),
-        40064 => Some("room_dw_castle_town"),
-        40065 => Some("room_dw_ralsei_castle_front"),
-        40066 => Some("room_dw_castle_restaurant"),
-        40067 => Some("room_dw_castle_cafe"),
-        40068 => Some("room_dw_castle_dojo"),
-        40069 => Some("room_dw_ralsei_castle_1f"),
-        40070 => Some("room_dw_ralsei_castle_2f"),
-        40071 => Some("room_dw_castle_dungeon"),
-        40072 => Some("room_dw_castle_rooms_kris"),
-        40073 => Some("room_dw_castle_rooms_susie"),
-        40083 => Some("PLACE_MENU"),
-        40088 => Some("room_cc_lancer"),
-        40089 => Some("room_cc_clover"),
-        40090 => Some("room_cc_fountain"),
-        40101 => Some("room_dw_church_slidingbookshelf"),
-        40104 => Some("room_dw_church_bellplay"),
-        40105 => Some("room_intro_ch4"),
-        40106 => Some("room_dw_church_candlelighting"),
-        40107 => Some("room_town_noellehouse"),
-        40108 => Some("room_dw_church_intro1"),
-        40109 => Some("room_dw_church_intro3"),
-        40110 => Some("room_dw_church_intro_guei"),
-        40113 => Some("room_dw_church_lantern_hallway"),
-        40115 => Some("room_dw_church_gersonstudy"),
-        40116 => Some("room_dw_church_stairs_west_bell"),
-        40117 => Some("room_dw_church_librarybookenemy"),
-        40118 => Some("room_dw_church_bookshelfpuzzle1"),
-        40119 => Some("room_dw_church_bookshelfpuzzle2"),
-        40120 => Some("room_dw_church_worshiproom"),
-        40121 => Some("room_dw_church_swingingbell"),
-        40122 => Some("room_dw_church_tallbookcases"),
-        40123 => Some("room_dw_church_lantern2"),
-        40124 => Some("room_dw_church_smallbells"),
-        40125 => Some("room_dw_church_claw"),
-        40126 => Some("room_dw_church_shelfclimb1"),
-        40127 => Some("room_dw_church_statueclimb"),
-        40128 => Some("room_dw_church_offering"),
-        40129 => Some("room_dw_church_npcroom_shelfclimb"),
-        40130 => Some("room_dw_church_stairs_stainedglass"),
-        40131 => Some("room_dw_church_bellsareawest"),
-        40132 => Some("room_dw_church_stairs_topright"),
-        40133 => Some("room_dw_church_poolsroom1"),
-        40134 => Some("room_dw_church_candlesroom1"),
-        40135 => Some("room_dw_church_solowaterfall"),
-        40136 => Some("room_dw_church_statueroom"),
-        40137 => Some("room_dw_church_poolsroom2"),
-        40138 => Some("room_dw_church_shelfclimb2"),
-        40139 => Some("room_dw_church_statueclimb_npcroom"),
-        40140 => Some("room_dw_church_bellhall_west"),
-        40141 => Some("room_dw_church_fountainconnection"),
-        40142 => Some("room_dw_church_stainedglasspreview"),
-        40143 => Some("room_dw_church_stairs_topleft"),
-        40144 => Some("room_dw_church_npcroom_pools1"),
-        40145 => Some("room_dw_church_poolsroom1_east"),
-        40146 => Some("room_dw_church_minorlegend"),
-        40147 => Some("room_dw_church_bookenemywest"),
-        40148 => Some("room_dw_church_poolsroom2south"),
-        40149 => Some("room_lw_church_office"),
-        40150 => Some("room_dw_church_bellhall_east"),
-        40151 => Some("room_dw_church_bellhall_central"),
-        40154 => Some("room_dw_ralsei_castle_3f"),
-        40155 => Some("room_dw_castle_rooms_ralsei"),
-        40156 => Some("room_dw_rotating_tower"),
-        40159 => Some("room_krisroom_dark"),
-        40162 => Some("room_lw_noellehouse_vents_west"),
-        40166 => Some("room_town_krisyard_dark"),
-        40167 => Some("room_dw_castle_rooms_queen"),
-        40168 => Some("room_dw_castle_rooms_tenna"),
-        40171 => Some("room_dw_church_ripplepuzzle"),
-        40173 => Some("room_dw_church_glass"),
-        40174 => Some("room_dw_church_staircase"),
-        40175 => Some("room_dw_church_bellhall_bookroom"),
-        40176 => Some("room_dw_church_bookcase"),
-        40177 => Some("room_dw_church_savepoint"),
-        40178 => Some("room_dw_church_dark_fire_puzzle"),
-        40179 => Some("room_dw_church_turtles"),
-        40180 => Some("room_dw_church_guei"),
-        40181 => Some("room_dw_church_jackenstein"),
-        40182 => Some("room_dw_church_bellhall_curtain"),
-        40184 => Some("room_dw_church_dogclimb"),
-        40185 => Some("room_dw_church_arena"),
-        40192 => Some("room_dw_churchb_nongerson_post"),
-        40193 => Some("room_lw_noellehouse_vents_north"),
-        40194 => Some("room_lw_noellehouse_vents_east"),
-        40195 => Some("room_lw_noellehouse_vents_south"),
-        40205 => Some("room_dw_church_climbtut"),
-        40206 => Some("room_dw_church_fastwater"),
-        40207 => Some("room_dw_church_crumbletower"),
-        40208 => Some("room_dw_church_tower1"),
-        40209 => Some("room_lw_church_choir"),
-        40210 => Some("room_dw_church_bellclimb"),
-        40211 => Some("room_dw_church_shiftclimb"),
-        40212 => Some("room_dw_church_intropiano"),
-        40213 => Some("room_dw_church_intro_gerson"),
-        40214 => Some("room_dw_church_pianopiece_left"),
-        40215 => Some("room_dw_church_northprophecies"),
-        40216 => Some("room_dw_church_darkmaze"),
-        40217 => Some("room_dw_church_quicktest"),
-        40218 => Some("room_dw_church_pianopiece_right"),
-        40219 => Some("room_dw_church_pianopuzzle"),
-        40220 => Some("room_dw_church_nwconnect"),
-        40221 => Some("room_dw_church_rightconnect"),
-        40222 => Some("room_dw_church_stairspreview"),
-        40223 => Some("room_dw_church_trueclimbadventure"),
-        40224 => Some("room_dw_church_organpuzzle"),
-        40225 => Some("room_dw_church_mizzleencounter"),
-        40226 => Some("room_dw_church_sideclimb"),
-        40227 => Some("room_dw_church_secretpiano"),
-        40228 => Some("room_dw_church_ripplepuzzle_postgers"),
-        40229 => Some("room_dw_church_tallbookcases_backup"),
-        40230 => Some("room_dw_church_fountain"),
-        40231 => Some("room_dw_church_b_intro"),
-        40232 => Some("room_dw_churchc_final_prophecy"),
-        40233 => Some("room_dw_church_bookshelfpuzzle"),
-        40234 => Some("room_lw_noellehouse_basement"),
-        40235 => Some("room_dw_church_biblioxencounter"),
-        40236 => Some("room_dw_church_darkclimb"),
-        40237 => Some("room_dw_church_moneyfountain"),
-        40239 => Some("room_dw_church_pianopiece_left_b"),
-        40240 => Some("room_dw_church_pianopiece_rightprophecy"),
-        40242 => Some("room_dw_church_shadowgerson"),
-        40243 => Some("room_dw_church_holywatercooler"),
-        40244 => Some("room_dw_church_rippleworship"),
-        40245 => Some("room_dw_church_waterfallroom"),
-        40246 => Some("room_dw_church_waterfalltearoom"),
-        40247 => Some("room_dw_castle_rooms_kris_susie"),
-        40248 => Some("room_dw_churchb_staircaseintro"),
-        40249 => Some("room_dw_churchb_prophecyencounter"),
-        40250 => Some("room_dw_churchb_worshiproom"),
-        40251 => Some("room_dw_churchb_libraryconnector"),
-        40252 => Some("room_dw_churchb_savepoint"),
-        40253 => Some("room_dw_churchb_rotatingtower"),
-        40254 => Some("room_dw_churchb_bellroom"),
-        40255 => Some("room_dw_churchb_escherstaircase"),
-        40256 => Some("room_dw_churchb_prophecymaze"),
-        40257 => Some("room_dw_churchb_rotatingtower2"),
-        40258 => Some("room_dw_churchb_gersonstudy"),
-        40259 => Some("room_dw_churchb_gersonchase"),
-        40260 => Some("room_dw_churchb_extinguisher"),
-        40261 => Some("room_dw_church_knightclimb"),
-        40262 => Some("room_dw_church_knightclimb_post"),
-        40263 => Some("room_dw_rhythm"),
-        40264 => Some("room_dw_rhythm_countdown"),
-        40265 => Some("room_dw_rhythm_empty"),
-        40266 => Some("room_rhythmgame_editor"),
-        40267 => Some("room_dw_churchb_ripple1"),
-        40268 => Some("room_dw_churchb_ripplepost"),
-        40269 => Some("room_dw_churchb_library_alternate"),
-        40270 => Some("room_dw_churchb_moneyfountain"),
-        40271 => Some("room_dw_churchc_superprophecies"),
-        40272 => Some("room_dw_churchc_encounter1"),
-        40273 => Some("room_dw_churchc_slidingpiano"),
-        40274 => Some("room_dw_churchc_ripplesneak_poc"),
-        40275 => Some("room_dw_churchc_encounter2"),
-        40276 => Some("room_dw_churchc_finalclimb"),
-        40277 => Some("room_dw_churchc_angelprophecy_encounter"),
-        40278 => Some("room_dw_churchc_savepoint"),
-        40279 => Some("room_dw_churchc_darkswords"),
-        40280 => Some("room_dw_churchc_pretitan"),
-        40281 => Some("room_dw_churchc_prepretitan"),
-        40282 => Some("room_dw_churchc_titandefeated"),
-        40283 => Some("room_dw_churchb_fountain"),
-        40284 => Some("room_dw_church_ripseq1"),
-        40285 => Some("room_dw_church_ripseq2"),
-        40286 => Some("room_dw_churchc_titanclimb1"),
-        40287 => Some("room_dw_churchc_titanclimb2"),
-        40291 => Some("room_lw_noellehouse_north"),
-        40292 => Some("room_lw_noellehouse_vents_north_west"),
-        40296 => Some("room_dw_churchb_fireplace"),
-        40297 => Some("room_dw_churchb_nongerson"),
-        40298 => Some("room_dw_churchb_library"),
-        40299 => Some("room_dw_castle_tv_rhythm"),
-        40300 => Some("room_dw_castle_tv"),
-        40301 => Some("room_dw_churchb_windows"),
-        40302 => Some("room_dw_churchb_bookshelf"),
-        40303 => Some("room_dw_churchb_man"),
-        40304 => Some("room_dw_churchb_gallery"),
-        40305 => Some("room_dw_churchb_rotatingtower_old"),
-        40306 => Some("room_dw_churchb_darkclimb"),
-        40307 => Some("room_dw_churchc_titanclimb1_post"),
-        40308 => Some("room_dw_churchc_titanclimb2_post"),
-        40309 => Some("room_dw_churchb_darkclimb_scene"),
-        40310 => Some("room_dw_churchc_dodge"),
-        40311 => Some("room_dw_churchc_insidetitan"),
-        40313 => Some("room_dw_churchc_prophecies"),
-        40314 => Some("room_dw_churchc_angelprophecy"),
-        40315 => Some("room_dw_church_bookshelfpuzzle_rev"),
-        40317 => Some("room_dw_churchc_treasurechest"),
-        40321 => Some("room_dw_castle_tv_zone_1"),
-        40322 => Some("room_dw_castle_tv_zone_2"),
-        40323 => Some("room_dw_castle_tv_zone_3"),
-        40324 => Some("room_dw_castle_tv_zone_battle"),
-        40325 => Some("room_dw_castle_tv_zone_minigame"),
-        // endregion
-        _ => None,
+
+static ROOM_LISTS: [&str; CHAPTER_COUNT] = [
+    include_str!("../../../research/rooms/chapter1_rooms.csv"),
+    include_str!("../../../research/rooms/chapter2_rooms.csv"),
+    include_str!("../../../research/rooms/chapter3_rooms.csv"),
+    include_str!("../../../research/rooms/chapter4_rooms.csv"),
+];
+
+// Point a chapter at its `chapterN_scr_roomname.gml` once it's checked in, see
+// `is_roomname_script`.
+static ROOM_NAMES: [&str; CHAPTER_COUNT] = [
+    include_str!("../../../research/rooms/chapter1_roomnames.csv"),
+    include_str!("../../../research/rooms/chapter2_roomnames.csv"),
+    include_str!("../../../research/rooms/chapter3_roomnames.csv"),
+    include_str!("../../../research/rooms/chapter4_roomnames.csv"),
+];
+
+/// Whether bundled room names are an export of `scr_roomname` rather than the partial CSV, which
+/// starts with its header.
+fn is_roomname_script(room_names: &str) -> bool {
+    !room_names.starts_with("room,name")
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum RoomImportError {
+    #[error("line {line}: expected `{expected}`, got `{content}`")]
+    Malformed {
+        line: usize,
+        expected: &'static str,
+        content: CompactString,
+    },
+    #[error("line {line}: room {room} is not in the room list")]
+    UnknownRoom { line: usize, room: CompactString },
+}
+
+/// The rooms of one chapter.
+#[derive(Debug, Default)]
+pub struct RoomTable<'a> {
+    rooms: BTreeMap<i32, &'a str>,
+    names: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> RoomTable<'a> {
+    /// Imports a room list and a `scr_roomname` mapping, both CSV with a header line.
+    pub fn import(room_list: &'a str, room_names: &'a str) -> Result<Self, RoomImportError> {
+        let mut table = RoomTable::default();
+
+        for (line, index, room) in csv_rows(room_list, "index,room") {
+            let index = index.parse::<i32>().map_err(|_| RoomImportError::Malformed {
+                line,
+                expected: "index,room",
+                content: format_compact!("{index},{room}"),
+            })?;
+            table.rooms.insert(index, room);
+        }
+
+        for (line, room, name) in csv_rows(room_names, "room,name") {
+            table.add_name(line, room, name)?;
+        }
+
+        Ok(table)
+    }
+
+    /// Imports a room list like [`import`](Self::import), with the names read from the
+    /// `scr_roomname` script decompiled by UndertaleModTool.
+    ///
+    /// The script assigns `roomname` after comparing its argument with a room, either as
+    /// `if (argument0 == room_krisroom)` or `case room_krisroom:`. The name can be a plain
+    /// string or the first argument of `stringsetloc`.
+    pub fn import_script(
+        room_list: &'a str,
+        roomname_script: &'a str,
+    ) -> Result<Self, RoomImportError> {
+        let mut table = RoomTable::import(room_list, "")?;
+
+        let mut room = None;
+        for (i, content) in roomname_script.lines().enumerate() {
+            let line = i + 1;
+
+            if let Some(found) = compared_room(content) {
+                room = Some(found);
+            }
+
+            let Some((_, assigned)) = content.split_once("roomname =") else {
+                continue;
+            };
+            // Skips the default set before any room is compared, and names that aren't literals.
+            if let (Some(room), Some(name)) = (room, string_literal(assigned)) {
+                table.add_name(line, room, name)?;
+            }
+        }
+
+        Ok(table)
+    }
+
+    fn add_name(&mut self, line: usize, room: &str, name: &'a str) -> Result<(), RoomImportError> {
+        let Some(&room) = self.rooms.values().find(|&&r| r == room) else {
+            return Err(RoomImportError::UnknownRoom {
+                line,
+                room: room.into(),
+            });
+        };
+        self.names.insert(room, name);
+        Ok(())
+    }
+
+    /// The internal name of the room at the given index, like `room_krisroom`.
+    pub fn room(&self, index: i32) -> Option<&'a str> {
+        self.rooms.get(&index).copied()
+    }
+
+    /// The name shown in the save menu for the room at the given index.
+    pub fn name(&self, index: i32) -> Option<&'a str> {
+        self.room(index).and_then(|room| self.names.get(room).copied())
     }
+
+    /// The room with the lowest index.
+    pub fn first(&self) -> Option<i32> {
+        self.rooms.keys().next().copied()
+    }
+
+    /// Finds the index of a room by its internal name, ignoring ASCII case.
+    pub fn find(&self, room: &str) -> Option<i32> {
+        self.rooms
+            .iter()
+            .find(|(_, r)| r.eq_ignore_ascii_case(room))
+            .map(|(&index, _)| index)
+    }
+}
+
+/// Splits CSV lines into their line number and two columns, skipping the header and empty lines.
+/// Only the first comma separates, so the second column may contain commas.
+fn csv_rows<'a>(
+    csv: &'a str,
+    header: &'static str,
+) -> impl Iterator<Item = (usize, &'a str, &'a str)> {
+    csv.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end()))
+        .filter(move |&(_, line)| !line.is_empty() && line != header)
+        .map(|(line, content)| {
+            let (a, b) = content.split_once(',').unwrap_or((content, ""));
+            (line, a, b)
+        })
+}
+
+/// The room a line of `scr_roomname` compares its argument with, if any.
+fn compared_room(line: &str) -> Option<&str> {
+    let rest = match line.split_once("== ") {
+        Some((_, rest)) => rest,
+        None => line.trim_start().strip_prefix("case ")?,
+    };
+    let end = rest
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .unwrap_or(rest.len());
+    let room = &rest[..end];
+    (room.starts_with("room_") || room.starts_with("ROOM_") || room.starts_with("PLACE_"))
+        .then_some(room)
+}
+
+/// The first string literal in a GML expression, without its quotes. Names containing escapes
+/// aren't supported, none of the chapters have one.
+pub(super) fn string_literal(expression: &str) -> Option<&str> {
+    let expression = expression.trim_start();
+    let expression = expression.strip_prefix("stringsetloc(").unwrap_or(expression);
+    let rest = expression.strip_prefix('"')?;
+    rest.split_once('"').map(|(literal, _)| literal)
+}
+
+fn room_tables() -> &'static [RoomTable<'static>] {
+    static TABLES: OnceLock<Vec<RoomTable<'static>>> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        ROOM_LISTS
+            .iter()
+            .zip(ROOM_NAMES.iter())
+            .map(|(list, names)| {
+                if is_roomname_script(names) {
+                    RoomTable::import_script(list, names)
+                } else {
+                    RoomTable::import(list, names)
+                }
+                .expect("bundled room tables are valid")
+            })
+            .collect()
+    })
+}
+
+/// Returns the room table of a chapter.
+pub fn room_table(chapter_id: i32) -> Option<&'static RoomTable<'static>> {
+    let index = usize::try_from(chapter_id).ok()?.checked_sub(1)?;
+    room_tables().get(index)
+}
+
+/// Returns the internal room name of a full room ID (chapter times 10000 plus index).
+pub fn try_get_room_id(room_index: i32) -> Option<&'static str> {
+    registry()
+        .room(room_index)
+        .or_else(|| room_table(room_index / 10000)?.room(room_index % 10000))
+}
+
+/// The room each chapter starts in, by internal name. Chapter 3 starts in the TV World.
+static START_ROOMS: [&str; CHAPTER_COUNT] = [
+    "room_krisroom",
+    "room_krisroom",
+    "room_dw_couch_overworld_intro",
+    "room_krisroom",
+];
+
+/// The room a chapter starts in, for new saves and saves imported from the previous chapter.
+/// Falls back to the first room for chapters without a known start room.
+pub fn start_room(chapter_id: i32) -> RoomIndex {
+    let start = usize::try_from(chapter_id)
+        .ok()
+        .and_then(|chapter| START_ROOMS.get(chapter.checked_sub(1)?));
+    let index = room_table(chapter_id)
+        .and_then(|table| start.and_then(|room| table.find(room)).or_else(|| table.first()))
+        .unwrap_or_default();
+    RoomIndex(chapter_id * 10000 + index)
 }
 
-// Extracted from scr_roomname in gml_GlobalScript_scr_roomname using UndertaleModTool
-// TODO Parse room IDs from exported data instead of guessing
+/// Returns the save menu name of a room in the given chapter.
 pub fn try_get_room_name(chapter_id: i32, room_index: i32) -> Option<&'static str> {
-    match chapter_id {
-        1 => match room_index - 281 {
-            0 => Some("---"),
-            2 => Some("Kris's Room"),
-            35 => Some("??????"),
-            40 => Some("Eye Puzzle"),
-            45 => Some("Castle Town"),
-            49 => Some("Field - Great Door"),
-            59 => Some("Field - Seam's Shop"),
-            68 => Some("Field - Great Board"),
-            71 => Some("Field - Great Board 2"),
-            73 => Some("Forest - Entrance"),
-            82 => Some("Forest - Bake Sale"),
-            90 => Some("Forest - Before Maze"),
-            96 => Some("Forest - After Maze"),
-            97 => Some("Forest - Thrashing Room"),
-            107 => Some("Card Castle - Prison"),
-            114 => Some("Card Castle - 1F"),
-            123 => Some("Card Castle - 5F"),
-            126 => Some("Card Castle - Throne"),
-            111 => Some("Card Castle - ???"),
-            56 => Some("Field - Maze of Death"),
-            _ => None,
-        },
-        2 => match room_index - 10 {
-            0 => Some("---"),
-            18 => Some("Kris's Room"),
-            55 => Some("Castle Town"),
-            61 => Some("Castle Town"),
-            62 => Some("My Castle Town"),
-            78 => Some("Cyber Field - Entrance"),
-            83 => Some("Cyber Field - Arcade Machine"),
-            89 => Some("Cyber Field - Music Shop"),
-            95 => Some("Dark World?"),
-            112 => Some("Cyber City - Entrance"),
-            115 => Some("Cyber City - First Alleyway"),
-            126 => Some("Cyber City - Mouse Alley"),
-            121 => Some("Cyber City - Music Shop"),
-            128 => Some("Cyber City - Second Alleyway"),
-            133 => Some("Cyber City - Heights"),
-            152 => Some("Queen's Mansion - Guest Hall"),
-            157 => Some("Queen's Mansion - Entrance"),
-            187 => Some("Queen's Mansion - 3F"),
-            193 => Some("Queen's Mansion - Acid Tunnel"),
-            171 => Some("Queen's Mansion - Basement"),
-            196 => Some("Queen's Mansion - 4F"),
-            199 | 200 => Some("Queen's Mansion - Rooftop"),
-            _ => None,
-        },
-        3 => match room_index {
-            0 => Some("---"),
-            98 => Some("Dark World"),
-            135 => Some("Green Room"),
-            195 => Some("TV World - Before the Show"),
-            109 => Some("Cold Place"),
-            196 => Some("TV World - Entrance"),
-            178 => Some("TV World - Goulden Sam"),
-            209 => Some("TV World - Concert"),
-            214 => Some("TV World - Cooking Show"),
-            _ => None,
-        },
-        4 => match room_index {
-            0 => Some("---"),
-            14 => Some("Kris's Room"),
-            23 => Some("Hometown"),
-            48 => Some("Noelle's House"),
-            64 => Some("My Castle Town"),
-            108 => Some("Dark Sanctuary - Atrium"),
-            177 => Some("Dark Sanctuary - Lower Left"),
-            228 => Some("Dark Sanctuary - Lower Right"),
-            115 => Some("Dark Sanctuary - Study"),
-            220 => Some("Dark Sanctuary - Library"),
-            219 => Some("Dark Sanctuary - Small Piano"),
-            248 => Some("2nd Sanctuary - Atrium"),
-            252 => Some("2nd Sanctuary - Floor"),
-            258 => Some("2nd Sanctuary - Study"), // Actual study
-            306 => Some("2nd Sanctuary - Study"), // Before the actual study
-            271 => Some("3rd Sanctuary"),
-            278 => Some("3rd Sanctuary - Stairs"),
-            281 => Some("3rd Sanctuary - Last Chamber"),
-            322 => Some("MIKE ZONE"),
-            298 => Some("Castle Town - TV Building"),
-            _ => None,
-        },
-        _ => None,
+    let room = try_get_room_id(chapter_id * 10000 + room_index)?;
+    registry()
+        .room_name(chapter_id, room)
+        .or_else(|| room_table(chapter_id)?.name(room_index))
+}
+
+/// Resolves the save menu name of a full room ID (chapter times 10000 plus index). Rooms only
+/// exist in their own chapter.
+pub fn try_get_room_name_in(
+    room_id: i32,
+    context: &GameContext,
+) -> Result<&'static str, LookupError> {
+    let room = RoomIndex(room_id);
+    if room.chapter() != context.chapter {
+        return Err(LookupError::NotInChapter {
+            kind: RoomIndex::KIND,
+            id: room_id,
+            chapter: context.chapter,
+        });
     }
+
+    room.name().ok_or(LookupError::Unknown {
+        kind: RoomIndex::KIND,
+        id: room_id,
+    })
+}
+
+/// Area prefixes of each chapter's Dark World rooms, most specific first.
+static DARK_WORLD_AREAS: [&[(&str, &str)]; CHAPTER_COUNT] = [
+    &[
+        ("room_dark", "Dark World entrance"),
+        ("room_castle_", "Castle Town"),
+        ("room_field", "Field of Hopes and Dreams"),
+        ("room_forest", "Scarlet Forest"),
+        ("room_cc_", "Card Castle"),
+    ],
+    &[
+        ("room_dw_cyber", "Cyber Field"),
+        ("room_dw_city", "Cyber City"),
+        ("room_dw_mansion", "Queen's Mansion"),
+        ("room_dw_castle", "Castle Town"),
+        ("room_dw_ralsei", "Castle Town"),
+    ],
+    &[
+        ("room_board", "Game Board"),
+        ("room_shadowmantle", "Game Board"),
+        ("room_dw_", "TV World"),
+    ],
+    &[
+        ("room_dw_church", "Dark Sanctuary"),
+        ("room_dw_castle", "Castle Town"),
+        ("room_dw_ralsei", "Castle Town"),
+    ],
+];
+
+/// Prefixes of the Light World rooms, which are the same in every chapter.
+static LIGHT_WORLD_PREFIXES: &[&str] = &[
+    "room_krisroom",
+    "room_krishallway",
+    "room_tor",
+    "room_town_",
+    "room_beach",
+    "room_graveyard",
+    "room_hospital_",
+    "room_diner",
+    "room_townhall",
+    "room_flowershop",
+    "room_library",
+    "room_alphysalley",
+    "room_alphysclass",
+    "room_schoollobby",
+    "room_schooldoor",
+    "room_school_",
+    "room_insidecloset",
+    "room_lw_",
+];
+
+/// Returns the area a full room ID (chapter times 10000 plus index) belongs to, like
+/// "Card Castle" or "Hometown" for the Light World. Debug and cutscene-only rooms have no area.
+pub fn try_get_room_area(room_index: i32) -> Option<&'static str> {
+    let room = try_get_room_id(room_index)?;
+    let chapter = usize::try_from(room_index / 10000).ok()?.checked_sub(1)?;
+
+    DARK_WORLD_AREAS
+        .get(chapter)?
+        .iter()
+        .find(|(prefix, _)| room.starts_with(prefix))
+        .map(|&(_, area)| area)
+        .or_else(|| {
+            LIGHT_WORLD_PREFIXES
+                .iter()
+                .any(|prefix| room.starts_with(prefix))
+                .then_some("Hometown")
+        })
 }
 
 pub fn display_room(room_index: i32) -> CompactString {
@@ -897,3 +350,190 @@ pub fn display_room(room_index: i32) -> CompactString {
         (index, _, _) => format_compact!("Unknown room {index}", index = index),
     }
 }
+
+/// A room, as stored in saves: the chapter times 10000 plus the room's index in that chapter.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RoomIndex(pub i32);
+
+impl RoomIndex {
+    pub const KIND: &'static str = "room";
+
+    pub fn chapter(self) -> i32 {
+        self.0 / 10000
+    }
+
+    pub fn index(self) -> i32 {
+        self.0 % 10000
+    }
+
+    /// The internal room name, like `room_krisroom`.
+    pub fn id_name(self) -> Option<&'static str> {
+        try_get_room_id(self.0)
+    }
+
+    /// The name shown in the game's save menu.
+    pub fn name(self) -> Option<&'static str> {
+        try_get_room_name(self.chapter(), self.index())
+    }
+
+    /// The save menu name, or why this room can't be visited in the given context.
+    pub fn name_in(self, context: &GameContext) -> Result<&'static str, LookupError> {
+        try_get_room_name_in(self.0, context)
+    }
+
+    /// The area of the chapter this room is in.
+    pub fn area(self) -> Option<&'static str> {
+        try_get_room_area(self.0)
+    }
+}
+
+impl From<i32> for RoomIndex {
+    fn from(id: i32) -> Self {
+        RoomIndex(id)
+    }
+}
+
+impl From<RoomIndex> for i32 {
+    fn from(room: RoomIndex) -> Self {
+        room.0
+    }
+}
+
+impl Display for RoomIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&display_room(self.0))
+    }
+}
+
+impl FromStr for RoomIndex {
+    type Err = UnknownNameError;
+
+    /// Parses a room number or an internal room name. Rooms that exist in multiple chapters
+    /// resolve to the earliest one.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if let Ok(id) = s.parse::<i32>() {
+            return Ok(RoomIndex(id));
+        }
+
+        registry()
+            .find_room(s)
+            .or_else(|| {
+                (1..=CHAPTER_COUNT as i32)
+                    .find_map(|chapter| Some(chapter * 10000 + room_table(chapter)?.find(s)?))
+                    .map(RoomIndex)
+            })
+            .ok_or_else(|| UnknownNameError {
+                kind: Self::KIND,
+                name: s.into(),
+            })
+    }
+}
+
+impl Serializable for RoomIndex {
+    fn serialize(&self) -> CompactString {
+        self.0.serialize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_tables() {
+        assert_eq!(room_tables().len(), CHAPTER_COUNT);
+        assert_eq!(try_get_room_id(10283), Some("room_krisroom"));
+        assert_eq!(try_get_room_id(20011), Some("ROOM_INITIALIZE"));
+        assert_eq!(try_get_room_id(40083), Some("PLACE_MENU"));
+        assert_eq!(try_get_room_name(1, 283), Some("Kris's Room"));
+        assert_eq!(try_get_room_name(5, 283), None);
+        assert_eq!("room_krisroom".parse::<RoomIndex>(), Ok(RoomIndex(10283)));
+    }
+
+    #[test]
+    fn test_start_rooms() {
+        assert_eq!(start_room(1), RoomIndex(10283));
+        assert_eq!(start_room(2), RoomIndex(20028));
+        assert_eq!(start_room(3), RoomIndex(30097));
+        assert_eq!(start_room(4), RoomIndex(40014));
+    }
+
+    #[test]
+    fn test_room_areas() {
+        assert_eq!(RoomIndex(10283).area(), Some("Hometown"));
+        assert_eq!(RoomIndex(10330).area(), Some("Field of Hopes and Dreams"));
+        assert_eq!(RoomIndex(20084).area(), Some("Cyber Field"));
+        assert_eq!(RoomIndex(20006).area(), None);
+    }
+
+    #[test]
+    fn test_import_errors() {
+        assert_eq!(
+            RoomTable::import("index,room\nfirst,room_a\n", "").err(),
+            Some(RoomImportError::Malformed {
+                line: 2,
+                expected: "index,room",
+                content: "first,room_a".into(),
+            })
+        );
+        assert_eq!(
+            RoomTable::import("index,room\n1,room_a\n", "room,name\nroom_b,Room B\n").err(),
+            Some(RoomImportError::UnknownRoom { line: 2, room: "room_b".into() })
+        );
+
+        let table = RoomTable::import("1,room_a\n", "room_a,Room A, upstairs\n").unwrap();
+        assert_eq!(table.name(1), Some("Room A, upstairs"));
+    }
+
+    #[test]
+    fn test_import_script() {
+        let rooms = "index,room\n1,room_a\n2,room_b\n3,room_c\n4,PLACE_MENU\n";
+        let script = indoc::indoc! {r#"
+            function scr_roomname(argument0)
+            {
+                roomname = " ";
+                if (argument0 == room_a)
+                    roomname = stringsetloc("Room A, upstairs", "scr_roomname_slash_scr_roomname_gml_0_0");
+                if (argument0 == room_b) roomname = "Room B";
+                switch (argument0)
+                {
+                    case room_c:
+                        roomname = stringsetloc("Room C", "scr_roomname_slash_scr_roomname_gml_1_0");
+                        break;
+                }
+                return roomname;
+            }
+        "#};
+
+        let table = RoomTable::import_script(rooms, script).unwrap();
+        assert_eq!(table.name(1), Some("Room A, upstairs"));
+        assert_eq!(table.name(2), Some("Room B"));
+        assert_eq!(table.name(3), Some("Room C"));
+        assert_eq!(table.name(4), None);
+
+        assert_eq!(
+            RoomTable::import_script(rooms, "if (argument0 == room_d)\n    roomname = \"D\";").err(),
+            Some(RoomImportError::UnknownRoom { line: 2, room: "room_d".into() })
+        );
+    }
+
+    /// Only chapters whose names come from an export of `scr_roomname` are expected to name
+    /// every room, the CSV files are partial.
+    #[test]
+    fn test_every_room_has_a_name() {
+        for (chapter, table) in room_tables().iter().enumerate() {
+            if !is_roomname_script(ROOM_NAMES[chapter]) {
+                continue;
+            }
+            let unnamed = table
+                .rooms
+                .iter()
+                .filter(|(_, room)| !table.names.contains_key(*room))
+                .map(|(index, room)| format!("{index}. {room}"))
+                .collect::<Vec<_>>();
+            assert!(unnamed.is_empty(), "chapter {} rooms without a name: {:?}", chapter + 1, unnamed);
+        }
+    }
+}
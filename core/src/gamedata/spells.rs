@@ -1,7 +1,15 @@
+use super::registry::{Table, registry};
+use super::{GameContext, LookupError};
 use compact_str::{CompactString, ToCompactString, format_compact};
 
-// Extracted from gml_GlobalScript_scr_spellinfo using UndertaleModTool
 pub fn try_get_spell_name(spell_id: i32) -> Option<&'static str> {
+    registry()
+        .name(Table::Spells, spell_id)
+        .or_else(|| builtin_spell_name(spell_id))
+}
+
+// Extracted from gml_GlobalScript_scr_spellinfo using UndertaleModTool
+pub(super) fn builtin_spell_name(spell_id: i32) -> Option<&'static str> {
     match spell_id {
         0 => Some("---"),
         1 => Some("Rude Sword"),
@@ -16,14 +24,45 @@ pub fn try_get_spell_name(spell_id: i32) -> Option<&'static str> {
         10 => Some("SnowGrave"),
         // Depending on the chapter and flags, can be:
         // UltimateHeal, UltraHeal, Heal, OKHeal, BetterHeal
+        // Which one isn't worked out yet, so the placeholder is shown everywhere.
         11 => Some("* Heal"),
         _ => None,
     }
 }
 
+/// The chapter a spell can first be learned in.
+fn spell_chapter(spell_id: i32) -> i32 {
+    if let Some(chapter) = registry().chapter(Table::Spells, spell_id) {
+        return chapter;
+    }
+
+    match spell_id {
+        8..=11 => 2,
+        _ => 1,
+    }
+}
+
+/// Resolves the name of a spell in the given chapter, failing if it can't be learned yet.
+pub fn try_get_spell_name_in(
+    spell_id: i32,
+    context: &GameContext,
+) -> Result<&'static str, LookupError> {
+    let name = try_get_spell_name(spell_id);
+    context.check(SpellId::KIND, spell_id, name, spell_chapter(spell_id))
+}
+
 pub fn display_spell(spell_id: i32) -> CompactString {
     let spell_name = try_get_spell_name(spell_id);
     spell_name
         .map(|n| n.to_compact_string())
         .unwrap_or_else(|| format_compact!("Spell {}", spell_id))
 }
+
+id_type!(
+    /// A spell a party member knows.
+    SpellId,
+    "spell",
+    try_get_spell_name,
+    try_get_spell_name_in,
+    display_spell
+);
@@ -1,7 +1,17 @@
+use super::equipment::{KRIS, NOELLE, RALSEI, StatBonus, SUSIE};
+use super::party_members::PartyMember;
+use super::registry::{Table, registry};
+use super::{GameContext, LookupError};
 use compact_str::{CompactString, ToCompactString, format_compact};
 
-// Extracted from gml_GlobalScript_scr_weaponinfo
 pub fn try_get_weapon_name(weapon_id: i32) -> Option<&'static str> {
+    registry()
+        .name(Table::Weapons, weapon_id)
+        .or_else(|| builtin_weapon_name(weapon_id))
+}
+
+// Extracted from gml_GlobalScript_scr_weaponinfo
+pub(super) fn builtin_weapon_name(weapon_id: i32) -> Option<&'static str> {
     match weapon_id {
         0 => Some("---"),
         1 => Some("Wood Blade"),
@@ -39,9 +49,320 @@ pub fn try_get_weapon_name(weapon_id: i32) -> Option<&'static str> {
     }
 }
 
+/// The chapter a weapon first appears in.
+fn weapon_chapter(weapon_id: i32) -> i32 {
+    if let Some(chapter) = registry().chapter(Table::Weapons, weapon_id) {
+        return chapter;
+    }
+
+    match weapon_id {
+        11..=25 => 2,
+        26 => 3,
+        50.. => 4,
+        _ => 1,
+    }
+}
+
+pub fn try_get_weapon_name_in(
+    weapon_id: i32,
+    context: &GameContext,
+) -> Result<&'static str, LookupError> {
+    let name = try_get_weapon_name(weapon_id);
+    context.check(WeaponId::KIND, weapon_id, name, weapon_chapter(weapon_id))
+}
+
 pub fn display_weapon(weapon_id: i32) -> CompactString {
     let weapon_name = try_get_weapon_name(weapon_id);
     weapon_name
         .map(|n| n.to_compact_string())
         .unwrap_or_else(|| format_compact!("Weapon {}", weapon_id))
 }
+
+id_type!(
+    /// A dark world weapon.
+    WeaponId,
+    "weapon",
+    try_get_weapon_name,
+    try_get_weapon_name_in,
+    display_weapon
+);
+
+impl WeaponId {
+    /// Returns the weapon's metadata, or `None` for empty slots and weapons without
+    /// known metadata.
+    pub fn info(self) -> Option<&'static WeaponInfo> {
+        try_get_weapon_info(self.0)
+    }
+
+    /// Stat changes applied while this weapon is equipped.
+    pub fn bonus(self) -> StatBonus {
+        self.info().map(|info| info.bonus).unwrap_or_default()
+    }
+
+    /// Whether the given party member can hold this weapon. Anyone can hold nothing.
+    pub fn can_equip(self, member: PartyMember) -> bool {
+        self.is_empty() || self.info().is_some_and(|info| info.equippable_by.contains(&member))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct WeaponInfo {
+    pub id: i32,
+    pub bonus: StatBonus,
+    pub equippable_by: &'static [PartyMember],
+    /// Shop value of the weapon, shops buy it back for half of it.
+    pub value: i32,
+    /// Passive ability shown in the equip menu.
+    pub ability: Option<&'static str>,
+    pub description: &'static str,
+}
+
+pub fn try_get_weapon_info(weapon_id: i32) -> Option<&'static WeaponInfo> {
+    WEAPONS.iter().find(|info| info.id == weapon_id)
+}
+
+// Stats and descriptions entered by hand from the in-game equip and shop menus. They haven't been
+// checked against a dump of scr_weaponinfo yet, so weapons whose stats aren't known are left out.
+// `test_tables_match_info_scripts` checks them once the dumps are in `research/iteminfo`.
+static WEAPONS: &[WeaponInfo] = &[
+    WeaponInfo {
+        id: 1,
+        bonus: StatBonus::new(0, 0, 0),
+        equippable_by: &[KRIS],
+        value: 60,
+        ability: None,
+        description: "A wooden practice blade with a carbon-reinforced core.",
+    },
+    WeaponInfo {
+        id: 2,
+        bonus: StatBonus::new(0, 0, 0),
+        equippable_by: &[SUSIE],
+        value: 60,
+        ability: None,
+        description: "Beginner's ax forged from the mane of a dragon whelp.",
+    },
+    WeaponInfo {
+        id: 3,
+        bonus: StatBonus::new(0, 0, 0),
+        equippable_by: &[RALSEI],
+        value: 60,
+        ability: None,
+        description: "A basic scarf made of lightly magical fiber.",
+    },
+    WeaponInfo {
+        id: 4,
+        bonus: StatBonus::new(0, 0, 0),
+        equippable_by: &[KRIS, SUSIE, RALSEI],
+        value: 0,
+        ability: None,
+        description: "Debug weapon that anyone can equip.",
+    },
+    WeaponInfo {
+        id: 5,
+        bonus: StatBonus::new(2, 0, 0),
+        equippable_by: &[KRIS],
+        value: 200,
+        ability: Some("Looks spooky"),
+        description: "A black-and-orange sword with a bat hilt.",
+    },
+    WeaponInfo {
+        id: 6,
+        bonus: StatBonus::new(2, 0, 0),
+        equippable_by: &[SUSIE],
+        value: 200,
+        ability: None,
+        description: "A majestic ax made for those with courage.",
+    },
+    WeaponInfo {
+        id: 7,
+        bonus: StatBonus::new(5, 0, 4),
+        equippable_by: &[SUSIE],
+        value: 0,
+        ability: Some("Chaos"),
+        description: "The legendary blade of a certain jester.",
+    },
+    WeaponInfo {
+        id: 8,
+        bonus: StatBonus::new(4, 0, 0),
+        equippable_by: &[KRIS],
+        value: 300,
+        ability: Some("$ +5%"),
+        description: "A green sword with a clover emblem. Increases money gained.",
+    },
+    WeaponInfo {
+        id: 9,
+        bonus: StatBonus::new(2, 0, 0),
+        equippable_by: &[RALSEI],
+        value: 100,
+        ability: None,
+        description: "A rugged scarf with many scratches.",
+    },
+    WeaponInfo {
+        id: 10,
+        bonus: StatBonus::new(0, 0, 2),
+        equippable_by: &[RALSEI],
+        value: 100,
+        ability: Some("Fluffy heal"),
+        description: "A delicate scarf that strengthens healing.",
+    },
+    WeaponInfo {
+        id: 11,
+        bonus: StatBonus::new(16, 0, 0),
+        equippable_by: &[KRIS],
+        value: 0,
+        ability: None,
+        description: "A sword that seems to twist without reason.",
+    },
+    WeaponInfo {
+        id: 12,
+        bonus: StatBonus::new(0, 0, 0),
+        equippable_by: &[NOELLE],
+        value: 0,
+        ability: None,
+        description: "A ring that is cold to the touch.",
+    },
+    WeaponInfo {
+        id: 13,
+        bonus: StatBonus::new(-10, 0, 4),
+        equippable_by: &[NOELLE],
+        value: 0,
+        ability: Some("Drains HP"),
+        description: "A ring with thorns that pierce its wearer. Strengthens ice magic.",
+    },
+    WeaponInfo {
+        id: 14,
+        bonus: StatBonus::new(2, 1, 0),
+        equippable_by: &[KRIS],
+        value: 150,
+        ability: None,
+        description: "A blade with a bouncy rubber edge.",
+    },
+    WeaponInfo {
+        id: 15,
+        bonus: StatBonus::new(1, 0, 1),
+        equippable_by: &[RALSEI],
+        value: 150,
+        ability: Some("TP +"),
+        description: "A scarf that cheers its wearer on.",
+    },
+    WeaponInfo {
+        id: 16,
+        bonus: StatBonus::new(4, 0, 0),
+        equippable_by: &[KRIS],
+        value: 250,
+        ability: None,
+        description: "A mechanical sword with a humming blade.",
+    },
+    WeaponInfo {
+        id: 17,
+        bonus: StatBonus::new(4, 0, 0),
+        equippable_by: &[SUSIE],
+        value: 250,
+        ability: None,
+        description: "An ax that swings by itself, almost.",
+    },
+    WeaponInfo {
+        id: 18,
+        bonus: StatBonus::new(2, 0, 2),
+        equippable_by: &[RALSEI],
+        value: 250,
+        ability: None,
+        description: "A scarf woven from optic fibers.",
+    },
+    WeaponInfo {
+        id: 19,
+        bonus: StatBonus::new(4, 0, 0),
+        equippable_by: &[RALSEI],
+        value: 250,
+        ability: None,
+        description: "A rugged scarf, now even more rugged.",
+    },
+    WeaponInfo {
+        id: 20,
+        bonus: StatBonus::new(1, 0, 0),
+        equippable_by: &[KRIS],
+        value: 0,
+        ability: None,
+        description: "A broken sword. It could be fixed.",
+    },
+    WeaponInfo {
+        id: 21,
+        bonus: StatBonus::new(10, 0, -6),
+        equippable_by: &[RALSEI],
+        value: 0,
+        ability: None,
+        description: "A scarf with strings attached. Hurts its wearer's magic.",
+    },
+    WeaponInfo {
+        id: 22,
+        bonus: StatBonus::new(4, 0, 4),
+        equippable_by: &[NOELLE],
+        value: 0,
+        ability: Some("Strengthens Ice"),
+        description: "A ring that glows with a freezing light.",
+    },
+    WeaponInfo {
+        id: 23,
+        bonus: StatBonus::new(6, 0, 0),
+        equippable_by: &[KRIS],
+        value: 0,
+        ability: None,
+        description: "A sword with a perfect score.",
+    },
+    WeaponInfo {
+        id: 24,
+        bonus: StatBonus::new(6, 0, 0),
+        equippable_by: &[SUSIE],
+        value: 0,
+        ability: Some("Poisons"),
+        description: "An ax dripping with something green.",
+    },
+    WeaponInfo {
+        id: 25,
+        bonus: StatBonus::new(4, 0, 2),
+        equippable_by: &[RALSEI],
+        value: 0,
+        ability: None,
+        description: "A scarf that stretches as needed.",
+    },
+    WeaponInfo {
+        id: 50,
+        bonus: StatBonus::new(8, 0, 0),
+        equippable_by: &[KRIS],
+        value: 400,
+        ability: None,
+        description: "A festive blade that jingles when swung.",
+    },
+    WeaponInfo {
+        id: 51,
+        bonus: StatBonus::new(4, 0, 4),
+        equippable_by: &[RALSEI],
+        value: 400,
+        ability: None,
+        description: "A scarf bearing a mysterious mark.",
+    },
+    WeaponInfo {
+        id: 52,
+        bonus: StatBonus::new(8, 0, 0),
+        equippable_by: &[SUSIE],
+        value: 400,
+        ability: None,
+        description: "An ax of unwavering justice.",
+    },
+    WeaponInfo {
+        id: 53,
+        bonus: StatBonus::new(10, 0, 0),
+        equippable_by: &[KRIS],
+        value: 0,
+        ability: None,
+        description: "A blade shaped like a wing.",
+    },
+    WeaponInfo {
+        id: 54,
+        bonus: StatBonus::new(8, 0, 2),
+        equippable_by: &[SUSIE],
+        value: 0,
+        ability: Some("Drains HP"),
+        description: "An ax that absorbs the strength of its foes.",
+    },
+];
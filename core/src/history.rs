@@ -0,0 +1,337 @@
+use crate::gamedata::armors::ArmorId;
+use crate::gamedata::items::ItemId;
+use crate::gamedata::key_items::KeyItemId;
+use crate::gamedata::weapons::WeaponId;
+use crate::savefile::SaveData;
+use compact_str::{CompactString, ToCompactString};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum HistoryError {
+    #[error("failed to run git: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("git {command} failed: {stderr}")]
+    Git {
+        command: &'static str,
+        stderr: String,
+    },
+    #[error("git output is not valid UTF-8")]
+    Utf8,
+    #[error("unexpected git log line: {0}")]
+    LogFormat(String),
+    #[error("{0} is not inside the git repository")]
+    OutsideRepository(String),
+}
+
+/// A single commit that touched a save file.
+#[derive(Debug, Clone)]
+pub struct Revision {
+    pub commit: CompactString,
+    pub short_commit: CompactString,
+    /// Author date in strict ISO 8601 format.
+    pub date: CompactString,
+    pub subject: String,
+}
+
+// Fields are separated with the ASCII unit separator, so subjects can contain anything else.
+const LOG_FORMAT: &str = "--format=%H%x1f%h%x1f%aI%x1f%s";
+
+fn run_git(command: &'static str, args: &[&str]) -> Result<String, HistoryError> {
+    String::from_utf8(run_git_bytes(command, args)?).map_err(|_| HistoryError::Utf8)
+}
+
+fn run_git_bytes(command: &'static str, args: &[&str]) -> Result<Vec<u8>, HistoryError> {
+    let output = Command::new("git").arg(command).args(args).output()?;
+
+    if !output.status.success() {
+        return Err(HistoryError::Git {
+            command,
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(output.stdout)
+}
+
+fn parse_log_line(line: &str) -> Result<Revision, HistoryError> {
+    let mut fields = line.splitn(4, '\x1f');
+
+    match (fields.next(), fields.next(), fields.next(), fields.next()) {
+        (Some(commit), Some(short_commit), Some(date), Some(subject)) => Ok(Revision {
+            commit: commit.to_compact_string(),
+            short_commit: short_commit.to_compact_string(),
+            date: date.to_compact_string(),
+            subject: subject.to_string(),
+        }),
+        _ => Err(HistoryError::LogFormat(line.to_string())),
+    }
+}
+
+/// Lists all commits that changed the file at `path`, oldest first.
+///
+/// The path is resolved relative to the current working directory, like git itself does.
+pub fn list_revisions(path: &str) -> Result<Vec<Revision>, HistoryError> {
+    let log = run_git("log", &["--reverse", LOG_FORMAT, "--", path])?;
+
+    log.lines()
+        .filter(|line| !line.is_empty())
+        .map(parse_log_line)
+        .collect()
+}
+
+/// Reads the contents of the file at `path` as it was in the given revision, for
+/// [`SaveData::from_bytes`].
+///
+/// Relative paths are resolved against the current working directory, like [`list_revisions`]
+/// does. Absolute paths have to point inside the repository.
+pub fn read_revision(revision: &Revision, path: &str) -> Result<Vec<u8>, HistoryError> {
+    let path = if Path::new(path).is_absolute() {
+        repository_path(path)?
+    } else if path.starts_with("./") || path.starts_with("../") {
+        path.to_string()
+    } else {
+        format!("./{}", path)
+    };
+
+    run_git_bytes("show", &[&format!("{}:{}", revision.commit, path)])
+}
+
+/// Turns an absolute path into one relative to the root of the repository, which is what
+/// `git show` expects after the revision.
+fn repository_path(path: &str) -> Result<String, HistoryError> {
+    let root = run_git("rev-parse", &["--show-toplevel"])?;
+    let root = PathBuf::from(root.trim_end());
+    let root = root.canonicalize().unwrap_or(root);
+
+    // The file may not exist anymore, so fall back to resolving just its directory.
+    let absolute = Path::new(path);
+    let resolved = absolute.canonicalize().ok().or_else(|| {
+        let parent = absolute.parent()?.canonicalize().ok()?;
+        Some(parent.join(absolute.file_name()?))
+    });
+
+    resolved
+        .as_deref()
+        .unwrap_or(absolute)
+        .strip_prefix(&root)
+        .ok()
+        .and_then(|relative| relative.to_str())
+        .map(|relative| relative.replace('\\', "/"))
+        .ok_or_else(|| HistoryError::OutsideRepository(path.to_string()))
+}
+
+/// Finds the first of `len` revisions for which `check` holds, assuming it keeps holding for all
+/// later revisions.
+///
+/// `check` can return `None` for revisions that can't be tested, which are then skipped over
+/// like `git bisect skip` does.
+pub fn bisect(len: usize, mut check: impl FnMut(usize) -> Option<bool>) -> Option<usize> {
+    // Results of the revisions tested so far, so skipping never tests one twice.
+    let mut tested: Vec<Option<Option<bool>>> = vec![None; len];
+    let mut test = |i: usize| *tested[i].get_or_insert_with(|| check(i));
+
+    // The first matching revision is always in lo..=hi, where hi == len means none.
+    let mut lo = 0;
+    let mut hi = len;
+
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+
+        // Look for a testable revision starting from the middle.
+        let found = (mid..hi).find_map(|i| test(i).map(|result| (i, result)));
+
+        match found {
+            Some((i, true)) => hi = i,
+            Some((i, false)) => lo = i + 1,
+            // Nothing in mid..hi can be tested, look before the middle instead.
+            None => match (lo..mid).rev().find_map(|i| test(i).map(|result| (i, result))) {
+                Some((i, true)) => hi = i,
+                // Everything between the last failing revision and hi is untestable.
+                _ => break,
+            },
+        }
+    }
+
+    (hi < len).then_some(hi)
+}
+
+/// Something suspicious that happened between two consecutive revisions of a save.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Anomaly {
+    TimeWentBackwards { from: Duration, to: Duration },
+    PlotRegressed { from: f64, to: f64 },
+    ItemDisappeared { kind: &'static str, name: CompactString },
+}
+
+impl Display for Anomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Anomaly::TimeWentBackwards { from, to } => write!(
+                f,
+                "time played went backwards ({}s -> {}s)",
+                from.as_secs(),
+                to.as_secs()
+            ),
+            Anomaly::PlotRegressed { from, to } => {
+                write!(f, "plot value regressed ({} -> {})", from, to)
+            }
+            Anomaly::ItemDisappeared { kind, name } => write!(f, "{} {} disappeared", kind, name),
+        }
+    }
+}
+
+fn find_missing<T: Copy + Default + PartialEq + Display>(
+    output: &mut Vec<Anomaly>,
+    previous: &[T],
+    current: &[T],
+    kind: &'static str,
+) {
+    let mut current = current.to_vec();
+
+    for &item in previous.iter().filter(|&&item| item != T::default()) {
+        match current.iter().position(|&other| other == item) {
+            Some(index) => {
+                current.swap_remove(index);
+            }
+            None => output.push(Anomaly::ItemDisappeared {
+                kind,
+                name: item.to_compact_string(),
+            }),
+        }
+    }
+}
+
+fn items_and_storage(save: &SaveData) -> Vec<ItemId> {
+    let mut items = save.inventory.to_vec();
+    if let Some(storage) = &save.storage {
+        items.extend_from_slice(storage);
+    }
+    items
+}
+
+fn equipped_weapons(save: &SaveData) -> Vec<WeaponId> {
+    let mut weapons = save.weapons.clone();
+    weapons.extend(save.stats.iter().map(|s| s.weapon));
+    weapons
+}
+
+fn equipped_armors(save: &SaveData) -> Vec<ArmorId> {
+    let mut armors = save.armors.clone();
+    armors.extend(save.stats.iter().flat_map(|s| [s.armor1, s.armor2]));
+    armors
+}
+
+/// Compares two consecutive revisions of the same save and reports anything that shouldn't
+/// normally happen when just playing the game.
+///
+/// Items that were consumed are reported too, as the save doesn't tell them apart from lost ones.
+pub fn detect_anomalies(previous: &SaveData, current: &SaveData) -> Vec<Anomaly> {
+    let mut anomalies = Vec::new();
+
+    if current.time_played < previous.time_played {
+        anomalies.push(Anomaly::TimeWentBackwards {
+            from: previous.time_played_duration(),
+            to: current.time_played_duration(),
+        });
+    }
+
+    if current.plot_value < previous.plot_value {
+        anomalies.push(Anomaly::PlotRegressed {
+            from: previous.plot_value.value(),
+            to: current.plot_value.value(),
+        });
+    }
+
+    find_missing(
+        &mut anomalies,
+        &items_and_storage(previous),
+        &items_and_storage(current),
+        ItemId::KIND,
+    );
+    find_missing(
+        &mut anomalies,
+        &previous.key_items,
+        &current.key_items,
+        KeyItemId::KIND,
+    );
+    find_missing(
+        &mut anomalies,
+        &equipped_weapons(previous),
+        &equipped_weapons(current),
+        WeaponId::KIND,
+    );
+    find_missing(
+        &mut anomalies,
+        &equipped_armors(previous),
+        &equipped_armors(current),
+        ArmorId::KIND,
+    );
+
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_line() {
+        let revision =
+            parse_log_line("0123abcd\x1f0123\x1f2025-06-01T12:00:00+02:00\x1fSave: a \x1f b")
+                .unwrap();
+
+        assert_eq!(revision.commit, "0123abcd");
+        assert_eq!(revision.short_commit, "0123");
+        assert_eq!(revision.date, "2025-06-01T12:00:00+02:00");
+        assert_eq!(revision.subject, "Save: a \x1f b");
+    }
+
+    #[test]
+    fn test_bisect() {
+        let revisions = [false, false, false, true, true, true, true];
+
+        assert_eq!(bisect(revisions.len(), |i| Some(revisions[i])), Some(3));
+        assert_eq!(bisect(3, |i| Some(revisions[i])), None);
+        assert_eq!(bisect(0, |_| Some(true)), None);
+    }
+
+    #[test]
+    fn test_bisect_skip() {
+        let revisions = [Some(false), None, None, Some(true), None, Some(true)];
+
+        assert_eq!(bisect(revisions.len(), |i| revisions[i]), Some(3));
+    }
+
+    #[test]
+    fn test_bisect_checks_once() {
+        let revisions = [None, None, Some(false), None, None, None, None, Some(true)];
+        let mut checked = vec![0; revisions.len()];
+
+        let found = bisect(revisions.len(), |i| {
+            checked[i] += 1;
+            revisions[i]
+        });
+
+        assert_eq!(found, Some(7));
+        assert!(checked.iter().all(|&count| count <= 1), "{:?}", checked);
+    }
+
+    #[test]
+    fn test_find_missing() {
+        let mut anomalies = Vec::new();
+
+        find_missing(&mut anomalies, &[1, 1, 2, 0], &[2, 1, 0, 0], "item");
+
+        assert_eq!(
+            anomalies,
+            vec![Anomaly::ItemDisappeared {
+                kind: "item",
+                name: "1".into()
+            }]
+        );
+    }
+}
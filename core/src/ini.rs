@@ -0,0 +1,410 @@
+//! Reads and writes `dr.ini`, the summary of every save slot shown on the file select screen.
+//!
+//! The game updates it whenever it saves, next to the `filechN_M` files:
+//!
+//! ```ini
+//! [G2_0]
+//! Name="Kris"
+//! Level="2.000000"
+//! Love="1.000000"
+//! Time="123456.000000"
+//! Room="20028.000000"
+//! ```
+//!
+//! Tools that write saves don't touch it, so the file select screen keeps showing the old
+//! values until the slot is saved in-game again.
+//!
+//! The game writes it from `scr_save`, which isn't in `research/` yet. The section names and
+//! where each key comes from are what `dr.ini` files written by the game show, see
+//! `research/ini/README.md` for how they get checked against the script.
+
+use crate::gamedata::rooms::RoomIndex;
+use crate::savefile::SaveData;
+use compact_str::{CompactString, ToCompactString};
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Name of the slot summary file in the save directory.
+pub const INI_FILE_NAME: &str = "dr.ini";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum IniError {
+    #[error("line {line}: entry outside of a section")]
+    EntryOutsideSection { line: usize },
+    #[error("line {line}: expected `key=value` or `[section]`, got `{content}`")]
+    InvalidLine { line: usize, content: String },
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IniSection {
+    pub name: String,
+    pub entries: Vec<(String, String)>,
+}
+
+/// An INI file as written by GameMaker. Sections and keys keep their order, so unrelated
+/// entries survive a read and write unchanged.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IniFile {
+    pub sections: Vec<IniSection>,
+}
+
+impl IniFile {
+    pub fn parse(content: &str) -> Result<Self, IniError> {
+        let mut sections: Vec<IniSection> = Vec::new();
+
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                sections.push(IniSection {
+                    name: name.trim().to_string(),
+                    entries: Vec::new(),
+                });
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(IniError::InvalidLine {
+                    line: i + 1,
+                    content: line.to_string(),
+                });
+            };
+            let section = sections
+                .last_mut()
+                .ok_or(IniError::EntryOutsideSection { line: i + 1 })?;
+
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            section.entries.push((key.trim().to_string(), value.to_string()));
+        }
+
+        Ok(IniFile { sections })
+    }
+
+    pub fn section(&self, name: &str) -> Option<&IniSection> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.section(section)?
+            .entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets a value, adding the section or key at the end if they don't exist yet.
+    pub fn set(&mut self, section: &str, key: &str, value: impl Into<String>) {
+        let index = match self.sections.iter().position(|s| s.name == section) {
+            Some(index) => index,
+            None => {
+                self.sections.push(IniSection {
+                    name: section.to_string(),
+                    entries: Vec::new(),
+                });
+                self.sections.len() - 1
+            }
+        };
+
+        let entries = &mut self.sections[index].entries;
+        match entries.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value.into(),
+            None => entries.push((key.to_string(), value.into())),
+        }
+    }
+
+    /// Reads the summary of a save slot, if the file has one.
+    pub fn slot_summary(&self, chapter: i32, slot: i32) -> Option<SlotSummary> {
+        let section = slot_section(chapter, slot);
+        let real = |key: &str| -> f64 {
+            self.get(&section, key)
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or_default()
+        };
+
+        self.section(&section)?;
+        Some(SlotSummary {
+            name: self.get(&section, "Name").unwrap_or_default().into(),
+            level: real("Level") as i32,
+            love: real("Love") as i32,
+            time_frames: real("Time").round() as i64,
+            room_id: RoomIndex(real("Room") as i32),
+        })
+    }
+
+    /// Replaces the summary of a save slot, keeping any other keys in its section.
+    pub fn set_slot_summary(&mut self, chapter: i32, slot: i32, summary: &SlotSummary) {
+        let section = slot_section(chapter, slot);
+        self.set(&section, "Name", summary.name.as_str());
+        self.set(&section, "Level", write_real(summary.level as f64));
+        self.set(&section, "Love", write_real(summary.love as f64));
+        self.set(&section, "Time", write_real(summary.time_frames as f64));
+        self.set(&section, "Room", write_real(summary.room_id.0 as f64));
+    }
+
+    /// Writes the file the way GameMaker does, with quoted values and CRLF line endings.
+    pub fn write(&self) -> String {
+        self.to_string().replace('\n', "\r\n")
+    }
+}
+
+impl Display for IniFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for section in &self.sections {
+            writeln!(f, "[{}]", section.name)?;
+            for (key, value) in &section.entries {
+                writeln!(f, "{key}=\"{value}\"")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The section name the game uses for a slot. Chapter 1 predates the other chapters sharing
+/// the file, so its sections aren't prefixed with the chapter. Taken from `dr.ini` files the
+/// game wrote.
+pub fn slot_section(chapter: i32, slot: i32) -> String {
+    if chapter == 1 {
+        format!("G{slot}")
+    } else {
+        format!("G{chapter}_{slot}")
+    }
+}
+
+/// `ini_write_real` always writes six decimals.
+fn write_real(value: f64) -> String {
+    format!("{value:.6}")
+}
+
+/// What the file select screen shows for a slot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotSummary {
+    pub name: CompactString,
+    /// Dark world level (`global.lv`), as `dr.ini` files written by the game match it.
+    pub level: i32,
+    /// Light world LOVE (`global.llv`), as `dr.ini` files written by the game match it.
+    pub love: i32,
+    /// Time played in frames, at 30 frames per second.
+    pub time_frames: i64,
+    pub room_id: RoomIndex,
+}
+
+impl SlotSummary {
+    /// The summary the game would write when saving this save.
+    pub fn from_save(save: &SaveData) -> Self {
+        SlotSummary {
+            name: save.true_name.as_ref().into(),
+            level: save.level,
+            love: save.lightworld_stats.lv,
+            time_frames: save.time_played_frames(),
+            room_id: save.room_id,
+        }
+    }
+
+    pub fn time_played(&self) -> Duration {
+        Duration::from_secs_f64(self.time_frames.max(0) as f64 / 30.0)
+    }
+
+    /// Lists the fields where this summary disagrees with the one expected from a save.
+    pub fn mismatches(&self, expected: &SlotSummary) -> Vec<SummaryMismatch> {
+        let mut mismatches = Vec::new();
+        let mut check = |field: &'static str, summary: CompactString, save: CompactString| {
+            if summary != save {
+                mismatches.push(SummaryMismatch::Field {
+                    field,
+                    summary,
+                    save,
+                });
+            }
+        };
+
+        check("Name", self.name.clone(), expected.name.clone());
+        check("Level", self.level.to_compact_string(), expected.level.to_compact_string());
+        check("Love", self.love.to_compact_string(), expected.love.to_compact_string());
+        check(
+            "Time",
+            self.time_frames.to_compact_string(),
+            expected.time_frames.to_compact_string(),
+        );
+        check("Room", self.room_id.to_compact_string(), expected.room_id.to_compact_string());
+        mismatches
+    }
+}
+
+/// A way a slot summary doesn't match the save in that slot.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SummaryMismatch {
+    /// The file has no section for the slot.
+    Missing { section: String },
+    Field {
+        field: &'static str,
+        summary: CompactString,
+        save: CompactString,
+    },
+}
+
+impl Display for SummaryMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SummaryMismatch::Missing { section } => {
+                write!(f, "{INI_FILE_NAME} has no [{section}] summary for the save")
+            }
+            SummaryMismatch::Field {
+                field,
+                summary,
+                save,
+            } => write!(f, "{INI_FILE_NAME} has {field} {summary}, but the save has {save}"),
+        }
+    }
+}
+
+/// Compares the summary of a slot with the save in it.
+pub fn check_slot(ini: &IniFile, chapter: i32, slot: i32, save: &SaveData) -> Vec<SummaryMismatch> {
+    match ini.slot_summary(chapter, slot) {
+        Some(summary) => summary.mismatches(&SlotSummary::from_save(save)),
+        None => vec![SummaryMismatch::Missing {
+            section: slot_section(chapter, slot),
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_and_write() {
+        let content = "[G2_0]\r\nName=\"Kris\"\r\nLevel=\"2.000000\"\r\nUraBoss=\"0.000000\"\r\n[LANG]\r\nLANG=\"en\"\r\n";
+        let mut ini = IniFile::parse(content).unwrap();
+
+        assert_eq!(ini.get("G2_0", "Level"), Some("2.000000"));
+        assert_eq!(ini.get("LANG", "LANG"), Some("en"));
+        assert_eq!(ini.write(), content);
+
+        ini.set_slot_summary(
+            2,
+            0,
+            &SlotSummary {
+                name: "Kris".into(),
+                level: 3,
+                love: 1,
+                time_frames: 900,
+                room_id: RoomIndex(20028),
+            },
+        );
+        assert_eq!(
+            ini.to_string(),
+            indoc! {r#"
+                [G2_0]
+                Name="Kris"
+                Level="3.000000"
+                UraBoss="0.000000"
+                Love="1.000000"
+                Time="900.000000"
+                Room="20028.000000"
+                [LANG]
+                LANG="en"
+            "#}
+        );
+        assert_eq!(ini.slot_summary(2, 0).unwrap().time_played(), Duration::from_secs(30));
+
+        assert_eq!(
+            IniFile::parse("Name=\"Kris\""),
+            Err(IniError::EntryOutsideSection { line: 1 })
+        );
+    }
+
+    #[test]
+    fn test_check_slot() {
+        let lines = vec!["0"; 3055];
+        let save = SaveData::read(2, &lines).unwrap();
+        let mut ini = IniFile::default();
+
+        assert_eq!(
+            check_slot(&ini, 2, 1, &save),
+            vec![SummaryMismatch::Missing {
+                section: "G2_1".into()
+            }]
+        );
+
+        ini.set_slot_summary(2, 1, &SlotSummary::from_save(&save));
+        assert_eq!(check_slot(&ini, 2, 1, &save), vec![]);
+
+        ini.set("G2_1", "Level", "4.000000");
+        assert_eq!(
+            check_slot(&ini, 2, 1, &save),
+            vec![SummaryMismatch::Field {
+                field: "Level",
+                summary: "4".into(),
+                save: "0".into()
+            }]
+        );
+    }
+
+    /// Checks which global each key is written from against the `scr_save` exports in
+    /// `research/ini`, for the chapters that have one.
+    #[test]
+    fn test_slot_summary_matches_scr_save() {
+        let research = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../research/ini");
+        let expected = [
+            ("Name", "global.truename"),
+            ("Level", "global.lv"),
+            ("Love", "global.llv"),
+            ("Time", "global.time"),
+        ];
+
+        for chapter in 1..=4 {
+            let path = research.join(format!("chapter{chapter}_scr_save.gml"));
+            let Ok(script) = std::fs::read_to_string(path) else { continue };
+
+            // ini_write_real(section, "Key", value);
+            let writes = script
+                .lines()
+                .filter_map(|line| line.split_once("ini_write_")?.1.split_once('(')?.1.rsplit_once(')'))
+                .filter_map(|(arguments, _)| {
+                    let (_, rest) = arguments.split_once(", \"")?;
+                    let (key, value) = rest.split_once("\", ")?;
+                    Some((key, value.trim()))
+                })
+                .collect::<Vec<_>>();
+
+            for (key, global) in expected {
+                assert!(
+                    writes.contains(&(key, global)),
+                    "chapter {chapter}: {key} isn't written from {global}: {writes:?}"
+                );
+            }
+            assert!(writes.iter().any(|(key, _)| *key == "Room"), "chapter {chapter}: no Room");
+        }
+    }
+
+    /// The `dr.ini` from the playthroughs in `fixtures/real` has to agree with their saves.
+    #[test]
+    fn test_real_ini() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/real");
+        let content = std::fs::read_to_string(dir.join(INI_FILE_NAME))
+            .expect("fixtures/real/dr.ini is missing, see fixtures/README.md");
+        let ini = IniFile::parse(&content).unwrap();
+        assert_eq!(ini.write(), content);
+
+        for entry in std::fs::read_dir(&dir).unwrap().flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let (chapter, slot) = crate::gamedata::parse_filename(&name);
+            if chapter == 0 {
+                continue;
+            }
+
+            let bytes = std::fs::read(entry.path()).unwrap();
+            let save = SaveData::from_bytes(chapter, &bytes).unwrap();
+            assert_eq!(check_slot(&ini, chapter, slot, &save), vec![], "{name}");
+        }
+    }
+}
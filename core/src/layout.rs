@@ -0,0 +1,499 @@
+//! Where each value of a save is stored, for every chapter.
+//!
+//! A save is a list of lines with one value each, in the order `scr_saveprocess` writes them.
+//! A [`SaveLayout`] lists the fields in that order, in groups: a group repeats all of its fields
+//! `count` times, which is how chapter 1 stores its inventory, key items, weapons and armors
+//! interleaved. Reading, writing and merging saves all follow the layout of the save's chapter,
+//! so a chapter with a new layout only needs a new [`SaveLayout`].
+
+use std::fmt::{Display, Formatter};
+
+/// How a field's values are stored.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FieldKind {
+    String,
+    Int,
+    /// `0` or `1`.
+    Bool,
+    /// A [`GmReal`](crate::real::GmReal).
+    Real,
+    /// An ID from the gamedata tables.
+    Id,
+    /// A party member's [`Stats`](crate::savefile::Stats), over several lines.
+    Stats,
+    /// Kris's [`LightworldStats`](crate::savefile::LightworldStats), over several lines.
+    LightworldStats,
+}
+
+/// A field of [`SaveData`](crate::savefile::SaveData), in file order.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SaveField {
+    TrueName,
+    VesselNames,
+    Party,
+    DarkDollars,
+    Xp,
+    Level,
+    Inv,
+    Invc,
+    IsDarkworld,
+    Stats,
+    BoltSpeed,
+    GrazeAmount,
+    GrazeSize,
+    Inventory,
+    KeyItems,
+    Weapons,
+    Armors,
+    Storage,
+    Tension,
+    MaxTension,
+    LightworldStats,
+    LightworldItems,
+    LightworldPhone,
+    Flags,
+    /// Flags past the 2500 that later chapters store. Always zero.
+    UnusedFlags,
+    PlotValue,
+    RoomId,
+    TimePlayed,
+}
+
+impl SaveField {
+    /// The name of the field in [`SaveData`](crate::savefile::SaveData).
+    pub fn name(self) -> &'static str {
+        match self {
+            SaveField::TrueName => "true_name",
+            SaveField::VesselNames => "vessel_names",
+            SaveField::Party => "party",
+            SaveField::DarkDollars => "dark_dollars",
+            SaveField::Xp => "xp",
+            SaveField::Level => "level",
+            SaveField::Inv => "inv",
+            SaveField::Invc => "invc",
+            SaveField::IsDarkworld => "is_darkworld",
+            SaveField::Stats => "stats",
+            SaveField::BoltSpeed => "bolt_speed",
+            SaveField::GrazeAmount => "graze_amount",
+            SaveField::GrazeSize => "graze_size",
+            SaveField::Inventory => "inventory",
+            SaveField::KeyItems => "key_items",
+            SaveField::Weapons => "weapons",
+            SaveField::Armors => "armors",
+            SaveField::Storage => "storage",
+            SaveField::Tension => "tension",
+            SaveField::MaxTension => "max_tension",
+            SaveField::LightworldStats => "lightworld_stats",
+            SaveField::LightworldItems => "lightworld_items",
+            SaveField::LightworldPhone => "lightworld_phone",
+            SaveField::Flags => "flags",
+            SaveField::UnusedFlags => "unused_flags",
+            SaveField::PlotValue => "plot_value",
+            SaveField::RoomId => "room_id",
+            SaveField::TimePlayed => "time_played",
+        }
+    }
+
+    pub fn kind(self) -> FieldKind {
+        match self {
+            SaveField::TrueName | SaveField::VesselNames => FieldKind::String,
+            SaveField::IsDarkworld => FieldKind::Bool,
+            SaveField::Tension
+            | SaveField::MaxTension
+            | SaveField::Flags
+            | SaveField::PlotValue
+            | SaveField::TimePlayed => FieldKind::Real,
+            SaveField::Party
+            | SaveField::Inventory
+            | SaveField::KeyItems
+            | SaveField::Weapons
+            | SaveField::Armors
+            | SaveField::Storage
+            | SaveField::LightworldItems
+            | SaveField::LightworldPhone
+            | SaveField::RoomId => FieldKind::Id,
+            SaveField::Stats => FieldKind::Stats,
+            SaveField::LightworldStats => FieldKind::LightworldStats,
+            SaveField::DarkDollars
+            | SaveField::Xp
+            | SaveField::Level
+            | SaveField::Inv
+            | SaveField::Invc
+            | SaveField::BoltSpeed
+            | SaveField::GrazeAmount
+            | SaveField::GrazeSize
+            | SaveField::UnusedFlags => FieldKind::Int,
+        }
+    }
+}
+
+impl Display for SaveField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Fields that are stored `count` times, one after another.
+#[derive(Debug)]
+pub struct FieldGroup {
+    pub count: usize,
+    pub fields: &'static [SaveField],
+}
+
+const fn group(count: usize, fields: &'static [SaveField]) -> FieldGroup {
+    FieldGroup { count, fields }
+}
+
+/// A field that's stored once, or `count` times in a row.
+macro_rules! field {
+    ($field:ident) => {
+        field!($field, 1)
+    };
+    ($field:ident, $count:expr) => {
+        group($count, &[SaveField::$field])
+    };
+}
+
+#[derive(Debug)]
+pub struct SaveLayout {
+    pub name: &'static str,
+    /// Whether the item stats in stat blocks store an element and its amount.
+    pub item_elements: bool,
+    pub groups: &'static [FieldGroup],
+}
+
+pub static CHAPTER_1: SaveLayout = SaveLayout {
+    name: "Chapter 1",
+    item_elements: false,
+    groups: &[
+        field!(TrueName),
+        field!(VesselNames, 6),
+        field!(Party, 3),
+        field!(DarkDollars),
+        field!(Xp),
+        field!(Level),
+        field!(Inv),
+        field!(Invc),
+        field!(IsDarkworld),
+        field!(Stats, 4),
+        field!(BoltSpeed),
+        field!(GrazeAmount),
+        field!(GrazeSize),
+        group(
+            13,
+            &[SaveField::Inventory, SaveField::KeyItems, SaveField::Weapons, SaveField::Armors],
+        ),
+        field!(Tension),
+        field!(MaxTension),
+        field!(LightworldStats),
+        group(8, &[SaveField::LightworldItems, SaveField::LightworldPhone]),
+        field!(Flags, 2500),
+        field!(UnusedFlags, 7499),
+        field!(PlotValue),
+        field!(RoomId),
+        field!(TimePlayed),
+    ],
+};
+
+/// Chapter 2 added Noelle's stat block, elements, the storage and more room for equipment.
+/// Chapters 3 and 4 kept it.
+pub static CHAPTER_2: SaveLayout = SaveLayout {
+    name: "Chapters 2-4",
+    item_elements: true,
+    groups: &[
+        field!(TrueName),
+        field!(VesselNames, 6),
+        field!(Party, 3),
+        field!(DarkDollars),
+        field!(Xp),
+        field!(Level),
+        field!(Inv),
+        field!(Invc),
+        field!(IsDarkworld),
+        field!(Stats, 5),
+        field!(BoltSpeed),
+        field!(GrazeAmount),
+        field!(GrazeSize),
+        group(13, &[SaveField::Inventory, SaveField::KeyItems]),
+        group(48, &[SaveField::Weapons, SaveField::Armors]),
+        field!(Storage, 72),
+        field!(Tension),
+        field!(MaxTension),
+        field!(LightworldStats),
+        group(8, &[SaveField::LightworldItems, SaveField::LightworldPhone]),
+        field!(Flags, 2500),
+        field!(PlotValue),
+        field!(RoomId),
+        field!(TimePlayed),
+    ],
+};
+
+impl SaveLayout {
+    /// The layout saves of a chapter are written in. Chapters after 4 are assumed to keep the
+    /// last known layout.
+    pub fn for_chapter(chapter: i32) -> &'static SaveLayout {
+        match chapter {
+            1 => &CHAPTER_1,
+            _ => &CHAPTER_2,
+        }
+    }
+
+    /// How many values of a field the layout stores, 0 if it has none.
+    pub fn count(&self, field: SaveField) -> usize {
+        self.groups
+            .iter()
+            .filter(|group| group.fields.contains(&field))
+            .map(|group| group.count)
+            .sum()
+    }
+
+    /// Number of lines one value of the given kind takes.
+    pub fn lines_of(&self, kind: FieldKind) -> usize {
+        match kind {
+            FieldKind::Stats => {
+                let item_stats = if self.item_elements { 10 } else { 8 };
+                10 + 4 * item_stats + 12
+            }
+            FieldKind::LightworldStats => 11,
+            _ => 1,
+        }
+    }
+
+    /// Number of lines in a save with this layout.
+    pub fn line_count(&self) -> usize {
+        self.groups
+            .iter()
+            .map(|group| {
+                let lines = group.fields.iter().map(|f| self.lines_of(f.kind())).sum::<usize>();
+                group.count * lines
+            })
+            .sum()
+    }
+
+    /// Calls `visit` with every field and index in file order, like the game writes them.
+    pub fn for_each_field<E>(
+        &self,
+        mut visit: impl FnMut(SaveField, usize) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let mut counts = Vec::new();
+        for group in self.groups {
+            for _ in 0..group.count {
+                for &field in group.fields {
+                    // Fields split over several groups keep counting up.
+                    let index = match counts.iter_mut().find(|(f, _)| *f == field) {
+                        Some((_, count)) => count,
+                        None => {
+                            counts.push((field, 0));
+                            &mut counts.last_mut().unwrap().1
+                        }
+                    };
+                    visit(field, *index)?;
+                    *index += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the field stored at a line (counting from 0) and the index of the value in it, to
+    /// describe where two saves differ.
+    pub fn field_at(&self, line: usize) -> Option<(SaveField, usize)> {
+        let mut start = 0;
+        let mut found = None;
+        let _ = self.for_each_field(|field, index| {
+            start += self.lines_of(field.kind());
+            if start > line {
+                found = Some((field, index));
+                return Err(());
+            }
+            Ok(())
+        });
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::savefile::FLAG_COUNT;
+    use regex::Regex;
+
+    /// Runs the PC branch of a decompiled `scr_saveprocess`, returning the `global` variable
+    /// written on each line and whether the file ends with a line break.
+    fn saveprocess_lines(script: &str) -> (Vec<String>, bool) {
+        fn block_end(lines: &[&str], open: usize) -> usize {
+            let mut depth = 0;
+            for (i, line) in lines.iter().enumerate().skip(open) {
+                depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            panic!("unclosed block at line {open}");
+        }
+
+        struct Runner {
+            for_loop: Regex,
+            write: Regex,
+            output: Vec<String>,
+            newline: bool,
+        }
+
+        impl Runner {
+            fn run(&mut self, lines: &[&str]) {
+                let mut i = 0;
+                while i < lines.len() {
+                    let line = lines[i].trim();
+                    if let Some(count) = self.for_loop.captures(line) {
+                        let end = block_end(lines, i + 1);
+                        for _ in 0..count[1].parse().unwrap() {
+                            self.run(&lines[i + 2..end]);
+                        }
+                        i = end + 1;
+                    } else if line == "if (global.is_console)" {
+                        // Console saves write whole lists on one line, skip to the PC branch
+                        i = block_end(lines, i + 1) + 1;
+                        if lines.get(i).map(|l| l.trim()) == Some("else") {
+                            let end = block_end(lines, i + 1);
+                            self.run(&lines[i + 2..end]);
+                            i = end + 1;
+                        }
+                    } else if line == "if (!global.is_console)" {
+                        let end = block_end(lines, i + 1);
+                        self.run(&lines[i + 2..end]);
+                        i = end + 1;
+                    } else {
+                        if let Some(global) = self.write.captures(line) {
+                            let on_new_line = self.output.is_empty() || self.newline;
+                            assert!(on_new_line, "two values on one line: {line}");
+                            self.output.push(global[1].to_string());
+                            self.newline = false;
+                        } else if line.contains("file_text_writeln(myfileid)") {
+                            self.newline = true;
+                        }
+                        i += 1;
+                    }
+                }
+            }
+        }
+
+        let mut runner = Runner {
+            for_loop: Regex::new(r"^for \(\w+ = 0; \w+ < (\d+);").unwrap(),
+            write: Regex::new(r"file_text_write_(?:real|string)\(myfileid, global\.(\w+)").unwrap(),
+            output: Vec::new(),
+            newline: false,
+        };
+        runner.run(&script.lines().collect::<Vec<_>>());
+        (runner.output, runner.newline)
+    }
+
+    /// The `global` variables the lines of a field are written from, in order.
+    fn field_globals(layout: &SaveLayout, field: SaveField) -> Vec<&'static str> {
+        let simple = match field {
+            SaveField::TrueName => "truename",
+            SaveField::VesselNames => "othername",
+            SaveField::Party => "char",
+            SaveField::DarkDollars => "gold",
+            SaveField::Xp => "xp",
+            SaveField::Level => "lv",
+            SaveField::Inv => "inv",
+            SaveField::Invc => "invc",
+            SaveField::IsDarkworld => "darkzone",
+            SaveField::BoltSpeed => "boltspeed",
+            SaveField::GrazeAmount => "grazeamt",
+            SaveField::GrazeSize => "grazesize",
+            SaveField::Inventory => "item",
+            SaveField::KeyItems => "keyitem",
+            SaveField::Weapons => "weapon",
+            SaveField::Armors => "armor",
+            SaveField::Storage => "pocketitem",
+            SaveField::Tension => "tension",
+            SaveField::MaxTension => "maxtension",
+            SaveField::LightworldItems => "litem",
+            SaveField::LightworldPhone => "phone",
+            SaveField::Flags | SaveField::UnusedFlags => "flag",
+            SaveField::PlotValue => "plot",
+            SaveField::RoomId => "currentroom",
+            SaveField::TimePlayed => "time",
+            SaveField::Stats => {
+                let mut globals = vec![
+                    "hp", "maxhp", "at", "df", "mag", "guts", "charweapon", "chararmor1",
+                    "chararmor2", "weaponstyle",
+                ];
+                let mut item_stats = vec![
+                    "itemat", "itemdf", "itemmag", "itembolts", "itemgrazeamt", "itemgrazesize",
+                    "itemboltspeed", "itemspecial",
+                ];
+                if layout.item_elements {
+                    item_stats.extend(["itemelement", "itemelementamount"]);
+                }
+                for _ in 0..4 {
+                    globals.extend(&item_stats);
+                }
+                globals.extend(["spell"; 12]);
+                return globals;
+            }
+            SaveField::LightworldStats => {
+                return vec![
+                    "lweapon", "larmor", "lxp", "llv", "lgold", "lhp", "lmaxhp", "lat", "ldf",
+                    "lwstrength", "ladef",
+                ];
+            }
+        };
+        vec![simple]
+    }
+
+    #[test]
+    fn test_layouts_match_saveprocess() {
+        let scripts = [
+            (1, include_str!("../../research/saveprocess/chapter1.gml")),
+            (2, include_str!("../../research/saveprocess/chapter2.gml")),
+            (3, include_str!("../../research/saveprocess/chapter3.gml")),
+            (4, include_str!("../../research/saveprocess/chapter4.gml")),
+        ];
+
+        for (chapter, script) in scripts {
+            let layout = SaveLayout::for_chapter(chapter);
+            let (written, ends_with_newline) = saveprocess_lines(script);
+
+            let mut expected = Vec::new();
+            let _ = layout.for_each_field(|field, _| {
+                expected.extend(field_globals(layout, field));
+                Ok::<_, ()>(())
+            });
+
+            assert_eq!(written.len(), layout.line_count(), "chapter {chapter}");
+            for (line, (written, expected)) in written.iter().zip(&expected).enumerate() {
+                assert_eq!(written, expected, "chapter {chapter}, line {line}");
+            }
+            // The time is written last, without a line break after it
+            assert!(!ends_with_newline, "chapter {chapter}");
+        }
+    }
+
+    #[test]
+    fn test_line_counts() {
+        assert_eq!(CHAPTER_1.line_count(), 10318);
+        assert_eq!(CHAPTER_2.line_count(), 3055);
+        assert_eq!(SaveLayout::for_chapter(4).name, "Chapters 2-4");
+
+        for layout in [&CHAPTER_1, &CHAPTER_2] {
+            assert_eq!(layout.count(SaveField::Flags), FLAG_COUNT);
+            assert_eq!(layout.count(SaveField::Inventory), 13);
+        }
+        assert_eq!(CHAPTER_1.count(SaveField::Storage), 0);
+        assert_eq!(CHAPTER_2.count(SaveField::Weapons), 48);
+    }
+
+    #[test]
+    fn test_field_at() {
+        assert_eq!(CHAPTER_2.field_at(0), Some((SaveField::TrueName, 0)));
+        assert_eq!(CHAPTER_2.field_at(9), Some((SaveField::Party, 2)));
+        assert_eq!(CHAPTER_2.field_at(16), Some((SaveField::Stats, 0)));
+        // Chapter 1 interleaves the inventory with the equipment
+        let inventory_start = 16 + 4 * CHAPTER_1.lines_of(FieldKind::Stats) + 3;
+        assert_eq!(CHAPTER_1.field_at(inventory_start + 6), Some((SaveField::Weapons, 1)));
+        assert_eq!(CHAPTER_2.field_at(3054), Some((SaveField::TimePlayed, 0)));
+        assert_eq!(CHAPTER_2.field_at(3055), None);
+    }
+}
@@ -1,11 +1,26 @@
 extern crate core;
+// Lets the code generated by deltakit-derive name this crate as `::deltakit` here too.
+extern crate self as deltakit;
 
+pub mod builder;
+pub mod checklist;
 pub mod gamedata;
+pub mod history;
+pub mod ini;
 pub mod iter;
+pub mod layout;
 mod save_parser;
 pub mod savefile;
 pub mod merging;
+pub mod profile;
+pub mod query;
+pub mod real;
 mod serialize;
+pub mod sessions;
+pub mod stats;
+pub mod transfer;
+pub mod validate;
+pub mod visit;
 
 use log::{LevelFilter, debug, error};
 use std::{panic, process};
@@ -27,5 +42,7 @@ pub fn init() {
         process::exit(255);
     }));
 
+    gamedata::registry::load_default();
+
     debug!("deltakit initialized.");
 }
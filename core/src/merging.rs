@@ -1,15 +1,18 @@
 use std::fmt::Display;
 use std::ops::{Add, Sub};
-use compact_str::CompactString;
+use compact_str::{CompactString, ToCompactString};
 use indoc::indoc;
 use log::warn;
-use crate::gamedata::armors::display_armor;
-use crate::gamedata::items::display_item;
-use crate::gamedata::key_items::display_key_item;
-use crate::gamedata::lightworld_items::display_lightworld_item;
-use crate::gamedata::phone_numbers::display_phone_number;
-use crate::gamedata::weapons::display_weapon;
-use crate::savefile::{ItemStats, LightworldStats, SaveData, Stats};
+use crate::gamedata::armors::ArmorId;
+use crate::gamedata::items::ItemId;
+use crate::gamedata::key_items::KeyItemId;
+use crate::gamedata::lightworld_items::LightworldItemId;
+use crate::gamedata::phone_numbers::PhoneNumberId;
+use crate::gamedata::recruits::{RecruitInfo, recruits_in};
+use crate::gamedata::weapons::WeaponId;
+use crate::layout::{SaveField, SaveLayout};
+use crate::real::GmReal;
+use crate::savefile::{LightworldStats, SaveData, Stats};
 use crate::serialize::Serializable;
 
 #[derive(Clone, Debug)]
@@ -193,27 +196,27 @@ fn merge_max<
     MergeResult::Resolved(if theirs > ours { theirs } else { ours })
 }
 
-fn merge_item_stats(
-    output: &mut Vec<MergeResult<CompactString>>,
-    ours: &ItemStats,
-    theirs: &ItemStats,
-    ancestor: Option<&ItemStats>,
-    chapter: i32,
-) -> Result<(), &'static str> {
-    output.push(merge_simple(ours.attack, theirs.attack, ancestor.map(|a| a.attack)).map(|v| v.serialize()));
-    output.push(merge_simple(ours.defense, theirs.defense, ancestor.map(|a| a.defense)).map(|v| v.serialize()));
-    output.push(merge_simple(ours.magic, theirs.magic, ancestor.map(|a| a.magic)).map(|v| v.serialize()));
-    output.push(merge_simple(ours.bolts, theirs.bolts, ancestor.map(|a| a.bolts)).map(|v| v.serialize()));
-    output.push(merge_simple(ours.graze_amount, theirs.graze_amount, ancestor.map(|a| a.graze_amount)).map(|v| v.serialize()));
-    output.push(merge_simple(ours.graze_size, theirs.graze_size, ancestor.map(|a| a.graze_size)).map(|v| v.serialize()));
-    output.push(merge_simple(ours.bolts_speed, theirs.bolts_speed, ancestor.map(|a| a.bolts_speed)).map(|v| v.serialize()));
-    output.push(merge_simple(ours.item_special, theirs.item_special, ancestor.map(|a| a.item_special)).map(|v| v.serialize()));
-    if chapter > 1 {
-        output.push(merge_simple(ours.item_element, theirs.item_element, ancestor.map(|a| a.item_element)).map(|v| v.serialize()));
-        output.push(merge_simple(ours.item_element_amount, theirs.item_element_amount, ancestor.map(|a| a.item_element_amount)).map(|v| v.serialize()));
+/// Recruit progress only goes up until the enemy is recruited or lost, so the furthest side
+/// wins. Recruited on one side and lost on the other can't both have happened.
+fn merge_recruit<'a>(
+    ours: &'a GmReal,
+    theirs: &'a GmReal,
+    ancestor: Option<&'a GmReal>,
+) -> MergeResult<&'a GmReal> {
+    let lost = |progress: &GmReal| progress.value() < 0.0;
+    let recruited = |progress: &GmReal| progress.value() >= 1.0;
+
+    match (ours, theirs) {
+        (o, t) if o == t => MergeResult::Resolved(o),
+        (o, t) if (lost(o) && recruited(t)) || (recruited(o) && lost(t)) => MergeResult::Conflict {
+            ours: o,
+            theirs: t,
+            ancestor,
+        },
+        (o, _) if lost(o) => MergeResult::Resolved(o),
+        (_, t) if lost(t) => MergeResult::Resolved(t),
+        (o, t) => MergeResult::Resolved(if t > o { t } else { o }),
     }
-
-    Ok(())
 }
 
 fn merge_stats(
@@ -221,7 +224,7 @@ fn merge_stats(
     ours: &Stats,
     theirs: &Stats,
     ancestor: Option<&Stats>,
-    chapter: i32,
+    item_elements: bool,
 ) -> Result<(), &'static str> {
     let max_hp = merge_simple(ours.max_hp, theirs.max_hp, ancestor.map(|a| a.max_hp))
         .map(|v| v.serialize());
@@ -246,7 +249,7 @@ fn merge_stats(
         output.push(MergeResult::Resolved(ours.item_stats[i].graze_size.serialize()));
         output.push(MergeResult::Resolved(ours.item_stats[i].bolts_speed.serialize()));
         output.push(MergeResult::Resolved(ours.item_stats[i].item_special.serialize()));
-        if chapter > 1 {
+        if item_elements {
             output.push(MergeResult::Resolved(ours.item_stats[i].item_element.serialize()));
             output.push(MergeResult::Resolved(ours.item_stats[i].item_element_amount.serialize()));
         }
@@ -276,28 +279,20 @@ fn add_to_inventory<T: Copy + Default + PartialEq>(
 ) -> Result<usize, ()> {
     let no_item = T::default();
 
-    for i in start_index..inventory.len() {
-        if inventory[i] == no_item {
-            inventory[i] = item;
-            return Ok(i);
-        }
-    }
-
-    for i in (0..start_index).rev() {
-        if inventory[i] == no_item {
-            inventory[i] = item;
-            return Ok(i);
-        }
-    }
+    let slot = (start_index..inventory.len())
+        .chain((0..start_index).rev())
+        .find(|&i| inventory[i] == no_item)
+        .ok_or(())?;
+    inventory[slot] = item;
 
-    Err(())
+    Ok(slot)
 }
 
 fn merge_inventories<T : Copy + Default + PartialEq>(
     ours: &mut [T],
     theirs: &[T],
     item_kind: &str,
-    display: impl Fn(T) -> CompactString,
+    display: impl Fn(&T) -> CompactString,
 ) {
     let no_item = T::default();
     let mut theirs = theirs.to_owned();
@@ -314,7 +309,7 @@ fn merge_inventories<T : Copy + Default + PartialEq>(
             theirs[i] = no_item;
 
             if res.is_err() {
-                warn!("could not add {} {} to inventory", item_kind, display(theirs[i]));
+                warn!("could not add {} {} to inventory", item_kind, display(&theirs[i]));
             }
         }
     }
@@ -335,8 +330,8 @@ fn merge_lightworld_stats(
     output.push(merge_simple(ours.max_hp, theirs.max_hp, ancestor.map(|a| a.max_hp)).map(|v| v.serialize()));
     output.push(merge_simple(ours.attack, theirs.attack, ancestor.map(|a| a.attack)).map(|v| v.serialize()));
     output.push(merge_simple(ours.defense, theirs.defense, ancestor.map(|a| a.defense)).map(|v| v.serialize()));
-    output.push(merge_simple(ours.wstrength, theirs.wstrength, ancestor.map(|a| a.wstrength)).map(|v| v.serialize()));
-    output.push(merge_simple(ours.adef, theirs.adef, ancestor.map(|a| a.adef)).map(|v| v.serialize()));
+    output.push(merge_simple(ours.weapon_attack, theirs.weapon_attack, ancestor.map(|a| a.weapon_attack)).map(|v| v.serialize()));
+    output.push(merge_simple(ours.armor_defense, theirs.armor_defense, ancestor.map(|a| a.armor_defense)).map(|v| v.serialize()));
 }
 
 pub fn merge_savefiles(
@@ -350,99 +345,15 @@ pub fn merge_savefiles(
         return Err("Chapter mismatch")
     }
 
-    let mut data: Vec<MergeResult<CompactString>> = Vec::with_capacity(if chapter == 1 { 10318 } 
-    else { 3055 });
-
-    data.push(MergeResult::Resolved(ours.true_name.serialize()));
-
-    for i in 0..6 {
-        data.push(MergeResult::Resolved(ours.vessel_names[i].serialize()));
-    }
-
-    for i in 0..3 {
-        data.push(MergeResult::Resolved(ours.party[i].serialize()));
-    }
-
-    data.push(
-        merge_values(
-            ours.dark_dollars,
-            theirs.dark_dollars, 
-            ancestor.map(|a| a.dark_dollars),
-            Some(0),
-            None,
-            "dark dollars",
-        ).map(|v| v.serialize())
-    );
-
-    data.push(
-        merge_max(
-            ours.xp,
-            theirs.xp,
-        ).map(|v| v.serialize())
-    );
-
-    data.push(
-        merge_max(
-            ours.level,
-            theirs.level,
-        ).map(|v| v.serialize())
-    );
-
-    data.push(
-        // merge_simple(
-        //     ours.inv,
-        //     theirs.inv,
-        //     ancestor.map(|a| a.inv),
-        // ).map(|v| v.serialize())
-        MergeResult::Resolved(ours.inv.serialize())
-    );
-
-    data.push(
-        // merge_simple(
-        //     ours.invc,
-        //     theirs.invc,
-        //     ancestor.map(|a| a.invc),
-        // ).map(|v| v.serialize())
-        MergeResult::Resolved(ours.invc.serialize())
-    );
-
-    data.push(
-        MergeResult::Resolved(if ours.is_darkworld { "1" } else { "0" }.serialize())
-    );
-
-    // stats
-    let stat_blocks = match chapter {
-        1 => 4,
-        _ => 5,
-    };
-
-    for i in 0..stat_blocks {
-        merge_stats(
-            &mut data,
-            &ours.stats[i],
-            &theirs.stats[i],
-            ancestor.map(|a| &a.stats[i]),
-            chapter,
-        )?;
-    }
-
-    data.push(merge_simple(ours.bolt_speed, theirs.bolt_speed, ancestor.map(|a| a.bolt_speed)).map(|v| v.serialize()));
-    data.push(merge_simple(ours.graze_amount, theirs.graze_amount, ancestor.map(|a| a.graze_amount)).map(|v| v.serialize()));
-    data.push(merge_simple(ours.graze_size, theirs.graze_size, ancestor.map(|a| a.graze_size)).map(|v| v.serialize()));
+    let layout = SaveLayout::for_chapter(chapter);
 
     // INVENTORY, STORAGE...
 
-    let mut inventory_and_storage = Vec::with_capacity(12 + if chapter > 1 { 72 } else { 0 });
-    inventory_and_storage.extend_from_slice(&ours.inventory[0..12]);
-    if chapter > 1 {
-        inventory_and_storage.extend_from_slice(ours.storage.as_ref().unwrap());
-    }
+    let mut inventory_and_storage = ours.inventory[0..12].to_vec();
+    inventory_and_storage.extend(ours.storage.iter().flatten());
 
-    let mut their_inventory_and_storage = Vec::with_capacity(12 + if chapter > 1 { 72 } else { 0 });
-    their_inventory_and_storage.extend_from_slice(&theirs.inventory[0..12]);
-    if chapter > 1 {
-        their_inventory_and_storage.extend_from_slice(theirs.storage.as_ref().unwrap());
-    }
+    let mut their_inventory_and_storage = theirs.inventory[0..12].to_vec();
+    their_inventory_and_storage.extend(theirs.storage.iter().flatten());
 
     let mut key_items = ours.key_items;
     let mut weapons = ours.weapons.clone();
@@ -455,12 +366,13 @@ pub fn merge_savefiles(
         }
     }
     let mut armors = ours.armors.clone();
+    let mut their_armors = theirs.armors.clone();
     for (i, stats) in theirs.stats.iter().enumerate() {
         if stats.armor1 != ours.stats[i].armor1 {
-            armors.push(stats.armor1);
+            their_armors.push(stats.armor1);
         }
         if stats.armor2 != ours.stats[i].armor2 {
-            armors.push(stats.armor2);
+            their_armors.push(stats.armor2);
         }
     }
 
@@ -468,67 +380,28 @@ pub fn merge_savefiles(
         &mut inventory_and_storage,
         &their_inventory_and_storage,
         "item",
-        display_item,
+        ItemId::to_compact_string,
     );
 
     merge_inventories(
         &mut key_items,
         &theirs.key_items,
         "key item",
-        display_key_item,
+        KeyItemId::to_compact_string,
     );
 
     merge_inventories(
         &mut weapons,
         &their_weapons,
         "weapon",
-        display_weapon,
+        WeaponId::to_compact_string,
     );
 
     merge_inventories(
         &mut armors,
-        &their_weapons,
+        &their_armors,
         "armor",
-        display_armor,
-    );
-
-    for i in 0..12 {
-        data.push(MergeResult::Resolved(inventory_and_storage[i].serialize()));
-        data.push(MergeResult::Resolved(key_items[i].serialize()));
-
-        if chapter == 1 {
-            data.push(MergeResult::Resolved(weapons[i].serialize()));
-            data.push(MergeResult::Resolved(armors[i].serialize()));
-        }
-    }
-
-    data.push(MergeResult::Resolved(ours.inventory[12].serialize()));
-    data.push(MergeResult::Resolved(key_items[12].serialize()));
-
-    if chapter == 1 {
-        data.push(MergeResult::Resolved(weapons[12].serialize()));
-        data.push(MergeResult::Resolved(armors[12].serialize()));
-    }
-
-    if chapter != 1 {
-        for i in 0..48 {
-            data.push(MergeResult::Resolved(weapons[i].serialize()));
-            data.push(MergeResult::Resolved(armors[i].serialize()));
-        }
-
-        for i in 0..72 {
-            data.push(MergeResult::Resolved(inventory_and_storage[i + 12].serialize()));
-        }
-    }
-
-    data.push(merge_simple(ours.tension, theirs.tension, ancestor.map(|a| a.tension)).map(|v| v.serialize()));
-    data.push(merge_simple(ours.max_tension, theirs.max_tension, ancestor.map(|a| a.max_tension)).map(|v| v.serialize()));
-
-    merge_lightworld_stats(
-        &mut data,
-        &ours.lightworld_stats,
-        &theirs.lightworld_stats,
-        ancestor.map(|a| &a.lightworld_stats),
+        ArmorId::to_compact_string,
     );
 
     let mut lightworld_items = ours.lightworld_items;
@@ -538,53 +411,99 @@ pub fn merge_savefiles(
         &mut lightworld_items,
         &theirs.lightworld_items,
         "lightworld item",
-        display_lightworld_item,
+        LightworldItemId::to_compact_string,
     );
 
     merge_inventories(
         &mut phone_numbers,
         &theirs.lightworld_phone,
         "phone number",
-        display_phone_number,
+        PhoneNumberId::to_compact_string,
     );
 
-    for i in 0..8 {
-        data.push(MergeResult::Resolved(lightworld_items[i].serialize()));
-        data.push(MergeResult::Resolved(phone_numbers[i].serialize()));
-    }
-
-    for i in 0..2500 {
-        if ancestor.is_some() {
-            data.push(merge_simple(ours.flags[i], theirs.flags[i], ancestor.map(|a| a.flags[i])).map(|v| v.serialize()));
-        } else {
-            data.push(merge_max(ours.flags[i], theirs.flags[i]).map(|v| v.serialize()));
-        }
-    }
-
-    if chapter == 1 {
-        for _ in 2500..9999 {
-            data.push(MergeResult::Resolved("0".serialize()));
-        }
-    }
-
-    data.push(MergeResult::Resolved(ours.plot_value.serialize()));
-    data.push(MergeResult::Resolved(ours.room_id.serialize()));
-    if ancestor.is_some() {
-        data.push(merge_values(
-            ours.time_played.as_secs() * 30,
-            theirs.time_played.as_secs() * 30,
-            ancestor.map(|a| a.time_played.as_secs() * 30),
-            None, None,
-            "time played"
-        ).map(|v| v.serialize()));
-    } else {
-        data.push(
-            merge_max(ours.time_played.as_secs_f64() * 30.0,
-                      theirs.time_played.as_secs_f64() * 30.0)
-                .map(|v| (v as f32).serialize()));
-    }
+    // Only the flags of this chapter's recruits are known to be recruit progress
+    let recruit_flags = recruits_in(chapter).map(RecruitInfo::flag_index).collect::<Vec<_>>();
+
+    let mut data: Vec<MergeResult<CompactString>> = Vec::with_capacity(layout.line_count());
+
+    layout.for_each_field(|field, i| {
+        let result = match field {
+            SaveField::TrueName => MergeResult::Resolved(ours.true_name.serialize()),
+            SaveField::VesselNames => MergeResult::Resolved(ours.vessel_names[i].serialize()),
+            SaveField::Party => MergeResult::Resolved(ours.party[i].serialize()),
+            SaveField::DarkDollars => merge_values(
+                ours.dark_dollars,
+                theirs.dark_dollars,
+                ancestor.map(|a| a.dark_dollars),
+                Some(0),
+                None,
+                "dark dollars",
+            ).map(|v| v.serialize()),
+            SaveField::Xp => merge_max(ours.xp, theirs.xp).map(|v| v.serialize()),
+            SaveField::Level => merge_max(ours.level, theirs.level).map(|v| v.serialize()),
+            SaveField::Inv => MergeResult::Resolved(ours.inv.serialize()),
+            SaveField::Invc => MergeResult::Resolved(ours.invc.serialize()),
+            SaveField::IsDarkworld => MergeResult::Resolved((ours.is_darkworld as i32).serialize()),
+            SaveField::Stats => {
+                return merge_stats(
+                    &mut data,
+                    &ours.stats[i],
+                    &theirs.stats[i],
+                    ancestor.map(|a| &a.stats[i]),
+                    layout.item_elements,
+                );
+            }
+            SaveField::BoltSpeed => merge_simple(ours.bolt_speed, theirs.bolt_speed, ancestor.map(|a| a.bolt_speed)).map(|v| v.serialize()),
+            SaveField::GrazeAmount => merge_simple(ours.graze_amount, theirs.graze_amount, ancestor.map(|a| a.graze_amount)).map(|v| v.serialize()),
+            SaveField::GrazeSize => merge_simple(ours.graze_size, theirs.graze_size, ancestor.map(|a| a.graze_size)).map(|v| v.serialize()),
+            // The last inventory slot isn't merged
+            SaveField::Inventory if i < 12 => MergeResult::Resolved(inventory_and_storage[i].serialize()),
+            SaveField::Inventory => MergeResult::Resolved(ours.inventory[i].serialize()),
+            SaveField::KeyItems => MergeResult::Resolved(key_items[i].serialize()),
+            SaveField::Weapons => MergeResult::Resolved(weapons[i].serialize()),
+            SaveField::Armors => MergeResult::Resolved(armors[i].serialize()),
+            SaveField::Storage => MergeResult::Resolved(inventory_and_storage[i + 12].serialize()),
+            SaveField::Tension => merge_simple(&ours.tension, &theirs.tension, ancestor.map(|a| &a.tension)).map(|v| v.serialize()),
+            SaveField::MaxTension => merge_simple(&ours.max_tension, &theirs.max_tension, ancestor.map(|a| &a.max_tension)).map(|v| v.serialize()),
+            SaveField::LightworldStats => {
+                merge_lightworld_stats(
+                    &mut data,
+                    &ours.lightworld_stats,
+                    &theirs.lightworld_stats,
+                    ancestor.map(|a| &a.lightworld_stats),
+                );
+                return Ok(());
+            }
+            SaveField::LightworldItems => MergeResult::Resolved(lightworld_items[i].serialize()),
+            SaveField::LightworldPhone => MergeResult::Resolved(phone_numbers[i].serialize()),
+            SaveField::Flags if recruit_flags.contains(&i) => {
+                merge_recruit(&ours.flags[i], &theirs.flags[i], ancestor.map(|a| &a.flags[i])).map(|v| v.serialize())
+            }
+            SaveField::Flags if ancestor.is_some() => {
+                merge_simple(&ours.flags[i], &theirs.flags[i], ancestor.map(|a| &a.flags[i])).map(|v| v.serialize())
+            }
+            SaveField::Flags => merge_max(&ours.flags[i], &theirs.flags[i]).map(|v| v.serialize()),
+            SaveField::UnusedFlags => MergeResult::Resolved(0.serialize()),
+            SaveField::PlotValue => MergeResult::Resolved(ours.plot_value.serialize()),
+            SaveField::RoomId => MergeResult::Resolved(ours.room_id.serialize()),
+            SaveField::TimePlayed if ancestor.is_some() && ours.time_played != theirs.time_played => {
+                merge_values(
+                    ours.time_played_frames(),
+                    theirs.time_played_frames(),
+                    ancestor.map(|a| a.time_played_frames()),
+                    None, None,
+                    "time played"
+                ).map(|v| GmReal::from(v as f64).serialize())
+            }
+            SaveField::TimePlayed => {
+                merge_max(&ours.time_played, &theirs.time_played).map(|v| v.serialize())
+            }
+        };
+        data.push(result);
+        Ok(())
+    })?;
 
-    assert_eq!(data.len(), if chapter == 1 { 10318 } else { 3055 }, "Unexpected line count");
+    assert_eq!(data.len(), layout.line_count(), "Unexpected line count");
 
     Ok(data)
 }
@@ -630,4 +549,61 @@ mod tests {
 
         assert_eq!(ours, expected);
     }
+
+    #[test]
+    fn test_merge_fixtures() {
+        use crate::gamedata::flags::Flag;
+        use crate::savefile::FIXTURES;
+        use std::time::Duration;
+
+        for (chapter, content) in FIXTURES {
+            let lines = content.lines().collect::<Vec<_>>();
+            let ancestor = SaveData::read(chapter, &lines).unwrap();
+
+            let mut ours = ancestor.clone();
+            ours.dark_dollars += 100;
+            ours.flags[Flag::ShadowCrystalCh1.index()].set(1.0);
+            let mut theirs = ancestor.clone();
+            theirs.dark_dollars -= 20;
+            theirs.set_time_played(theirs.time_played_duration() + Duration::from_secs(60));
+
+            let merged = merge_savefiles(&ours, &theirs, Some(&ancestor))
+                .unwrap()
+                .iter()
+                .map(|result| match result {
+                    MergeResult::Resolved(line) => line.clone(),
+                    conflict => panic!("chapter {chapter}: {}", conflict.to_merge_string(7)),
+                })
+                .collect::<Vec<_>>();
+
+            let merged_lines = merged.iter().map(|line| line.as_str()).collect::<Vec<_>>();
+            let save = SaveData::read(chapter, &merged_lines).unwrap();
+            assert_eq!(save.dark_dollars, ancestor.dark_dollars + 80);
+            assert_eq!(save.time_played, theirs.time_played);
+            assert_eq!(save.shadow_crystals(), 1);
+            assert_eq!(save.plot_value, ancestor.plot_value);
+            assert_eq!(save.write(), merged.join("\r\n"));
+        }
+    }
+
+    #[test]
+    fn test_merge_recruit() {
+        let [none, half, recruited, lost] = [0.0, 0.5, 1.0, -1.0].map(GmReal::new);
+
+        assert!(matches!(merge_recruit(&half, &none, Some(&half)), MergeResult::Resolved(v) if *v == 0.5));
+        assert!(matches!(merge_recruit(&half, &recruited, None), MergeResult::Resolved(v) if *v == 1.0));
+        assert!(matches!(merge_recruit(&lost, &half, Some(&none)), MergeResult::Resolved(v) if *v == -1.0));
+        assert!(matches!(merge_recruit(&recruited, &lost, Some(&half)), MergeResult::Conflict { .. }));
+        // Only flags of the chapter's recruits merge this way, 602 is just a flag in chapter 3
+        let ancestor = SaveData::builder(3).build();
+        let mut ours = ancestor.clone();
+        let mut theirs = ancestor.clone();
+        for (index, our_value, their_value) in [(602, 0.5, 1.0), (632, 0.5, 1.0)] {
+            ours.flags[index].set(our_value);
+            theirs.flags[index].set(their_value);
+        }
+        let merged = merge_savefiles(&ours, &theirs, Some(&ancestor)).unwrap();
+        let conflicts = merged.iter().filter(|r| matches!(r, MergeResult::Conflict { .. }));
+        assert_eq!(conflicts.count(), 1);
+    }
 }
@@ -0,0 +1,215 @@
+//! Infers the route a save is on and the notable choices made, from its flags, party and
+//! inventory.
+
+use crate::gamedata::flags::Flag;
+use crate::gamedata::key_items::KeyItemId;
+use crate::gamedata::lightworld_items::LightworldItemId;
+use crate::gamedata::party_members::PartyMember;
+use crate::gamedata::recruits::RecruitStatus;
+use crate::gamedata::spells::SpellId;
+use crate::gamedata::weapons::WeaponId;
+use crate::savefile::SaveData;
+use compact_str::{CompactString, ToCompactString};
+use indoc::indoc;
+use std::fmt::{Display, Formatter};
+
+const THORN_RING: WeaponId = WeaponId(13);
+const FREEZE_RING: WeaponId = WeaponId(22);
+const SNOWGRAVE: SpellId = SpellId(10);
+
+/// Key items that are only found by going out of the way.
+const SECRET_KEY_ITEMS: [KeyItemId; 5] = [
+    KeyItemId(2),  // Egg
+    KeyItemId(13), // ShadowCrystal
+    KeyItemId(14), // Starwalker
+    KeyItemId(15), // PureCrystal
+    KeyItemId(16), // OddController
+];
+const LIGHTWORLD_EGG: LightworldItemId = LightworldItemId(8);
+
+/// Optional bosses and the flag that's set once they were fought.
+const OPTIONAL_BOSSES: [(&str, Flag); 2] = [
+    ("Jevil", Flag::JevilFight),
+    ("Spamton NEO", Flag::SpamtonNeoFight),
+];
+
+/// Names of the optional bosses [`Profile::optional_bosses`] can list.
+pub const OPTIONAL_BOSS_NAMES: [&str; OPTIONAL_BOSSES.len()] = {
+    let mut names = [""; OPTIONAL_BOSSES.len()];
+    let mut i = 0;
+    while i < names.len() {
+        names[i] = OPTIONAL_BOSSES[i].0;
+        i += 1;
+    }
+    names
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Route {
+    Normal,
+    /// Noelle was given the ThornRing, or the route's progress flag is set.
+    SnowgraveInProgress,
+    /// Noelle learned SnowGrave.
+    SnowgraveComplete,
+}
+
+impl Route {
+    /// IDs of every route, as returned by [`id`](Self::id).
+    pub const IDS: [&'static str; 3] = ["normal", "snowgrave_in_progress", "snowgrave_complete"];
+
+    /// The name used in queries and JSON output.
+    pub fn id(self) -> &'static str {
+        match self {
+            Route::Normal => "normal",
+            Route::SnowgraveInProgress => "snowgrave_in_progress",
+            Route::SnowgraveComplete => "snowgrave_complete",
+        }
+    }
+}
+
+impl Display for Route {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Route::Normal => "Normal",
+            Route::SnowgraveInProgress => "Snowgrave (in progress)",
+            Route::SnowgraveComplete => "Snowgrave (complete)",
+        })
+    }
+}
+
+/// What kind of playthrough a save is from.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Profile {
+    pub route: Route,
+    /// Number of the chapter's recruitable enemies that were recruited.
+    pub recruits: usize,
+    /// Number of recruits lost by defeating the enemy instead.
+    pub lost_recruits: usize,
+    /// Optional bosses that were fought.
+    pub optional_bosses: Vec<&'static str>,
+    /// Secret items held, like the Egg.
+    pub secrets: Vec<CompactString>,
+}
+
+fn has_weapon(save: &SaveData, weapon: WeaponId) -> bool {
+    save.weapons.contains(&weapon) || save.stats.iter().any(|stats| stats.weapon == weapon)
+}
+
+fn detect_route(save: &SaveData) -> Route {
+    // Snowgrave was added in chapter 2, and its items and spells carry over to later chapters.
+    if save.chapter < 2 {
+        return Route::Normal;
+    }
+
+    let noelle = save.stats.get(PartyMember::NOELLE.0 as usize);
+    if noelle.is_some_and(|stats| stats.spells.contains(&SNOWGRAVE)) {
+        Route::SnowgraveComplete
+    } else if has_weapon(save, THORN_RING)
+        || has_weapon(save, FREEZE_RING)
+        || save.flag(Flag::WeirdRouteProgress).is_set()
+    {
+        Route::SnowgraveInProgress
+    } else {
+        Route::Normal
+    }
+}
+
+/// Builds the profile of a save.
+pub fn profile(save: &SaveData) -> Profile {
+    let recruits = save.recruits();
+
+    let optional_bosses = OPTIONAL_BOSSES
+        .iter()
+        .filter(|(_, flag)| flag.info().chapter <= save.chapter && save.flag(*flag).is_set())
+        .map(|&(name, _)| name)
+        .collect();
+
+    let mut secrets = SECRET_KEY_ITEMS
+        .iter()
+        .filter(|id| save.key_items.contains(id))
+        .map(|id| id.to_compact_string())
+        .collect::<Vec<_>>();
+    if save.lightworld_items.contains(&LIGHTWORLD_EGG) {
+        secrets.push(format!("{} (Light World)", LIGHTWORLD_EGG).into());
+    }
+
+    Profile {
+        route: detect_route(save),
+        recruits: recruits.iter().filter(|(_, status)| *status == RecruitStatus::Recruited).count(),
+        lost_recruits: recruits.iter().filter(|(_, status)| *status == RecruitStatus::Lost).count(),
+        optional_bosses,
+        secrets,
+    }
+}
+
+impl Profile {
+    pub fn display(&self) -> String {
+        let list = |values: Vec<String>| {
+            if values.is_empty() { "---".to_string() } else { values.join(", ") }
+        };
+
+        format!(
+            indoc! {"
+                ---------------
+                Profile:
+                Route: {}
+                Recruits: {} ({} lost)
+                Optional bosses: {}
+                Secrets: {}
+            "},
+            self.route,
+            self.recruits,
+            self.lost_recruits,
+            list(self.optional_bosses.iter().map(|name| name.to_string()).collect()),
+            list(self.secrets.iter().map(|name| name.to_string()).collect()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile() {
+        let lines = vec!["0"; 3055];
+        let mut save = SaveData::read(2, &lines).unwrap();
+        assert_eq!(profile(&save).route, Route::Normal);
+
+        save.weapons[0] = THORN_RING;
+        save.key_items[2] = KeyItemId(2);
+        // Flags of enemies that aren't recruitable in this chapter don't count
+        save.flags[602].set(1.0);
+        save.flags[631].set(-1.0);
+        save.flags[Flag::SpamtonNeoFight.index()].set(1.0);
+        // Jevil is from chapter 1 and still counts in later chapters
+        save.flags[Flag::JevilFight.index()].set(2.0);
+
+        let profile_in_progress = profile(&save);
+        assert_eq!(profile_in_progress.route, Route::SnowgraveInProgress);
+        assert_eq!(profile_in_progress.recruits, 0);
+        assert_eq!(profile_in_progress.lost_recruits, 0);
+        assert_eq!(profile_in_progress.optional_bosses, vec!["Jevil", "Spamton NEO"]);
+        assert_eq!(profile_in_progress.secrets, vec![CompactString::from("Egg")]);
+
+        // Block 3 is Ralsei's
+        save.stats[3].spells[1] = SNOWGRAVE;
+        assert_eq!(profile(&save).route, Route::SnowgraveInProgress);
+        save.stats[4].spells[1] = SNOWGRAVE;
+        assert_eq!(profile(&save).route, Route::SnowgraveComplete);
+    }
+
+    #[test]
+    fn test_profile_recruits() {
+        let mut save = SaveData::builder(3).build();
+        save.flags[602].set(1.0);
+        save.flags[631].set(-1.0);
+        save.flags[632].set(1.0);
+        save.flags[633].set(0.5);
+
+        let profile = profile(&save);
+        assert_eq!(profile.recruits, 1);
+        assert_eq!(profile.lost_recruits, 1);
+    }
+}
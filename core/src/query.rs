@@ -0,0 +1,1067 @@
+//! A small expression language over [`SaveData`].
+//!
+//! Fields are bound through the same paths as the [`SaveData`] struct, with a couple of shorter
+//! aliases (`lv`, `plot`, `room`). Names from the gamedata tables can be used in place
+//! of IDs, quoted if they contain spaces. An unquoted name is only read as one when it's
+//! compared with something that has names, any other unknown identifier is an error. Known
+//! flags can be read by name as `flag.<name>`, and the inferred route and choices as
+//! `profile.<field>`. Routes and optional bosses must be compared with one of their known names.
+//! `time_played` is in frames like in the save, `time_played_secs` in seconds.
+//!
+//! ```text
+//! flags[1646] == 1
+//! flag.shadow_crystal_ch1 and flag.lancer_con_count > 0
+//! profile.route == snowgrave_complete or profile.secrets contains Egg
+//! lv >= 3 and party contains Noelle
+//! count(inventory, "Dark Candy") > 2
+//! room.chapter == 2 or not stats[1].weapon == "Wood Blade"
+//! ```
+
+use crate::gamedata::GameContext;
+use crate::gamedata::armors::ArmorId;
+use crate::gamedata::items::ItemId;
+use crate::gamedata::key_items::KeyItemId;
+use crate::gamedata::flags::{Flag, FlagValue};
+use crate::gamedata::lightworld_items::LightworldItemId;
+use crate::gamedata::party_members::PartyMember;
+use crate::gamedata::phone_numbers::PhoneNumberId;
+use crate::gamedata::rooms::RoomIndex;
+use crate::gamedata::spells::SpellId;
+use crate::gamedata::weapons::WeaponId;
+use crate::profile::{OPTIONAL_BOSS_NAMES, Route};
+use crate::savefile::{ItemStats, SaveData, Stats};
+use compact_str::{CompactString, ToCompactString};
+use std::fmt::Display;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum QueryError {
+    #[error("unexpected character '{0}'")]
+    UnexpectedChar(char),
+    #[error("unexpected token {0}")]
+    UnexpectedToken(CompactString),
+    #[error("query ended unexpectedly")]
+    UnexpectedEnd,
+    #[error("unknown field {0}")]
+    UnknownField(CompactString),
+    #[error("index {index} is out of range for {field}")]
+    IndexOutOfRange { field: CompactString, index: usize },
+    #[error("unknown {kind} {name}")]
+    UnknownName { kind: &'static str, name: CompactString },
+    #[error("unknown function {0}")]
+    UnknownFunction(CompactString),
+    #[error("wrong arguments for {0}")]
+    WrongArguments(CompactString),
+    #[error("cannot apply {op} to {lhs} and {rhs}")]
+    TypeMismatch {
+        op: BinaryOp,
+        lhs: &'static str,
+        rhs: &'static str,
+    },
+    #[error("expected a boolean, got {0}")]
+    NotBoolean(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(CompactString),
+    Number(f64),
+    Text(CompactString),
+    Op(BinaryOp),
+    LBracket,
+    RBracket,
+    LParen,
+    RParen,
+    Comma,
+    Dot,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(ident) => write!(f, "{}", ident),
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Text(text) => write!(f, "\"{}\"", text),
+            Token::Op(op) => write!(f, "{}", op),
+            Token::LBracket => write!(f, "["),
+            Token::RBracket => write!(f, "]"),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+            Token::Dot => write!(f, "."),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        let single = match c {
+            '[' => Some(Token::LBracket),
+            ']' => Some(Token::RBracket),
+            '(' => Some(Token::LParen),
+            ')' => Some(Token::RParen),
+            ',' => Some(Token::Comma),
+            '.' => Some(Token::Dot),
+            _ => None,
+        };
+        if let Some(token) = single {
+            chars.next();
+            tokens.push(token);
+            continue;
+        }
+
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '"' => {
+                chars.next();
+                let mut text = CompactString::default();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => text.push(c),
+                        None => return Err(QueryError::UnexpectedEnd),
+                    }
+                }
+                tokens.push(Token::Text(text));
+            }
+            '=' | '!' | '<' | '>' => {
+                chars.next();
+                let has_eq = chars.next_if_eq(&'=').is_some();
+                let op = match (c, has_eq) {
+                    ('=', true) => BinaryOp::Eq,
+                    ('!', true) => BinaryOp::Ne,
+                    ('<', false) => BinaryOp::Lt,
+                    ('<', true) => BinaryOp::Le,
+                    ('>', false) => BinaryOp::Gt,
+                    ('>', true) => BinaryOp::Ge,
+                    _ => return Err(QueryError::UnexpectedChar(c)),
+                };
+                tokens.push(Token::Op(op));
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let mut number = CompactString::default();
+                number.push(c);
+                chars.next();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                    number.push(c);
+                }
+                let number = number
+                    .parse()
+                    .map_err(|_| QueryError::UnexpectedToken(number))?;
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut ident = CompactString::default();
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                    ident.push(c);
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            c => return Err(QueryError::UnexpectedChar(c)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BinaryOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    And,
+    Or,
+}
+
+impl Display for BinaryOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::Contains => "contains",
+            BinaryOp::And => "and",
+            BinaryOp::Or => "or",
+        })
+    }
+}
+
+impl BinaryOp {
+    /// Whether the operands can be names, like in `party contains Noelle`.
+    fn compares_names(self) -> bool {
+        matches!(self, BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Contains)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    Field(CompactString),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Text(CompactString),
+    /// A field path like `stats[1].item_stats[0].attack`. A single unknown identifier compared
+    /// with IDs or names is treated as a name, so `party contains Noelle` works without quotes.
+    Path(Vec<PathSegment>),
+    Call(CompactString, Vec<Expr>),
+    Not(Box<Expr>),
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+}
+
+struct ExprParser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl ExprParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<Token, QueryError> {
+        let token = self.peek().cloned().ok_or(QueryError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), QueryError> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(QueryError::UnexpectedToken(token.to_compact_string())),
+        }
+    }
+
+    fn next_if(&mut self, expected: &Token) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn next_if_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Ident(ident)) if ident.eq_ignore_ascii_case(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.parse_and()?;
+        while self.next_if_keyword("or") {
+            expr = Expr::Binary(Box::new(expr), BinaryOp::Or, Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.parse_not()?;
+        while self.next_if_keyword("and") {
+            expr = Expr::Binary(Box::new(expr), BinaryOp::And, Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, QueryError> {
+        if self.next_if_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, QueryError> {
+        let lhs = self.parse_primary()?;
+
+        let op = match self.peek() {
+            Some(Token::Op(op)) => *op,
+            Some(Token::Ident(ident)) if ident.eq_ignore_ascii_case("contains") => {
+                BinaryOp::Contains
+            }
+            _ => return Ok(lhs),
+        };
+        self.position += 1;
+
+        Ok(Expr::Binary(Box::new(lhs), op, Box::new(self.parse_primary()?)))
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, QueryError> {
+        match self.next()? {
+            Token::Number(number) => Ok(Expr::Number(number)),
+            Token::Text(text) => Ok(Expr::Text(text)),
+            Token::LParen => {
+                let expr = self.parse_or()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Token::Ident(name) if self.next_if(&Token::LParen) => {
+                let mut args = Vec::new();
+                if !self.next_if(&Token::RParen) {
+                    loop {
+                        args.push(self.parse_or()?);
+                        if self.next_if(&Token::RParen) {
+                            break;
+                        }
+                        self.expect(Token::Comma)?;
+                    }
+                }
+                Ok(Expr::Call(name, args))
+            }
+            Token::Ident(name) => self.parse_path(name),
+            token => Err(QueryError::UnexpectedToken(token.to_compact_string())),
+        }
+    }
+
+    fn parse_path(&mut self, root: CompactString) -> Result<Expr, QueryError> {
+        let mut path = vec![PathSegment::Field(root)];
+
+        loop {
+            if self.next_if(&Token::Dot) {
+                match self.next()? {
+                    Token::Ident(field) => path.push(PathSegment::Field(field)),
+                    token => return Err(QueryError::UnexpectedToken(token.to_compact_string())),
+                }
+            } else if self.next_if(&Token::LBracket) {
+                match self.next()? {
+                    Token::Number(index) if index >= 0.0 && index.fract() == 0.0 => {
+                        path.push(PathSegment::Index(index as usize))
+                    }
+                    token => return Err(QueryError::UnexpectedToken(token.to_compact_string())),
+                }
+                self.expect(Token::RBracket)?;
+            } else {
+                return Ok(Expr::Path(path));
+            }
+        }
+    }
+}
+
+/// The gamedata table an ID belongs to.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum IdKind {
+    Item,
+    KeyItem,
+    Weapon,
+    Armor,
+    Spell,
+    PartyMember,
+    LightworldItem,
+    PhoneNumber,
+    Room,
+}
+
+impl IdKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            IdKind::Item => ItemId::KIND,
+            IdKind::KeyItem => KeyItemId::KIND,
+            IdKind::Weapon => WeaponId::KIND,
+            IdKind::Armor => ArmorId::KIND,
+            IdKind::Spell => SpellId::KIND,
+            IdKind::PartyMember => PartyMember::KIND,
+            IdKind::LightworldItem => LightworldItemId::KIND,
+            IdKind::PhoneNumber => PhoneNumberId::KIND,
+            IdKind::Room => RoomIndex::KIND,
+        }
+    }
+
+    pub fn display(self, id: i32) -> CompactString {
+        match self {
+            IdKind::Item => ItemId(id).to_compact_string(),
+            IdKind::KeyItem => KeyItemId(id).to_compact_string(),
+            IdKind::Weapon => WeaponId(id).to_compact_string(),
+            IdKind::Armor => ArmorId(id).to_compact_string(),
+            IdKind::Spell => SpellId(id).to_compact_string(),
+            IdKind::PartyMember => PartyMember(id).to_compact_string(),
+            IdKind::LightworldItem => LightworldItemId(id).to_compact_string(),
+            IdKind::PhoneNumber => PhoneNumberId(id).to_compact_string(),
+            IdKind::Room => RoomIndex(id).to_compact_string(),
+        }
+    }
+
+    /// Returns the name of the given ID in the latest chapter, like it's displayed. Rooms are
+    /// named by their internal name, like `room_krisroom`.
+    pub fn try_get_name(self, id: i32) -> Option<&'static str> {
+        let latest = GameContext::latest();
+        match self {
+            IdKind::Item => ItemId(id).name(&latest).ok(),
+            IdKind::KeyItem => KeyItemId(id).name(&latest).ok(),
+            IdKind::Weapon => WeaponId(id).name(&latest).ok(),
+            IdKind::Armor => ArmorId(id).name(&latest).ok(),
+            IdKind::Spell => SpellId(id).name(&latest).ok(),
+            IdKind::PartyMember => PartyMember(id).name(&latest).ok(),
+            IdKind::LightworldItem => LightworldItemId(id).name(&latest).ok(),
+            IdKind::PhoneNumber => PhoneNumberId(id).name(&latest).ok(),
+            IdKind::Room => RoomIndex(id).id_name(),
+        }
+    }
+
+    /// Checks whether the given ID has the given name, ignoring case.
+    ///
+    /// Fails if no ID of this kind has that name, so that typos don't silently never match.
+    fn has_name(self, id: i32, name: &str) -> Result<bool, QueryError> {
+        if self.try_get_name(id).is_some_and(|n| n.eq_ignore_ascii_case(name)) {
+            return Ok(true);
+        }
+
+        let exists = match self {
+            IdKind::Item => name.parse::<ItemId>().is_ok(),
+            IdKind::KeyItem => name.parse::<KeyItemId>().is_ok(),
+            IdKind::Weapon => name.parse::<WeaponId>().is_ok(),
+            IdKind::Armor => name.parse::<ArmorId>().is_ok(),
+            IdKind::Spell => name.parse::<SpellId>().is_ok(),
+            IdKind::PartyMember => name.parse::<PartyMember>().is_ok(),
+            IdKind::LightworldItem => name.parse::<LightworldItemId>().is_ok(),
+            IdKind::PhoneNumber => name.parse::<PhoneNumberId>().is_ok(),
+            IdKind::Room => name.parse::<RoomIndex>().is_ok(),
+        };
+
+        if exists {
+            Ok(false)
+        } else {
+            Err(QueryError::UnknownName {
+                kind: self.name(),
+                name: name.into(),
+            })
+        }
+    }
+}
+
+/// A value that can only be one of a few known names, like a route.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ChoiceKind {
+    Route,
+    OptionalBoss,
+}
+
+impl ChoiceKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ChoiceKind::Route => "route",
+            ChoiceKind::OptionalBoss => "optional boss",
+        }
+    }
+
+    pub fn options(self) -> &'static [&'static str] {
+        match self {
+            ChoiceKind::Route => &Route::IDS,
+            ChoiceKind::OptionalBoss => &OPTIONAL_BOSS_NAMES,
+        }
+    }
+
+    /// Checks whether `value` is `name`, ignoring case. Fails if `name` isn't one of the options,
+    /// like [`IdKind`] does for unknown names.
+    fn has_name(self, value: &str, name: &str) -> Result<bool, QueryError> {
+        if self.options().iter().any(|option| option.eq_ignore_ascii_case(name)) {
+            Ok(value.eq_ignore_ascii_case(name))
+        } else {
+            Err(QueryError::UnknownName {
+                kind: self.name(),
+                name: name.into(),
+            })
+        }
+    }
+}
+
+/// The result of evaluating an [`Expr`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Number(f64),
+    Text(CompactString),
+    Id(i32, IdKind),
+    Choice(&'static str, ChoiceKind),
+    List(Vec<Value>),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::Text(_) => "text",
+            Value::Id(_, kind) => kind.name(),
+            Value::Choice(_, kind) => kind.name(),
+            Value::List(_) => "list",
+        }
+    }
+
+    /// Whether this is something other than an empty slot.
+    fn is_present(&self) -> bool {
+        match self {
+            Value::Bool(b) => *b,
+            Value::Number(n) => *n != 0.0,
+            Value::Text(t) => !t.is_empty(),
+            Value::Id(id, _) => *id != 0,
+            Value::Choice(..) => true,
+            Value::List(list) => !list.is_empty(),
+        }
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            Value::Number(n) => Some(*n),
+            Value::Id(id, _) => Some(*id as f64),
+            _ => None,
+        }
+    }
+
+    /// Whether a name can stand in for this value, like `Noelle` for a party member.
+    fn has_names(&self) -> bool {
+        match self {
+            Value::Text(_) | Value::Id(..) | Value::Choice(..) => true,
+            Value::List(list) => list.first().is_none_or(Value::has_names),
+            _ => false,
+        }
+    }
+
+    /// Checks whether two values are equal, resolving names to IDs where needed.
+    fn equals(&self, other: &Value) -> Result<Option<bool>, QueryError> {
+        let result = match (self, other) {
+            (Value::Id(id, kind), Value::Text(name)) | (Value::Text(name), Value::Id(id, kind)) => {
+                Some(kind.has_name(*id, name)?)
+            }
+            (Value::Choice(value, kind), Value::Text(name))
+            | (Value::Text(name), Value::Choice(value, kind)) => Some(kind.has_name(value, name)?),
+            (Value::Choice(a, _), Value::Choice(b, _)) => Some(a == b),
+            (Value::Text(a), Value::Text(b)) => Some(a == b),
+            (Value::List(a), Value::List(b)) => Some(a == b),
+            (a, b) => a
+                .as_number()
+                .zip(b.as_number())
+                .map(|(a, b)| a == b),
+        };
+        Ok(result)
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Text(t) => write!(f, "{}", t),
+            Value::Id(id, kind) => write!(f, "{}", kind.display(*id)),
+            Value::Choice(value, _) => write!(f, "{}", value),
+            Value::List(list) => {
+                let items = list.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+                write!(f, "[{}]", items.join(", "))
+            }
+        }
+    }
+}
+
+fn ids<T: Copy + Into<i32>>(ids: &[T], kind: IdKind) -> Value {
+    Value::List(ids.iter().map(|&id| Value::Id(id.into(), kind)).collect())
+}
+
+fn numbers<'a, T>(numbers: &'a [T]) -> Value
+where
+    &'a T: Into<f64>,
+{
+    Value::List(numbers.iter().map(|n| Value::Number(n.into())).collect())
+}
+
+fn resolve_item_stats(item_stats: &ItemStats, field: &str) -> Option<Value> {
+    let value = match field {
+        "attack" => item_stats.attack,
+        "defense" => item_stats.defense,
+        "magic" => item_stats.magic,
+        "bolts" => item_stats.bolts,
+        "graze_amount" => item_stats.graze_amount,
+        "graze_size" => item_stats.graze_size,
+        "bolts_speed" => item_stats.bolts_speed,
+        "item_special" => item_stats.item_special,
+        "item_element" => item_stats.item_element,
+        "item_element_amount" => return Some(Value::Number(item_stats.item_element_amount.value())),
+        _ => return None,
+    };
+    Some(Value::Number(value.into()))
+}
+
+fn resolve_stats(stats: &Stats, field: &str) -> Option<Value> {
+    let value = match field {
+        "hp" => stats.hp,
+        "max_hp" => stats.max_hp,
+        "attack" => stats.attack,
+        "defense" => stats.defense,
+        "magic" => stats.magic,
+        "guts" => stats.guts,
+        "weapon" => return Some(Value::Id(stats.weapon.into(), IdKind::Weapon)),
+        "armor1" => return Some(Value::Id(stats.armor1.into(), IdKind::Armor)),
+        "armor2" => return Some(Value::Id(stats.armor2.into(), IdKind::Armor)),
+        "weapon_style" => return Some(Value::Text(stats.weapon_style.clone())),
+        "spells" => return Some(ids(&stats.spells, IdKind::Spell)),
+        _ => return None,
+    };
+    Some(Value::Number(value.into()))
+}
+
+/// Resolves a field path that doesn't end with an index.
+fn resolve_field(save: &SaveData, path: &[PathSegment]) -> Option<Value> {
+    use PathSegment::{Field, Index};
+
+    let lw = &save.lightworld_stats;
+
+    let value = match path {
+        [Field(root)] => match root.as_str() {
+            "chapter" => Value::Number(save.chapter.into()),
+            "true_name" => Value::Text(save.true_name.as_ref().into()),
+            "vessel_names" => {
+                let names = save.vessel_names.iter().map(|n| Value::Text(n.as_ref().into()));
+                Value::List(names.collect())
+            }
+            "party" => ids(&save.party, IdKind::PartyMember),
+            "dark_dollars" => Value::Number(save.dark_dollars.into()),
+            "xp" => Value::Number(save.xp.into()),
+            "level" | "lv" => Value::Number(save.level.into()),
+            "inv" => Value::Number(save.inv.into()),
+            "invc" => Value::Number(save.invc.into()),
+            "is_darkworld" => Value::Bool(save.is_darkworld),
+            "bolt_speed" => Value::Number(save.bolt_speed.into()),
+            "graze_amount" => Value::Number(save.graze_amount.into()),
+            "graze_size" => Value::Number(save.graze_size.into()),
+            "inventory" => ids(&save.inventory, IdKind::Item),
+            "key_items" => ids(&save.key_items, IdKind::KeyItem),
+            "weapons" => ids(&save.weapons, IdKind::Weapon),
+            "armors" => ids(&save.armors, IdKind::Armor),
+            "storage" => ids(save.storage.as_deref().unwrap_or_default(), IdKind::Item),
+            "tension" => Value::Number(save.tension.value()),
+            "max_tension" => Value::Number(save.max_tension.value()),
+            "lightworld_items" => ids(&save.lightworld_items, IdKind::LightworldItem),
+            "lightworld_phone" => ids(&save.lightworld_phone, IdKind::PhoneNumber),
+            "flags" => numbers(&save.flags),
+            "plot_value" | "plot" => Value::Number(save.plot_value.value()),
+            "room_id" | "room" => Value::Id(save.room_id.into(), IdKind::Room),
+            "time_played" => Value::Number(save.time_played.value()),
+            "time_played_secs" => Value::Number(save.time_played_duration().as_secs_f64()),
+            _ => return None,
+        },
+        [Field(room), Field(field)] if room == "room" => match field.as_str() {
+            "id" => Value::Number(save.room_id.0.into()),
+            "chapter" => Value::Number(save.room_id.chapter().into()),
+            "index" => Value::Number(save.room_id.index().into()),
+            "name" => Value::Text(save.room_name().unwrap_or_default().into()),
+            _ => return None,
+        },
+        [Field(profile), Field(field)] if profile == "profile" => {
+            let profile = save.profile();
+            let texts = |values: Vec<CompactString>| {
+                Value::List(values.into_iter().map(Value::Text).collect())
+            };
+            match field.as_str() {
+                "route" => Value::Choice(profile.route.id(), ChoiceKind::Route),
+                "recruits" => Value::Number(profile.recruits as f64),
+                "lost_recruits" => Value::Number(profile.lost_recruits as f64),
+                "optional_bosses" => Value::List(
+                    profile
+                        .optional_bosses
+                        .into_iter()
+                        .map(|boss| Value::Choice(boss, ChoiceKind::OptionalBoss))
+                        .collect(),
+                ),
+                "secrets" => texts(profile.secrets),
+                _ => return None,
+            }
+        }
+        [Field(flag), Field(name)] if flag == "flag" => {
+            match name.parse::<Flag>().ok()?.read(&save.flags) {
+                FlagValue::Bool(value) => Value::Bool(value),
+                FlagValue::Count(value) => Value::Number(value.into()),
+            }
+        }
+        [Field(stats), Index(i), Field(field)] if stats == "stats" => {
+            resolve_stats(save.stats.get(*i)?, field)?
+        }
+        [Field(stats), Index(i), Field(item_stats), Index(j), Field(field)]
+            if stats == "stats" && item_stats == "item_stats" =>
+        {
+            resolve_item_stats(save.stats.get(*i)?.item_stats.get(*j)?, field)?
+        }
+        [Field(lightworld_stats), Field(field)] if lightworld_stats == "lightworld_stats" => {
+            let value = match field.as_str() {
+                "weapon" => return Some(Value::Id(lw.weapon.into(), IdKind::LightworldItem)),
+                "armor" => return Some(Value::Id(lw.armor.into(), IdKind::LightworldItem)),
+                "xp" => lw.xp,
+                "lv" => lw.lv,
+                "gold" => lw.gold,
+                "hp" => lw.hp,
+                "max_hp" => lw.max_hp,
+                "attack" => lw.attack,
+                "defense" => lw.defense,
+                "weapon_attack" => lw.weapon_attack,
+                "armor_defense" => lw.armor_defense,
+                _ => return None,
+            };
+            Value::Number(value.into())
+        }
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+fn display_path(path: &[PathSegment]) -> CompactString {
+    let mut output = CompactString::default();
+    for segment in path {
+        match segment {
+            PathSegment::Field(field) if output.is_empty() => output.push_str(field),
+            PathSegment::Field(field) => {
+                output.push('.');
+                output.push_str(field);
+            }
+            PathSegment::Index(index) => output.push_str(&format!("[{}]", index)),
+        }
+    }
+    output
+}
+
+fn resolve(save: &SaveData, path: &[PathSegment]) -> Result<Value, QueryError> {
+    // Avoid building the whole list for the most common lookup.
+    if let [PathSegment::Field(flags), PathSegment::Index(i)] = path
+        && flags == "flags"
+    {
+        return save
+            .flags
+            .get(*i)
+            .map(|flag| Value::Number(flag.value()))
+            .ok_or_else(|| QueryError::IndexOutOfRange {
+                field: flags.clone(),
+                index: *i,
+            });
+    }
+
+    if let Some(value) = resolve_field(save, path) {
+        return Ok(value);
+    }
+
+    match path {
+        [parent @ .., PathSegment::Index(index)] => match resolve_field(save, parent) {
+            Some(Value::List(list)) => list.get(*index).cloned().ok_or_else(|| {
+                QueryError::IndexOutOfRange {
+                    field: display_path(parent),
+                    index: *index,
+                }
+            }),
+            _ => Err(QueryError::UnknownField(display_path(path))),
+        },
+        _ => Err(QueryError::UnknownField(display_path(path))),
+    }
+}
+
+fn call(name: &str, args: Vec<Value>) -> Result<Value, QueryError> {
+    let wrong_arguments = || QueryError::WrongArguments(name.into());
+
+    match (name, args.as_slice()) {
+        // count(list) counts non-empty slots, count(list, x) counts occurrences of x
+        ("count", [Value::List(list)]) => {
+            Ok(Value::Number(list.iter().filter(|v| v.is_present()).count() as f64))
+        }
+        ("count", [Value::List(list), needle]) => {
+            let mut count = 0;
+            for value in list {
+                if value.equals(needle)?.unwrap_or(false) {
+                    count += 1;
+                }
+            }
+            Ok(Value::Number(count as f64))
+        }
+        ("count", _) => Err(wrong_arguments()),
+        ("len", [Value::List(list)]) => Ok(Value::Number(list.len() as f64)),
+        ("len", [Value::Text(text)]) => Ok(Value::Number(text.chars().count() as f64)),
+        ("len", _) => Err(wrong_arguments()),
+        ("name", [Value::Id(id, kind)]) => Ok(Value::Text(kind.display(*id))),
+        ("name", _) => Err(wrong_arguments()),
+        _ => Err(QueryError::UnknownFunction(name.into())),
+    }
+}
+
+/// Reads a bare identifier that isn't a field as a name, if it's compared with `other`.
+fn name_operand(
+    expr: &Expr,
+    result: Result<Value, QueryError>,
+    other: &Value,
+) -> Result<Value, QueryError> {
+    match (expr, result) {
+        (Expr::Path(path), Err(QueryError::UnknownField(field))) => match path.as_slice() {
+            [PathSegment::Field(name)] if other.has_names() => Ok(Value::Text(name.clone())),
+            _ => Err(QueryError::UnknownField(field)),
+        },
+        (_, result) => result,
+    }
+}
+
+fn as_bool(value: &Value) -> Result<bool, QueryError> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        other => Err(QueryError::NotBoolean(other.type_name())),
+    }
+}
+
+impl Expr {
+    pub fn parse(source: &str) -> Result<Expr, QueryError> {
+        let mut parser = ExprParser {
+            tokens: tokenize(source)?,
+            position: 0,
+        };
+
+        let expr = parser.parse_or()?;
+
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(QueryError::UnexpectedToken(token.to_compact_string())),
+        }
+    }
+
+    pub fn evaluate(&self, save: &SaveData) -> Result<Value, QueryError> {
+        match self {
+            Expr::Number(number) => Ok(Value::Number(*number)),
+            Expr::Text(text) => Ok(Value::Text(text.clone())),
+            Expr::Path(path) => resolve(save, path),
+            Expr::Call(name, args) => {
+                let mut values = Vec::with_capacity(args.len());
+                for arg in args {
+                    let value = match values.first() {
+                        // The needle of `count(inventory, Glowshard)`
+                        Some(list) if name == "count" => {
+                            name_operand(arg, arg.evaluate(save), list)?
+                        }
+                        _ => arg.evaluate(save)?,
+                    };
+                    values.push(value);
+                }
+                call(name, values)
+            }
+            Expr::Not(expr) => Ok(Value::Bool(!as_bool(&expr.evaluate(save)?)?)),
+            // Both sides are always evaluated, so mistakes on the right aren't hidden by the left.
+            Expr::Binary(lhs, op @ (BinaryOp::And | BinaryOp::Or), rhs) => {
+                let lhs = as_bool(&lhs.evaluate(save)?)?;
+                let rhs = as_bool(&rhs.evaluate(save)?)?;
+                Ok(Value::Bool(if *op == BinaryOp::And { lhs && rhs } else { lhs || rhs }))
+            }
+            Expr::Binary(lhs_expr, op, rhs_expr) => {
+                let (lhs, rhs) = match (lhs_expr.evaluate(save), rhs_expr.evaluate(save)) {
+                    (Ok(lhs), rhs) if op.compares_names() => {
+                        let rhs = name_operand(rhs_expr, rhs, &lhs)?;
+                        (lhs, rhs)
+                    }
+                    (lhs, Ok(rhs)) if op.compares_names() => {
+                        (name_operand(lhs_expr, lhs, &rhs)?, rhs)
+                    }
+                    (lhs, rhs) => (lhs?, rhs?),
+                };
+                let mismatch = || QueryError::TypeMismatch {
+                    op: *op,
+                    lhs: lhs.type_name(),
+                    rhs: rhs.type_name(),
+                };
+
+                let result = match op {
+                    BinaryOp::Eq => lhs.equals(&rhs)?.ok_or_else(mismatch)?,
+                    BinaryOp::Ne => !lhs.equals(&rhs)?.ok_or_else(mismatch)?,
+                    BinaryOp::Contains => match (&lhs, &rhs) {
+                        (Value::List(list), needle) => {
+                            let mut found = false;
+                            for value in list {
+                                if value.equals(needle)?.unwrap_or(false) {
+                                    found = true;
+                                    break;
+                                }
+                            }
+                            found
+                        }
+                        (Value::Text(text), Value::Text(needle)) => text.contains(needle.as_str()),
+                        _ => return Err(mismatch()),
+                    },
+                    _ => {
+                        let (a, b) = lhs.as_number().zip(rhs.as_number()).ok_or_else(mismatch)?;
+                        match op {
+                            BinaryOp::Lt => a < b,
+                            BinaryOp::Le => a <= b,
+                            BinaryOp::Gt => a > b,
+                            _ => a >= b,
+                        }
+                    }
+                };
+
+                Ok(Value::Bool(result))
+            }
+        }
+    }
+
+    /// Evaluates the expression, expecting it to produce a boolean.
+    pub fn matches(&self, save: &SaveData) -> Result<bool, QueryError> {
+        as_bool(&self.evaluate(save)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(segments: &[&str]) -> Expr {
+        Expr::Path(
+            segments
+                .iter()
+                .map(|s| match s.parse() {
+                    Ok(index) => PathSegment::Index(index),
+                    Err(_) => PathSegment::Field((*s).into()),
+                })
+                .collect(),
+        )
+    }
+
+    fn binary(lhs: Expr, op: BinaryOp, rhs: Expr) -> Expr {
+        Expr::Binary(Box::new(lhs), op, Box::new(rhs))
+    }
+
+    #[test]
+    fn test_parse_compare() {
+        assert_eq!(
+            Expr::parse("flags[1646] == 1"),
+            Ok(binary(path(&["flags", "1646"]), BinaryOp::Eq, Expr::Number(1.0)))
+        );
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        assert_eq!(
+            Expr::parse("party contains Noelle and not lv>=2 or plot < -5"),
+            Ok(binary(
+                binary(
+                    binary(path(&["party"]), BinaryOp::Contains, path(&["Noelle"])),
+                    BinaryOp::And,
+                    Expr::Not(Box::new(binary(
+                        path(&["lv"]),
+                        BinaryOp::Ge,
+                        Expr::Number(2.0)
+                    ))),
+                ),
+                BinaryOp::Or,
+                binary(path(&["plot"]), BinaryOp::Lt, Expr::Number(-5.0)),
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_call_and_path() {
+        assert_eq!(
+            Expr::parse("count(inventory, \"Dark Candy\") > (stats[0].item_stats[1].attack)"),
+            Ok(binary(
+                Expr::Call(
+                    "count".into(),
+                    vec![path(&["inventory"]), Expr::Text("Dark Candy".into())]
+                ),
+                BinaryOp::Gt,
+                path(&["stats", "0", "item_stats", "1", "attack"]),
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Expr::parse("flags[1 == 1"), Err(QueryError::UnexpectedToken("==".into())));
+        assert_eq!(Expr::parse("lv >="), Err(QueryError::UnexpectedEnd));
+        assert_eq!(Expr::parse("lv = 1"), Err(QueryError::UnexpectedChar('=')));
+        assert_eq!(Expr::parse("count(lv"), Err(QueryError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn test_count() {
+        let list = ids(&[1, 0, 1, 3], IdKind::Item);
+
+        assert_eq!(call("count", vec![list.clone()]), Ok(Value::Number(3.0)));
+        assert_eq!(
+            call("count", vec![list.clone(), Value::Text("dark candy".into())]),
+            Ok(Value::Number(2.0))
+        );
+        assert_eq!(
+            call("count", vec![list, Value::Text("Nothing".into())]),
+            Err(QueryError::UnknownName {
+                kind: "item",
+                name: "Nothing".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_named_flag() {
+        let lines = vec!["0"; 3055];
+        let mut save = SaveData::read(2, &lines).unwrap();
+        save.flags[1099].set(2.0);
+
+        let query =
+            Expr::parse("flag.lancer_con_count == 2 and not flag.shadow_crystal_ch1").unwrap();
+        assert_eq!(query.matches(&save), Ok(true));
+    }
+
+    #[test]
+    fn test_profile() {
+        let lines = vec!["0"; 3055];
+        let mut save = SaveData::read(2, &lines).unwrap();
+        save.key_items[0] = KeyItemId(2);
+
+        let query =
+            Expr::parse("profile.route == normal and profile.secrets contains Egg").unwrap();
+        assert_eq!(query.matches(&save), Ok(true));
+    }
+
+    #[test]
+    fn test_unknown_identifiers() {
+        let save = SaveData::builder(2).build();
+
+        let query = Expr::parse("party contains Kris and count(inventory, Glowshard) == 0");
+        assert_eq!(query.unwrap().matches(&save), Ok(true));
+
+        // Names only stand in for values that have them
+        let cases = [
+            ("lvl >= 2", "lvl"),
+            ("lv == Noelle", "Noelle"),
+            ("partyy", "partyy"),
+            ("gold > 0", "gold"),
+            // Short-circuiting doesn't hide the right-hand side
+            ("lv < 0 and flag.no_such_flag", "flag.no_such_flag"),
+            ("lv >= 0 or lvl > 2", "lvl"),
+            ("time_played_frames > 0", "time_played_frames"),
+        ];
+        for (source, field) in cases {
+            assert_eq!(
+                Expr::parse(source).unwrap().evaluate(&save),
+                Err(QueryError::UnknownField(field.into()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_choices_and_time() {
+        let save = SaveData::builder(2).time_played(std::time::Duration::from_secs(3600)).build();
+
+        let matches = |source: &str| Expr::parse(source).unwrap().matches(&save);
+        assert_eq!(matches("time_played == 108000 and time_played_secs == 3600"), Ok(true));
+        assert_eq!(matches("profile.route == Normal"), Ok(true));
+        assert_eq!(matches("profile.route != snowgrave_complete"), Ok(true));
+        assert_eq!(
+            matches("profile.route == snowgrave"),
+            Err(QueryError::UnknownName { kind: "route", name: "snowgrave".into() })
+        );
+    }
+}
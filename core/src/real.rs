@@ -0,0 +1,150 @@
+//! GameMaker reals, the double-precision numbers saves store flags, tension and time in.
+
+use crate::serialize::Serializable;
+use compact_str::{CompactString, format_compact};
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::num::ParseFloatError;
+use std::str::FromStr;
+
+/// A real number from a save.
+///
+/// Keeps the text it was parsed from, so values that aren't changed are written back exactly
+/// as the game wrote them. Values set from code are formatted like GameMaker's `string()`.
+/// Comparisons only look at the value.
+#[derive(Debug, Clone, Default)]
+pub struct GmReal {
+    value: f64,
+    text: Option<CompactString>,
+}
+
+impl GmReal {
+    pub const ZERO: GmReal = GmReal::new(0.0);
+
+    pub const fn new(value: f64) -> Self {
+        GmReal { value, text: None }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Changes the value. The original text is dropped, unless the value stays the same.
+    pub fn set(&mut self, value: f64) {
+        if value != self.value {
+            *self = GmReal::new(value);
+        }
+    }
+
+    /// The text the value was parsed from, if it wasn't changed since.
+    pub fn original_text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+}
+
+/// Formats a real like GameMaker's `string()`: whole numbers without decimals, everything
+/// else rounded to two decimals.
+pub fn format_real(value: f64) -> CompactString {
+    if value.is_nan() {
+        "NaN".into()
+    } else if value.is_infinite() {
+        if value > 0.0 { "inf".into() } else { "-inf".into() }
+    } else if value.fract() == 0.0 {
+        // Avoids printing negative zero as "-0"
+        format_compact!("{:.0}", value + 0.0)
+    } else {
+        format_compact!("{:.2}", value)
+    }
+}
+
+impl FromStr for GmReal {
+    type Err = ParseFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Ok(GmReal {
+            value: s.parse()?,
+            text: Some(s.into()),
+        })
+    }
+}
+
+impl From<f64> for GmReal {
+    fn from(value: f64) -> Self {
+        GmReal::new(value)
+    }
+}
+
+impl From<i32> for GmReal {
+    fn from(value: i32) -> Self {
+        GmReal::new(value.into())
+    }
+}
+
+impl From<&GmReal> for f64 {
+    fn from(real: &GmReal) -> Self {
+        real.value
+    }
+}
+
+impl PartialEq for GmReal {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl PartialEq<f64> for GmReal {
+    fn eq(&self, other: &f64) -> bool {
+        self.value == *other
+    }
+}
+
+impl PartialOrd for GmReal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl Display for GmReal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.text {
+            Some(text) => f.write_str(text),
+            None => f.write_str(&format_real(self.value)),
+        }
+    }
+}
+
+impl Serializable for GmReal {
+    fn serialize(&self) -> CompactString {
+        format_compact!("{} ", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_real() {
+        assert_eq!(format_real(0.0), "0");
+        assert_eq!(format_real(-0.0), "0");
+        assert_eq!(format_real(16777217.0), "16777217");
+        assert_eq!(format_real(0.5), "0.50");
+        assert_eq!(format_real(-1.0 / 3.0), "-0.33");
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        // 16777217 frames is more than f32 can hold exactly
+        let mut real = "16777217.0 ".parse::<GmReal>().unwrap();
+        assert_eq!(real, 16777217.0);
+        assert_eq!(real.serialize(), "16777217.0 ");
+
+        real.set(16777217.0);
+        assert_eq!(real.serialize(), "16777217.0 ");
+
+        real.set(16777218.0);
+        assert_eq!(real.original_text(), None);
+        assert_eq!(real.serialize(), "16777218 ");
+    }
+}
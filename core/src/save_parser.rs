@@ -1,13 +1,20 @@
+use std::borrow::Cow;
 use std::fmt::Display;
-use compact_str::ToCompactString;
+use std::io::BufRead;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use compact_str::CompactString;
 use crate::iter::ResultArrayExt;
+use crate::real::GmReal;
 use crate::savefile::{ItemStats, LightworldStats, Stats};
 use thiserror::Error;
-use crate::save_parser::ParseErrorKind::{EofExpected, EofUnexpected, IntParse};
+use crate::save_parser::ParseErrorKind::{EofExpected, EofUnexpected, FloatParse, IntParse};
 
 #[derive(Debug, Error)]
 pub struct ParseError {
     pub kind: ParseErrorKind,
+    /// Number of the line the error is on, starting at 1. At the end of the file, it's the line
+    /// that was expected.
     pub line: Option<usize>,
 }
 
@@ -43,6 +50,10 @@ pub enum ParseErrorKind {
     EofUnexpected,
     #[error("expected end of file")]
     EofExpected,
+    #[error("invalid chapter number {0}")]
+    InvalidChapter(i32),
+    #[error("failed to read save: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 impl From<std::num::ParseIntError> for ParseErrorKind {
@@ -57,39 +68,153 @@ impl From<std::num::ParseFloatError> for ParseErrorKind {
     }
 }
 
-pub struct SaveParser<'a> {
-    chapter: i32,
-    save_lines: &'a [&'a str],
+/// Where a [`SaveParser`] gets its lines from.
+pub trait LineSource<'a> {
+    /// The next line, without its line ending. Only valid until the next call.
+    fn next_line(&mut self) -> Result<Option<&[u8]>, ParseErrorKind>;
+
+    /// The next line, kept for as long as the parsed save. Borrowed when the source can.
+    fn next_kept_line(&mut self) -> Result<Option<Cow<'a, [u8]>>, ParseErrorKind>;
+}
+
+/// Lines that were already split, like by [`str::lines`].
+pub struct StrLines<'a>(pub std::slice::Iter<'a, &'a str>);
+
+impl<'a> LineSource<'a> for StrLines<'a> {
+    fn next_line(&mut self) -> Result<Option<&[u8]>, ParseErrorKind> {
+        Ok(self.0.next().map(|line| line.as_bytes()))
+    }
+
+    fn next_kept_line(&mut self) -> Result<Option<Cow<'a, [u8]>>, ParseErrorKind> {
+        Ok(self.0.next().map(|line| Cow::Borrowed(line.as_bytes())))
+    }
+}
+
+/// Splits the contents of a save into lines as it goes, without copying them.
+pub struct ByteLines<'a>(pub &'a [u8]);
+
+impl<'a> ByteLines<'a> {
+    fn split_line(&mut self) -> Option<&'a [u8]> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let (line, rest) = match self.0.iter().position(|&b| b == b'\n') {
+            Some(end) => (&self.0[..end], &self.0[end + 1..]),
+            None => (self.0, &[][..]),
+        };
+        self.0 = rest;
+        Some(line.strip_suffix(b"\r").unwrap_or(line))
+    }
+}
+
+impl<'a> LineSource<'a> for ByteLines<'a> {
+    fn next_line(&mut self) -> Result<Option<&[u8]>, ParseErrorKind> {
+        Ok(self.split_line())
+    }
+
+    fn next_kept_line(&mut self) -> Result<Option<Cow<'a, [u8]>>, ParseErrorKind> {
+        Ok(self.split_line().map(Cow::Borrowed))
+    }
+}
+
+/// Reads lines into one reused buffer, so only the lines that are kept get allocated.
+pub struct ReaderLines<R> {
+    reader: R,
+    buffer: Vec<u8>,
+}
+
+impl<R: BufRead> ReaderLines<R> {
+    pub fn new(reader: R) -> Self {
+        ReaderLines {
+            reader,
+            buffer: Vec::with_capacity(64),
+        }
+    }
+}
+
+impl<R: BufRead> LineSource<'static> for ReaderLines<R> {
+    fn next_line(&mut self) -> Result<Option<&[u8]>, ParseErrorKind> {
+        self.buffer.clear();
+        if self.reader.read_until(b'\n', &mut self.buffer)? == 0 {
+            return Ok(None);
+        }
+        let line = self.buffer.strip_suffix(b"\n").unwrap_or(&self.buffer);
+        Ok(Some(line.strip_suffix(b"\r").unwrap_or(line)))
+    }
+
+    fn next_kept_line(&mut self) -> Result<Option<Cow<'static, [u8]>>, ParseErrorKind> {
+        Ok(self.next_line()?.map(|line| Cow::Owned(line.to_vec())))
+    }
+}
+
+/// Text of a line. Saves are UTF-8, but invalid sequences in names are replaced with U+FFFD
+/// rather than failing the whole save.
+fn line_text<'b>(line: Cow<'b, [u8]>) -> Cow<'b, str> {
+    match line {
+        Cow::Borrowed(bytes) => String::from_utf8_lossy(bytes),
+        Cow::Owned(bytes) => match String::from_utf8(bytes) {
+            Ok(text) => Cow::Owned(text),
+            Err(err) => Cow::Owned(String::from_utf8_lossy(err.as_bytes()).into_owned()),
+        },
+    }
+}
+
+pub struct SaveParser<'a, S: LineSource<'a>> {
+    lines: S,
+    /// Number of the last line read, starting at 1.
     current_line: usize,
+    _lifetime: PhantomData<&'a ()>,
 }
 
-impl<'a> SaveParser<'a> {
-    pub fn new(chapter: i32, save_lines: &'a [&'a str]) -> SaveParser<'a> {
+impl<'a, S: LineSource<'a>> SaveParser<'a, S> {
+    pub fn new(lines: S) -> SaveParser<'a, S> {
         SaveParser {
-            chapter,
-            save_lines,
+            lines,
             current_line: 0,
+            _lifetime: PhantomData,
         }
     }
 
-    pub fn parse_string(&mut self) -> Result<&'a str, ParseError> {
-        if self.current_line >= self.save_lines.len() {
-            return Err(ParseError::new(EofUnexpected, Some(self.current_line)));
+    fn next_line(&mut self) -> Result<&[u8], ParseError> {
+        self.current_line += 1;
+        let line = self.current_line;
+        match self.lines.next_line() {
+            Ok(Some(bytes)) => Ok(bytes),
+            Ok(None) => Err(ParseError::new(EofUnexpected, Some(line))),
+            Err(kind) => Err(ParseError::new(kind, Some(line))),
         }
+    }
 
-        let line = self.save_lines[self.current_line];
+    /// A line that is kept in the save, like a name.
+    pub fn parse_string(&mut self) -> Result<Cow<'a, str>, ParseError> {
         self.current_line += 1;
-        Ok(line)
+        let line = self.current_line;
+        match self.lines.next_kept_line() {
+            Ok(Some(bytes)) => Ok(line_text(bytes)),
+            Ok(None) => Err(ParseError::new(EofUnexpected, Some(line))),
+            Err(kind) => Err(ParseError::new(kind, Some(line))),
+        }
+    }
+
+    /// A line that is only looked at while parsing, like a number.
+    fn parse_str<T: FromStr>(&mut self, kind: ParseErrorKind) -> Result<T, ParseError> {
+        let bytes = self.next_line()?;
+        std::str::from_utf8(bytes)
+            .ok()
+            .and_then(|text| text.trim().parse::<T>().ok())
+            .ok_or_else(|| ParseError::new(kind, Some(self.current_line)))
     }
 
     pub fn parse_int(&mut self) -> Result<i32, ParseError> {
-        self.parse_string()?.trim().parse::<i32>()
-                .map_err(|e| ParseError::new(e.into(), Some(self.current_line)))
+        self.parse_str(IntParse)
+    }
+
+    pub fn parse_real(&mut self) -> Result<GmReal, ParseError> {
+        self.parse_str(FloatParse)
     }
 
-    pub fn parse_float(&mut self) -> Result<f32, ParseError> {
-        self.parse_string()?.trim().parse::<f32>()
-                .map_err(|e| ParseError::new(e.into(), Some(self.current_line)))
+    pub fn parse_id<T: From<i32>>(&mut self) -> Result<T, ParseError> {
+        self.parse_int().map(T::from)
     }
 
     pub fn parse_bool(&mut self) -> Result<bool, ParseError> {
@@ -100,7 +225,8 @@ impl<'a> SaveParser<'a> {
         }
     }
 
-    pub fn parse_stats(&mut self) -> Result<Stats, ParseError> {
+    /// Parses a stat block. `item_elements` is whether the save's layout stores elements.
+    pub fn parse_stats(&mut self, item_elements: bool) -> Result<Stats, ParseError> {
         Ok(Stats {
             hp: self.parse_int()?,
             max_hp: self.parse_int()?,
@@ -109,10 +235,10 @@ impl<'a> SaveParser<'a> {
             magic: self.parse_int()?,
             guts: self.parse_int()?,
 
-            weapon: self.parse_int()?,
-            armor1: self.parse_int()?,
-            armor2: self.parse_int()?,
-            weapon_style: self.parse_string()?.to_compact_string(),
+            weapon: self.parse_id()?,
+            armor1: self.parse_id()?,
+            armor2: self.parse_id()?,
+            weapon_style: CompactString::from(self.parse_string()?),
 
             item_stats: [(); 4]
                 .map(|_| {
@@ -126,27 +252,27 @@ impl<'a> SaveParser<'a> {
                         bolts_speed: self.parse_int()?,
                         item_special: self.parse_int()?,
 
-                        item_element: if self.chapter >= 2 {
+                        item_element: if item_elements {
                             self.parse_int()?
                         } else {
                             0
                         },
-                        item_element_amount: if self.chapter >= 2 {
-                            self.parse_float()?
+                        item_element_amount: if item_elements {
+                            self.parse_real()?
                         } else {
-                            0.0
+                            GmReal::ZERO
                         },
                     })
                 })
                 .flatten_ok()?,
-            spells: [(); 12].map(|_| self.parse_int()).flatten_ok()?,
+            spells: [(); 12].map(|_| self.parse_id()).flatten_ok()?,
         })
     }
 
     pub fn parse_lightworld_stats(&mut self) -> Result<LightworldStats, ParseError> {
         Ok(LightworldStats {
-            weapon: self.parse_int()?,
-            armor: self.parse_int()?,
+            weapon: self.parse_id()?,
+            armor: self.parse_id()?,
             xp: self.parse_int()?,
             lv: self.parse_int()?,
             gold: self.parse_int()?,
@@ -154,24 +280,23 @@ impl<'a> SaveParser<'a> {
             max_hp: self.parse_int()?,
             attack: self.parse_int()?,
             defense: self.parse_int()?,
-            wstrength: self.parse_int()?,
-            adef: self.parse_int()?,
+            weapon_attack: self.parse_int()?,
+            armor_defense: self.parse_int()?,
         })
     }
 
-    pub fn expect_eof(&self) -> Result<(), ParseError> {
-        if self.current_line < self.save_lines.len() {
+    pub fn expect_eof(&mut self) -> Result<(), ParseError> {
+        let line = self.current_line + 1;
+        match self.lines.next_line() {
+            Ok(None) => Ok(()),
             // Allow the last line to be empty
             // This shouldn't happen with unedited saves [?]
-            if self.current_line + 1 == self.save_lines.len()
-                && self.save_lines[self.current_line].is_empty()
-            {
-                return Ok(());
-            }
-
-            Err(ParseError::new(EofExpected, Some(self.current_line)))
-        } else {
-            Ok(())
+            Ok(Some(b"")) => match self.lines.next_line() {
+                Ok(None) => Ok(()),
+                _ => Err(ParseError::new(EofExpected, Some(line))),
+            },
+            Ok(Some(_)) => Err(ParseError::new(EofExpected, Some(line))),
+            Err(kind) => Err(ParseError::new(kind, Some(line))),
         }
     }
 }
@@ -1,19 +1,53 @@
-use crate::gamedata::items::display_item;
-use crate::gamedata::key_items::display_key_item;
-use crate::gamedata::rooms::display_room;
-use crate::iter::{ResultArrayExt, ResultVecExt};
-use crate::save_parser::{ParseError, SaveParser};
-use compact_str::CompactString;
+use crate::builder::SaveBuilder;
+use crate::gamedata::GameContext;
+use crate::gamedata::armors::ArmorId;
+use crate::gamedata::flags::{Flag, FlagValue, SHADOW_CRYSTALS, known_flags};
+use crate::gamedata::items::ItemId;
+use crate::gamedata::key_items::KeyItemId;
+use crate::gamedata::lightworld_items::LightworldItemId;
+use crate::gamedata::party_members::PartyMember;
+use crate::gamedata::phone_numbers::PhoneNumberId;
+use crate::gamedata::recruits::{RecruitInfo, RecruitStatus, recruits_in};
+use crate::gamedata::rooms::RoomIndex;
+use crate::gamedata::spells::SpellId;
+use crate::gamedata::weapons::WeaponId;
+use crate::layout::{SaveField, SaveLayout};
+use crate::profile::{self, Profile};
+use crate::real::GmReal;
+use crate::validate::{self, Issue};
+use crate::visit::Visit;
+use crate::stats::effective_stats;
+use crate::save_parser::{
+    ByteLines, LineSource, ParseError, ParseErrorKind, ReaderLines, SaveParser, StrLines,
+};
+use crate::serialize::Serializable;
+use compact_str::{CompactString, ToCompactString, format_compact};
 use indoc::indoc;
 use log::warn;
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::io::BufRead;
 use std::time::Duration;
 
-#[derive(Debug)]
+/// Number of flags the game uses. Chapter 1 stores more, but they're always zero.
+pub const FLAG_COUNT: usize = 2500;
+
+pub(crate) fn display_time_played(time_played_secs: u64) -> CompactString {
+    let time_played_h = time_played_secs / 3600;
+    let time_played_m = (time_played_secs % 3600) / 60;
+    let time_played_s = time_played_secs % 60;
+    format_compact!(
+        "{}h{:02}m{:02}s",
+        time_played_h, time_played_m, time_played_s
+    )
+}
+
+#[derive(Debug, Clone, Visit)]
 pub struct SaveData<'a> {
     pub chapter: i32,
-    pub true_name: &'a str,
-    pub vessel_names: [&'a str; 6],
-    pub party: [i32; 3],
+    pub true_name: Cow<'a, str>,
+    pub vessel_names: [Cow<'a, str>; 6],
+    pub party: [PartyMember; 3],
     pub dark_dollars: i32,
     pub xp: i32,
     pub level: i32,
@@ -24,30 +58,93 @@ pub struct SaveData<'a> {
     pub bolt_speed: i32,
     pub graze_amount: i32,
     pub graze_size: i32,
-    pub inventory: [i32; 13],
-    pub key_items: [i32; 13],
-    pub weapons: Vec<i32>,
-    pub armors: Vec<i32>,
-    pub storage: Option<Vec<i32>>,
-    pub tension: f32,
-    pub max_tension: f32,
+    pub inventory: [ItemId; 13],
+    pub key_items: [KeyItemId; 13],
+    pub weapons: Vec<WeaponId>,
+    pub armors: Vec<ArmorId>,
+    pub storage: Option<Vec<ItemId>>,
+    pub tension: GmReal,
+    pub max_tension: GmReal,
     pub lightworld_stats: LightworldStats,
-    pub lightworld_items: [i32; 8],
-    pub lightworld_phone: [i32; 8],
-    pub flags: [f32; 2500],
-    pub plot_value: f32,
-    pub room_id: i32,
-    pub time_played: Duration,
+    pub lightworld_items: [LightworldItemId; 8],
+    pub lightworld_phone: [PhoneNumberId; 8],
+    /// [`FLAG_COUNT`] flags, boxed so that saves stay small to move around.
+    pub flags: Box<[GmReal]>,
+    pub plot_value: GmReal,
+    pub room_id: RoomIndex,
+    /// Time played in frames, 30 per second, as the game stores it. See
+    /// [`time_played_duration`](Self::time_played_duration).
+    pub time_played: GmReal,
 }
 
 impl SaveData<'_> {
-    /// Parses the Deltarune save data.
+    /// Starts building a save of the given chapter, see [`SaveBuilder`].
+    pub fn builder<'a>(chapter: i32) -> SaveBuilder<'a> {
+        SaveBuilder::new(chapter)
+    }
+
+    /// A save of the given chapter with every value zero or empty, sized for its layout.
+    pub(crate) fn empty<'a>(chapter: i32) -> SaveData<'a> {
+        let layout = SaveLayout::for_chapter(chapter);
+        let storage_slots = layout.count(SaveField::Storage);
+
+        SaveData {
+            chapter,
+            true_name: Cow::Borrowed(""),
+            vessel_names: Default::default(),
+            party: Default::default(),
+            dark_dollars: 0,
+            xp: 0,
+            level: 0,
+            inv: 0,
+            invc: 0,
+            is_darkworld: false,
+            stats: vec![Stats::default(); layout.count(SaveField::Stats)],
+            bolt_speed: 0,
+            graze_amount: 0,
+            graze_size: 0,
+            inventory: Default::default(),
+            key_items: Default::default(),
+            weapons: vec![WeaponId::default(); layout.count(SaveField::Weapons)],
+            armors: vec![ArmorId::default(); layout.count(SaveField::Armors)],
+            storage: (storage_slots > 0).then(|| vec![ItemId::default(); storage_slots]),
+            tension: GmReal::ZERO,
+            max_tension: GmReal::ZERO,
+            lightworld_stats: LightworldStats::default(),
+            lightworld_items: Default::default(),
+            lightworld_phone: Default::default(),
+            flags: vec![GmReal::ZERO; FLAG_COUNT].into_boxed_slice(),
+            plot_value: GmReal::ZERO,
+            room_id: RoomIndex::default(),
+            time_played: GmReal::ZERO,
+        }
+    }
+
+    /// Parses the Deltarune save data, following the [layout](SaveLayout) of its chapter.
     ///
     /// Made based on `gml_GlobalScript_scr_saveprocess` (see `research/saveprocess` directory for
     /// decompiled code).
     pub fn read<'a>(chapter: i32, save_lines: &'a [&'a str]) -> Result<SaveData<'a>, ParseError> {
+        SaveData::parse(chapter, StrLines(save_lines.iter()))
+    }
+
+    /// Parses a save from its contents, splitting the lines as it goes. Lines can end with LF or
+    /// CRLF, and names that aren't valid UTF-8 are read lossily. The names borrow from `bytes`.
+    pub fn from_bytes(chapter: i32, bytes: &[u8]) -> Result<SaveData<'_>, ParseError> {
+        SaveData::parse(chapter, ByteLines(bytes))
+    }
+
+    /// Parses a save line by line from a reader, like [`from_bytes`](SaveData::from_bytes).
+    pub fn from_reader(
+        chapter: i32,
+        reader: impl BufRead,
+    ) -> Result<SaveData<'static>, ParseError> {
+        SaveData::parse(chapter, ReaderLines::new(reader))
+    }
+
+    fn parse<'a>(chapter: i32, lines: impl LineSource<'a>) -> Result<SaveData<'a>, ParseError> {
         if chapter < 1 {
-            panic!("Invalid chapter number");
+            return Err(ParseErrorKind::InvalidChapter(chapter).into());
         }
 
         if chapter > 4 {
@@ -58,153 +155,186 @@ impl SaveData<'_> {
             );
         }
 
-        let is_chapter_1 = chapter == 1;
-
-        let mut parser = SaveParser::new(chapter, save_lines);
-
-        let true_name = parser.parse_string()?;
-
-        let vessel_names = [(); 6].map(|_| parser.parse_string()).flatten_ok()?;
-
-        let party = [(); 3].map(|_| parser.parse_int()).flatten_ok()?;
-
-        let dark_dollars = parser.parse_int()?;
-
-        let xp = parser.parse_int()?;
-
-        let level = parser.parse_int()?;
-
-        // Something with invincibility frames
-        let inv = parser.parse_int()?;
-        let invc = parser.parse_int()?;
-
-        let is_darkworld = parser.parse_bool()?;
-
-        let stat_blocks = match chapter {
-            1 => 4,
-            _ => 5,
-        };
-
-        let stats = (0..stat_blocks)
-            .map(|_i| parser.parse_stats())
-            .collect::<Vec<_>>()
-            .flatten_ok()?;
+        let layout = SaveLayout::for_chapter(chapter);
+        let mut parser = SaveParser::new(lines);
+        let mut save = SaveData::empty(chapter);
+
+        layout.for_each_field(|field, i| {
+            match field {
+                SaveField::TrueName => save.true_name = parser.parse_string()?,
+                SaveField::VesselNames => save.vessel_names[i] = parser.parse_string()?,
+                SaveField::Party => save.party[i] = parser.parse_id()?,
+                SaveField::DarkDollars => save.dark_dollars = parser.parse_int()?,
+                SaveField::Xp => save.xp = parser.parse_int()?,
+                SaveField::Level => save.level = parser.parse_int()?,
+                // Something with invincibility frames
+                SaveField::Inv => save.inv = parser.parse_int()?,
+                SaveField::Invc => save.invc = parser.parse_int()?,
+                SaveField::IsDarkworld => save.is_darkworld = parser.parse_bool()?,
+                SaveField::Stats => save.stats[i] = parser.parse_stats(layout.item_elements)?,
+                SaveField::BoltSpeed => save.bolt_speed = parser.parse_int()?, // ?
+                SaveField::GrazeAmount => save.graze_amount = parser.parse_int()?,
+                SaveField::GrazeSize => save.graze_size = parser.parse_int()?,
+                SaveField::Inventory => save.inventory[i] = parser.parse_id()?,
+                SaveField::KeyItems => save.key_items[i] = parser.parse_id()?,
+                SaveField::Weapons => save.weapons[i] = parser.parse_id()?,
+                SaveField::Armors => save.armors[i] = parser.parse_id()?,
+                SaveField::Storage => {
+                    save.storage.as_mut().expect("layout has storage")[i] = parser.parse_id()?
+                }
+                SaveField::Tension => save.tension = parser.parse_real()?,
+                SaveField::MaxTension => save.max_tension = parser.parse_real()?,
+                SaveField::LightworldStats => {
+                    save.lightworld_stats = parser.parse_lightworld_stats()?
+                }
+                SaveField::LightworldItems => save.lightworld_items[i] = parser.parse_id()?,
+                SaveField::LightworldPhone => save.lightworld_phone[i] = parser.parse_id()?,
+                SaveField::Flags => save.flags[i] = parser.parse_real()?,
+                // Chapter 1 stores 9999 flags, the ones past 2500 should all be zero
+                SaveField::UnusedFlags => _ = parser.parse_int()?,
+                SaveField::PlotValue => save.plot_value = parser.parse_real()?,
+                SaveField::RoomId => save.room_id = parser.parse_id()?,
+                SaveField::TimePlayed => save.time_played = parser.parse_real()?,
+            }
+            Ok::<_, ParseError>(())
+        })?;
 
-        let bolt_speed = parser.parse_int()?; // ?
-        let graze_amount = parser.parse_int()?;
-        let graze_size = parser.parse_int()?;
+        parser.expect_eof()?;
 
-        let mut inventory = [0; 13];
-        let mut key_items = [0; 13];
+        Ok(save)
+    }
 
-        let mut weapons = Vec::with_capacity(if is_chapter_1 { 13 } else { 48 });
-        let mut armors = weapons.clone();
+    /// Serializes the save back into lines, in the same format the game writes them.
+    pub fn to_lines(&self) -> Vec<CompactString> {
+        let layout = SaveLayout::for_chapter(self.chapter);
+        let mut lines = Vec::with_capacity(layout.line_count());
+
+        let _ = layout.for_each_field(|field, i| {
+            match field {
+                SaveField::TrueName => lines.push(self.true_name.serialize()),
+                SaveField::VesselNames => lines.push(self.vessel_names[i].serialize()),
+                SaveField::Party => lines.push(self.party[i].serialize()),
+                SaveField::DarkDollars => lines.push(self.dark_dollars.serialize()),
+                SaveField::Xp => lines.push(self.xp.serialize()),
+                SaveField::Level => lines.push(self.level.serialize()),
+                SaveField::Inv => lines.push(self.inv.serialize()),
+                SaveField::Invc => lines.push(self.invc.serialize()),
+                SaveField::IsDarkworld => lines.push((self.is_darkworld as i32).serialize()),
+                SaveField::Stats => self.stats[i].write_lines(&mut lines, layout.item_elements),
+                SaveField::BoltSpeed => lines.push(self.bolt_speed.serialize()),
+                SaveField::GrazeAmount => lines.push(self.graze_amount.serialize()),
+                SaveField::GrazeSize => lines.push(self.graze_size.serialize()),
+                SaveField::Inventory => lines.push(self.inventory[i].serialize()),
+                SaveField::KeyItems => lines.push(self.key_items[i].serialize()),
+                SaveField::Weapons => lines.push(self.weapons[i].serialize()),
+                SaveField::Armors => lines.push(self.armors[i].serialize()),
+                SaveField::Storage => {
+                    lines.push(self.storage.as_ref().expect("layout has storage")[i].serialize())
+                }
+                SaveField::Tension => lines.push(self.tension.serialize()),
+                SaveField::MaxTension => lines.push(self.max_tension.serialize()),
+                SaveField::LightworldStats => self.lightworld_stats.write_lines(&mut lines),
+                SaveField::LightworldItems => lines.push(self.lightworld_items[i].serialize()),
+                SaveField::LightworldPhone => lines.push(self.lightworld_phone[i].serialize()),
+                SaveField::Flags => lines.push(self.flags[i].serialize()),
+                SaveField::UnusedFlags => lines.push(0.serialize()),
+                SaveField::PlotValue => lines.push(self.plot_value.serialize()),
+                SaveField::RoomId => lines.push(self.room_id.serialize()),
+                SaveField::TimePlayed => lines.push(self.time_played.serialize()),
+            }
+            Ok::<_, ()>(())
+        });
 
-        let mut storage = if is_chapter_1 {
-            None
-        } else {
-            Some(Vec::with_capacity(72))
-        };
+        lines
+    }
 
-        for i in 0..13 {
-            inventory[i] = parser.parse_int()?;
-            key_items[i] = parser.parse_int()?;
+    /// Serializes the save into the contents of a save file.
+    pub fn write(&self) -> String {
+        self.to_lines().join("\r\n")
+    }
 
-            if is_chapter_1 {
-                weapons.push(parser.parse_int()?);
-                armors.push(parser.parse_int()?);
-            }
-        }
+    pub fn display_room(&self) -> CompactString {
+        self.room_id.to_compact_string()
+    }
 
-        if !is_chapter_1 {
-            for _ in 0..48 {
-                weapons.push(parser.parse_int()?);
-                armors.push(parser.parse_int()?);
-            }
+    /// The chapter and flags of this save, for chapter-aware gamedata lookups.
+    pub fn context(&self) -> GameContext<'_> {
+        GameContext::new(self.chapter).with_flags(&self.flags)
+    }
 
-            for _ in 0..72 {
-                storage.as_mut().unwrap().push(parser.parse_int()?);
-            }
-        }
+    /// Checks the save for inconsistencies, see [`validate`](crate::validate::validate).
+    pub fn validate(&self) -> Vec<Issue> {
+        validate::validate(self)
+    }
 
-        let tension = parser.parse_float()?;
-        let max_tension = parser.parse_float()?;
+    /// Infers the route and notable choices, see [`profile`](crate::profile::profile).
+    pub fn profile(&self) -> Profile {
+        profile::profile(self)
+    }
 
-        let lightworld_stats = parser.parse_lightworld_stats()?;
+    /// The recruit status of every recruitable enemy of this save's chapter.
+    pub fn recruits(&self) -> Vec<(&'static RecruitInfo, RecruitStatus)> {
+        recruits_in(self.chapter)
+            .map(|info| (info, info.status(&self.flags)))
+            .collect()
+    }
 
-        let mut lightworld_items = [0; 8];
-        let mut lightworld_phone = [0; 8];
+    /// Reads a known flag.
+    pub fn flag(&self, flag: Flag) -> FlagValue {
+        flag.read(&self.flags)
+    }
 
-        for i in 0..8 {
-            lightworld_items[i] = parser.parse_int()?;
-            lightworld_phone[i] = parser.parse_int()?;
-        }
+    /// Counts the ShadowCrystals collected up to this save's chapter, like
+    /// `scr_get_total_shadow_crystal_amount`.
+    pub fn shadow_crystals(&self) -> usize {
+        SHADOW_CRYSTALS
+            .iter()
+            .filter(|flag| flag.info().chapter <= self.chapter && self.flag(**flag).is_set())
+            .count()
+    }
 
-        let flags = [(); 2500].map(|_| parser.parse_float()).flatten_ok()?;
+    /// Returns the room name shown in the game's save menu, if known.
+    pub fn room_name(&self) -> Option<&'static str> {
+        self.room_id.name_in(&self.context()).ok()
+    }
 
-        if is_chapter_1 {
-            // Chapter 1 stores 9999 flags, we need to skip the rest of them
-            // They should all be zero
-            for _ in 2500..9999 {
-                _ = parser.parse_int()?;
-            }
-        }
+    /// Time played in whole frames, as the game counts it.
+    pub fn time_played_frames(&self) -> i64 {
+        self.time_played.value().round() as i64
+    }
 
-        let plot_value = parser.parse_float()?;
-        let room_id = parser.parse_int()?;
-        let time_played_frames = parser.parse_float()?;
-        let time_played = Duration::from_secs_f64(time_played_frames as f64 / 30.0);
+    /// Time played, with negative frame counts read as zero.
+    pub fn time_played_duration(&self) -> Duration {
+        Duration::from_secs_f64(self.time_played.value().max(0.0) / 30.0)
+    }
 
-        parser.expect_eof()?;
+    /// Sets the time played, rounded to whole frames.
+    pub fn set_time_played(&mut self, time_played: Duration) {
+        self.time_played.set((time_played.as_secs_f64() * 30.0).round());
+    }
 
-        Ok(SaveData {
-            chapter,
-            true_name,
-            vessel_names,
-            party,
-            dark_dollars,
-            xp,
-            level,
-            inv,
-            invc,
-            is_darkworld,
-            stats,
-            bolt_speed,
-            graze_amount,
-            graze_size,
-            inventory,
-            key_items,
-            weapons,
-            armors,
-            storage,
-            tension,
-            max_tension,
-            lightworld_stats,
-            lightworld_items,
-            lightworld_phone,
-            flags,
-            plot_value,
-            room_id,
-            time_played,
-        })
+    pub fn display_time_played(&self) -> CompactString {
+        display_time_played(self.time_played_duration().as_secs())
     }
 
-    pub fn display_room(&self) -> CompactString {
-        display_room(self.room_id)
+    pub fn display_party(&self) -> CompactString {
+        let members = self
+            .party
+            .iter()
+            .filter(|member| !member.is_empty())
+            .map(|member| member.to_string())
+            .collect::<Vec<_>>();
+        members.join(", ").into()
     }
 
-    pub fn display_inventory(
-        inventory: Option<&[i32]>,
-        name: &str,
-        display: impl Fn(i32) -> CompactString,
-    ) -> String {
+    pub fn display_inventory<T: Display>(inventory: Option<&[T]>, name: &str) -> String {
         if inventory.is_none() {
             return "".to_string();
         }
         let items = inventory
             .unwrap()
             .iter()
-            .map(|i| format!("{: <13}", display(*i)))
+            .map(|i| format!("{: <13}", i.to_string()))
             .collect::<Vec<_>>();
         let chunks = items.chunks(4);
         let inventory = chunks
@@ -214,16 +344,122 @@ impl SaveData<'_> {
         format!("---------------\n{}:\n{}\n", name, inventory)
     }
 
-    pub fn display_info(&self) -> String {
-        let time_played_secs = self.time_played.as_secs();
-        let time_played_h = time_played_secs / 3600;
-        let time_played_m = (time_played_secs % 3600) / 60;
-        let time_played_s = time_played_secs % 60;
-        let time_played = format!(
-            "{}h{:02}m{:02}s",
-            time_played_h, time_played_m, time_played_s
-        );
+    pub fn summary(&self) -> SaveSummary {
+        SaveSummary {
+            chapter: self.chapter,
+            name: self.vessel_names[0].as_ref().into(),
+            room: self.room_name().unwrap_or("?").into(),
+            room_id: self.room_id.into(),
+            level: self.level,
+            dark_dollars: self.dark_dollars,
+            plot_value: self.plot_value.value(),
+            time_played_secs: self.time_played_duration().as_secs(),
+            party: self
+                .party
+                .iter()
+                .filter(|member| !member.is_empty())
+                .map(|member| member.to_compact_string())
+                .collect(),
+            profile: self.profile(),
+        }
+    }
+
+    /// Renders a character sheet for every party member's stat block: HP, stats with equipment
+    /// bonuses, gear and spells. Current party members are marked.
+    pub fn display_characters(&self) -> String {
+        let mut output = String::new();
+
+        for effective in effective_stats(self) {
+            let member = effective.member;
+            let stats = &self.stats[member.0 as usize];
+            let spells = stats
+                .spells
+                .iter()
+                .filter(|spell| !spell.is_empty())
+                .map(|spell| spell.to_string())
+                .collect::<Vec<_>>();
+
+            output += &format!(
+                indoc! {"
+                    ---------------
+                    {}{}
+                    HP {}/{}  AT {} ({:+})  DF {} ({:+})  MG {} ({:+})  Guts {}
+                    Weapon: {}
+                    Armor: {}, {}
+                    Spells: {}
+                "},
+                member,
+                if self.party.contains(&member) { " (in party)" } else { "" },
+                effective.hp,
+                effective.max_hp,
+                effective.attack,
+                effective.attack - stats.attack,
+                effective.defense,
+                effective.defense - stats.defense,
+                effective.magic,
+                effective.magic - stats.magic,
+                effective.guts,
+                stats.weapon,
+                stats.armor1,
+                stats.armor2,
+                if spells.is_empty() { "---".to_string() } else { spells.join(", ") },
+            );
+        }
+
+        output
+    }
+
+    /// Renders Kris's light world stats, inventory and phone contacts.
+    pub fn display_lightworld(&self) -> String {
+        let lw = &self.lightworld_stats;
+        let contacts = self
+            .lightworld_phone
+            .iter()
+            .filter(|contact| !contact.is_empty())
+            .map(|contact| contact.to_string())
+            .collect::<Vec<_>>();
 
+        format!(
+            indoc! {"
+                ---------------
+                Light World:
+                LV{} HP {}/{}  AT {} ({:+})  DF {} ({:+})  ${}  EXP {}
+                Weapon: {}
+                Armor: {}
+                Phone: {}
+                {}"},
+            lw.lv,
+            lw.hp,
+            lw.max_hp,
+            lw.attack + lw.weapon_attack,
+            lw.weapon_attack,
+            lw.defense + lw.armor_defense,
+            lw.armor_defense,
+            lw.gold,
+            lw.xp,
+            lw.weapon,
+            lw.armor,
+            if contacts.is_empty() { "---".to_string() } else { contacts.join(", ") },
+            Self::display_inventory(Some(&self.lightworld_items[..]), "Light World Items"),
+        )
+    }
+
+    /// Lists the known flags that are set, by name.
+    pub fn display_flags(&self) -> String {
+        let flags = known_flags()
+            .iter()
+            .map(|info| (info, self.flag(info.flag)))
+            .filter(|(_, value)| value.is_set())
+            .map(|(info, value)| format!("{} = {} ({})\n", info.name, value, info.description))
+            .collect::<String>();
+
+        if flags.is_empty() {
+            return "".to_string();
+        }
+        format!("---------------\nFlags:\n{}", flags)
+    }
+
+    pub fn display_info(&self) -> String {
         format!(
             indoc! {"
                 Save for chapter {}
@@ -232,7 +468,9 @@ impl SaveData<'_> {
                 Plot value {}
                 {}{}
                 Played for {}
-                {}{}{}
+                Party: {}
+                TP {}/{}
+                {}{}{}{}{}{}{}
             "},
             self.chapter,
             self.true_name,
@@ -246,18 +484,45 @@ impl SaveData<'_> {
             } else {
                 " (Light World)"
             },
-            time_played,
-            Self::display_inventory(Some(&self.inventory[..]), "Items", display_item),
-            Self::display_inventory(Some(&self.key_items[..]), "Keys", display_key_item),
+            self.display_time_played(),
+            self.display_party(),
+            self.tension,
+            self.max_tension,
+            Self::display_inventory(Some(&self.inventory[..]), "Items"),
+            Self::display_inventory(Some(&self.key_items[..]), "Keys"),
             if self.storage.is_some() {
-                Self::display_inventory(Some(self.storage.as_deref().unwrap()), "Storage",
-                                        display_item)
-            } else { "".to_string() }
+                Self::display_inventory(self.storage.as_deref(), "Storage")
+            } else { "".to_string() },
+            if self.is_darkworld { "".to_string() } else { self.display_lightworld() },
+            self.display_flags(),
+            self.profile().display(),
+            self.display_characters(),
         )
     }
 }
 
-#[derive(Debug)]
+/// The most important values of a save, for overviews of many saves at once.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SaveSummary {
+    pub chapter: i32,
+    pub name: CompactString,
+    pub room: CompactString,
+    pub room_id: i32,
+    pub level: i32,
+    pub dark_dollars: i32,
+    pub plot_value: f64,
+    pub time_played_secs: u64,
+    pub party: Vec<CompactString>,
+    pub profile: Profile,
+}
+
+impl SaveSummary {
+    pub fn display_time_played(&self) -> CompactString {
+        display_time_played(self.time_played_secs)
+    }
+}
+
+#[derive(Debug, Clone, Default, Visit)]
 pub struct ItemStats {
     pub attack: i32,
     pub defense: i32,
@@ -271,10 +536,10 @@ pub struct ItemStats {
     // Chapter 2 and up
     // For chapter 1, both set to 0
     pub item_element: i32,
-    pub item_element_amount: f32,
+    pub item_element_amount: GmReal,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default, Visit)]
 pub struct Stats {
     pub hp: i32,
     pub max_hp: i32,
@@ -283,19 +548,56 @@ pub struct Stats {
     pub magic: i32,
     pub guts: i32,
 
-    pub weapon: i32,
-    pub armor1: i32,
-    pub armor2: i32,
+    pub weapon: WeaponId,
+    pub armor1: ArmorId,
+    pub armor2: ArmorId,
     pub weapon_style: CompactString,
 
     pub item_stats: [ItemStats; 4],
-    pub spells: [i32; 12],
+    pub spells: [SpellId; 12],
 }
 
-#[derive(Debug)]
+impl ItemStats {
+    fn write_lines(&self, lines: &mut Vec<CompactString>, item_elements: bool) {
+        lines.push(self.attack.serialize());
+        lines.push(self.defense.serialize());
+        lines.push(self.magic.serialize());
+        lines.push(self.bolts.serialize());
+        lines.push(self.graze_amount.serialize());
+        lines.push(self.graze_size.serialize());
+        lines.push(self.bolts_speed.serialize());
+        lines.push(self.item_special.serialize());
+        if item_elements {
+            lines.push(self.item_element.serialize());
+            lines.push(self.item_element_amount.serialize());
+        }
+    }
+}
+
+impl Stats {
+    fn write_lines(&self, lines: &mut Vec<CompactString>, item_elements: bool) {
+        lines.push(self.hp.serialize());
+        lines.push(self.max_hp.serialize());
+        lines.push(self.attack.serialize());
+        lines.push(self.defense.serialize());
+        lines.push(self.magic.serialize());
+        lines.push(self.guts.serialize());
+        lines.push(self.weapon.serialize());
+        lines.push(self.armor1.serialize());
+        lines.push(self.armor2.serialize());
+        lines.push(self.weapon_style.serialize());
+        for item_stats in &self.item_stats {
+            item_stats.write_lines(lines, item_elements);
+        }
+        lines.extend(self.spells.iter().map(|spell| spell.serialize()));
+    }
+}
+
+/// Kris's stats in the light world, which work like in Undertale.
+#[derive(Debug, Clone, Default, Visit)]
 pub struct LightworldStats {
-    pub weapon: i32,
-    pub armor: i32,
+    pub weapon: LightworldItemId,
+    pub armor: LightworldItemId,
     pub xp: i32,
     pub lv: i32,
     pub gold: i32,
@@ -303,6 +605,247 @@ pub struct LightworldStats {
     pub max_hp: i32,
     pub attack: i32,
     pub defense: i32,
-    pub wstrength: i32, // ?
-    pub adef: i32,      // ?
+    /// Attack added by the equipped weapon (`global.lwstrength`).
+    pub weapon_attack: i32,
+    /// Defense added by the equipped armor (`global.ladef`).
+    pub armor_defense: i32,
+}
+
+impl LightworldStats {
+    fn write_lines(&self, lines: &mut Vec<CompactString>) {
+        lines.push(self.weapon.serialize());
+        lines.push(self.armor.serialize());
+        lines.push(self.xp.serialize());
+        lines.push(self.lv.serialize());
+        lines.push(self.gold.serialize());
+        lines.push(self.hp.serialize());
+        lines.push(self.max_hp.serialize());
+        lines.push(self.attack.serialize());
+        lines.push(self.defense.serialize());
+        lines.push(self.weapon_attack.serialize());
+        lines.push(self.armor_defense.serialize());
+    }
+}
+
+/// The saves in `core/fixtures`, one per chapter.
+#[cfg(test)]
+pub(crate) const FIXTURES: [(i32, &str); 4] = [
+    (1, include_str!("../fixtures/filech1_0")),
+    (2, include_str!("../fixtures/filech2_0")),
+    (3, include_str!("../fixtures/filech3_0")),
+    (4, include_str!("../fixtures/filech4_0")),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixtures() {
+        // Where Kris's stat block starts, counted by hand from `research/saveprocess`: 16 lines
+        // before the stat blocks, then block 0, which is 54 lines in chapter 1 and 62 after.
+        for ((chapter, content), kris_start) in FIXTURES.into_iter().zip([70, 78, 78, 78]) {
+            let lines = content.lines().collect::<Vec<_>>();
+            let save = SaveData::read(chapter, &lines).unwrap();
+
+            let kris = &save.stats[PartyMember::KRIS.0 as usize];
+            assert_eq!(lines[kris_start].trim(), kris.hp.to_string(), "chapter {chapter}");
+            assert_eq!(lines[kris_start + 6].trim(), kris.weapon.0.to_string());
+            assert_eq!(lines[lines.len() - 2].trim(), save.room_id.0.to_string());
+            assert_eq!(&lines[7..10], ["1 ", "2 ", "3 "]);
+
+            // Like the game, `write` leaves out the line break after the time played
+            assert_eq!(save.write(), content, "chapter {chapter}");
+            assert_eq!(save.validate(), vec![], "chapter {chapter}");
+            assert_eq!(save.display_party(), "Kris, Susie, Ralsei");
+            assert!(save.room_id.area().is_some());
+        }
+    }
+
+    /// Sanitized saves from real playthroughs in `fixtures/real`, named like the game names them.
+    fn real_saves() -> Vec<(String, i32, Vec<u8>)> {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/real");
+        let entries = std::fs::read_dir(dir).expect("fixtures/real is missing, see fixtures/README.md");
+
+        let saves = entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let (chapter, _) = crate::gamedata::parse_filename(&name);
+                (chapter > 0).then(|| (name, chapter, std::fs::read(entry.path()).unwrap()))
+            })
+            .collect::<Vec<_>>();
+
+        for chapter in 1..=crate::gamedata::CHAPTER_COUNT as i32 {
+            assert!(
+                saves.iter().any(|(_, c, _)| *c == chapter),
+                "fixtures/real has no chapter {chapter} save, see fixtures/README.md"
+            );
+        }
+        saves
+    }
+
+    #[test]
+    fn test_real_saves() {
+        for (name, chapter, content) in real_saves() {
+            let save = SaveData::from_bytes(chapter, &content).unwrap();
+            assert_eq!(save.write().as_bytes(), content, "{name}");
+            assert_eq!(save.validate(), vec![], "{name}");
+
+            let merged = crate::merging::merge_savefiles(&save, &save, Some(&save))
+                .unwrap()
+                .into_iter()
+                .map(|result| match result {
+                    crate::merging::MergeResult::Resolved(line) => line,
+                    conflict => panic!("{name}: {}", conflict.to_merge_string(7)),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(merged.join("\r\n").as_bytes(), content, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        for (chapter, content) in FIXTURES {
+            let save = SaveData::from_bytes(chapter, content.as_bytes()).unwrap();
+            assert_eq!(save.write(), content, "chapter {chapter}");
+            let save = SaveData::from_reader(chapter, content.as_bytes()).unwrap();
+            assert_eq!(save.write(), content, "chapter {chapter}");
+        }
+
+        // The game writes CRLF, but saves that went through git may have LF
+        let mut crlf = SaveData::builder(2).build().write().into_bytes();
+        crlf.splice(0..4, *b"Kr\xffs");
+        let lf = crlf.iter().copied().filter(|&b| b != b'\r').collect::<Vec<_>>();
+        for bytes in [&crlf, &lf] {
+            let save = SaveData::from_bytes(2, bytes).unwrap();
+            assert_eq!(save.true_name, "Kr\u{fffd}s");
+            assert!(matches!(save.true_name, Cow::Owned(_)));
+            assert!(matches!(save.vessel_names[0], Cow::Borrowed("")));
+            let save = SaveData::from_reader(2, bytes.as_slice()).unwrap();
+            assert_eq!(save.true_name, "Kr\u{fffd}s");
+        }
+
+        let err = SaveData::from_bytes(2, &crlf[..crlf.len() - 10]).unwrap_err();
+        assert!(matches!(err.kind, ParseErrorKind::EofUnexpected));
+
+        // Both a bad value and a missing one report the line they're on, counting from 1
+        let mut lines = vec!["0"; 3055];
+        lines[10] = "abc"; // D$
+        let err = SaveData::read(2, &lines).unwrap_err();
+        assert!(matches!(err.kind, ParseErrorKind::IntParse));
+        assert_eq!(err.line, Some(11));
+        let err = SaveData::read(2, &lines[..10]).unwrap_err();
+        assert!(matches!(err.kind, ParseErrorKind::EofUnexpected));
+        assert_eq!(err.line, Some(11));
+
+        let mut extra = lf.clone();
+        extra.extend_from_slice(b"\n\n0");
+        let err = SaveData::from_reader(2, extra.as_slice()).unwrap_err();
+        assert!(matches!(err.kind, ParseErrorKind::EofExpected));
+        // A single empty line at the end is allowed
+        extra.truncate(lf.len() + 2);
+        assert!(SaveData::from_reader(2, extra.as_slice()).is_ok());
+
+        let err = SaveData::from_bytes(0, &lf).unwrap_err();
+        assert!(matches!(err.kind, ParseErrorKind::InvalidChapter(0)));
+    }
+
+    #[test]
+    fn test_write_roundtrip() {
+        for (chapter, line_count) in [(1, 10318), (2, 3055), (4, 3055)] {
+            let lines = vec!["0"; line_count];
+            let save = SaveData::read(chapter, &lines).unwrap();
+
+            let written = save.to_lines();
+            assert_eq!(written.len(), line_count);
+
+            let written_lines = written.iter().map(|l| l.as_str()).collect::<Vec<_>>();
+            let reread = SaveData::read(chapter, &written_lines).unwrap();
+            assert_eq!(reread.to_lines(), written);
+        }
+    }
+
+    #[test]
+    fn test_lossless_reals() {
+        let mut lines = vec!["0"; 3055];
+        lines[3000] = "123456789.125 ";
+        lines[3054] = "16777217 ";
+        let save = SaveData::read(2, &lines).unwrap();
+
+        assert_eq!(save.time_played_frames(), 16777217);
+        let written = save.to_lines();
+        assert_eq!(written[3000], "123456789.125 ");
+        assert_eq!(written[3054], "16777217 ");
+    }
+
+    #[test]
+    fn test_time_played_roundtrip() {
+        for frames in ["108123.5 ", "1.08e+05 ", "-30 "] {
+            let mut lines = vec!["0"; 3055];
+            lines[3054] = frames;
+            let save = SaveData::read(2, &lines).unwrap();
+            assert_eq!(save.to_lines()[3054], frames);
+        }
+
+        let mut lines = vec!["0"; 3055];
+        lines[3054] = "108123.5 ";
+        let mut save = SaveData::read(2, &lines).unwrap();
+        assert_eq!(save.time_played_duration().as_secs(), 3604);
+        assert_eq!(save.time_played_frames(), 108124);
+
+        save.set_time_played(Duration::from_secs(60));
+        assert_eq!(save.to_lines()[3054], "1800 ");
+    }
+
+    #[test]
+    fn test_display_characters() {
+        let lines = vec!["0"; 3055];
+        let mut save = SaveData::read(2, &lines).unwrap();
+        save.party[0] = PartyMember::KRIS;
+        save.stats[1].weapon = WeaponId(5);
+        save.stats[1].item_stats[0].attack = 2;
+        save.stats[1].spells[0] = SpellId(7);
+        save.stats[0].max_hp = 50;
+
+        let characters = save.display_characters();
+        assert!(characters.contains("Kris (in party)\nHP 0/0  AT 2 (+2)"));
+        assert!(characters.contains("Weapon: Spookysword\n"));
+        assert!(characters.contains("Spells: ACT\n"));
+        assert!(characters.contains("\nNoelle\n"));
+        // Block 0 is unused and gets no sheet
+        assert!(!characters.contains("HP 0/50"));
+        assert!(save.display_info().contains("Party: Kris\n"));
+    }
+
+    #[test]
+    fn test_display_lightworld() {
+        let lines = vec!["0"; 3055];
+        let mut save = SaveData::read(2, &lines).unwrap();
+        save.lightworld_stats.attack = 10;
+        save.lightworld_stats.weapon_attack = 1;
+        save.lightworld_stats.weapon = LightworldItemId(2);
+        save.lightworld_phone[0] = PhoneNumberId(201);
+
+        let lightworld = save.display_lightworld();
+        assert!(lightworld.contains("AT 11 (+1)"));
+        assert!(lightworld.contains("Weapon: Pencil\n"));
+        assert!(lightworld.contains("Phone: Call Home\n"));
+        assert!(save.display_info().contains("Light World:"));
+
+        save.is_darkworld = true;
+        assert!(!save.display_info().contains("Light World:"));
+    }
+
+    #[test]
+    fn test_shadow_crystals() {
+        let lines = vec!["0"; 3055];
+        let mut save = SaveData::read(2, &lines).unwrap();
+        save.flags[Flag::ShadowCrystalCh1.index()].set(1.0);
+        save.flags[Flag::ShadowCrystalCh3.index()].set(1.0);
+
+        assert_eq!(save.flag(Flag::ShadowCrystalCh1), FlagValue::Bool(true));
+        assert_eq!(save.shadow_crystals(), 1);
+        assert!(save.display_flags().contains("shadow_crystal_ch1 = true"));
+    }
 }
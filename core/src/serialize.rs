@@ -16,10 +16,10 @@ impl Serializable for i32 {
     }
 }
 
-impl Serializable for f32 {
+impl Serializable for i64 {
     fn serialize(&self) -> CompactString {
         format_compact!("{} ", self)
-    }   
+    }
 }
 
 impl Serializable for u64 {
@@ -0,0 +1,282 @@
+//! Statistics about play sessions, built from the history of a save slot in git.
+//!
+//! Every commit of a save is a point where the game was saved. The time played between two
+//! consecutive commits is counted towards the area of the later save, the area that stretch of
+//! play ended in. Time that went backwards, like after loading an older save, isn't counted.
+
+use crate::gamedata::rooms::RoomIndex;
+use crate::history::Revision;
+use crate::savefile::{SaveData, display_time_played};
+use compact_str::{CompactString, format_compact};
+use std::fmt::Write;
+use std::time::Duration;
+
+/// The values of a save that session statistics look at, in one revision.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveSnapshot {
+    pub commit: CompactString,
+    /// Author date in strict ISO 8601 format.
+    pub date: CompactString,
+    pub room_id: RoomIndex,
+    pub time_played: Duration,
+    pub plot_value: f64,
+    pub xp: i32,
+    pub dark_dollars: i32,
+}
+
+impl SaveSnapshot {
+    pub fn new(revision: &Revision, save: &SaveData) -> Self {
+        SaveSnapshot {
+            commit: revision.short_commit.clone(),
+            date: revision.date.clone(),
+            room_id: save.room_id,
+            time_played: save.time_played_duration(),
+            plot_value: save.plot_value.value(),
+            xp: save.xp,
+            dark_dollars: save.dark_dollars,
+        }
+    }
+
+    /// The area of the save's room, or the chapter for rooms outside of any area.
+    pub fn area(&self) -> CompactString {
+        match self.room_id.area() {
+            Some(area) => area.into(),
+            None => format_compact!("Chapter {}", self.room_id.chapter()),
+        }
+    }
+}
+
+/// How a save changed since the previous commit.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SaveDelta {
+    pub commit: CompactString,
+    pub date: CompactString,
+    pub room: CompactString,
+    pub area: CompactString,
+    /// Negative if time went backwards.
+    pub time_played_secs: f64,
+    pub plot_value: f64,
+    pub xp: i32,
+    pub dark_dollars: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct AreaTime {
+    pub area: CompactString,
+    pub time_played_secs: f64,
+    /// Number of saves made in the area.
+    pub saves: usize,
+}
+
+/// The most in-game time played between two consecutive saves.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SaveGap {
+    pub from_commit: CompactString,
+    pub to_commit: CompactString,
+    pub time_played_secs: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SessionStats {
+    pub deltas: Vec<SaveDelta>,
+    /// Time played per area, in the order the areas were first saved in.
+    pub areas: Vec<AreaTime>,
+    pub time_played_secs: f64,
+    /// D$ gained over all commits. Spending doesn't count against it.
+    pub dark_dollars_earned: i32,
+    pub dark_dollars_per_hour: Option<f64>,
+    pub longest_gap: Option<SaveGap>,
+}
+
+/// Computes the statistics of a save's snapshots, oldest first.
+pub fn session_stats(snapshots: &[SaveSnapshot]) -> SessionStats {
+    let mut deltas = Vec::new();
+    let mut areas: Vec<AreaTime> = Vec::new();
+    let mut longest_gap: Option<SaveGap> = None;
+
+    for pair in snapshots.windows(2) {
+        let (previous, current) = (&pair[0], &pair[1]);
+        let time_played_secs =
+            current.time_played.as_secs_f64() - previous.time_played.as_secs_f64();
+        let area = current.area();
+
+        let index = match areas.iter().position(|a| a.area == area) {
+            Some(index) => index,
+            None => {
+                areas.push(AreaTime {
+                    area: area.clone(),
+                    time_played_secs: 0.0,
+                    saves: 0,
+                });
+                areas.len() - 1
+            }
+        };
+        areas[index].time_played_secs += time_played_secs.max(0.0);
+        areas[index].saves += 1;
+
+        if longest_gap.as_ref().is_none_or(|gap| time_played_secs > gap.time_played_secs) {
+            longest_gap = Some(SaveGap {
+                from_commit: previous.commit.clone(),
+                to_commit: current.commit.clone(),
+                time_played_secs,
+            });
+        }
+
+        deltas.push(SaveDelta {
+            commit: current.commit.clone(),
+            date: current.date.clone(),
+            room: current.room_id.name().unwrap_or("?").into(),
+            area,
+            time_played_secs,
+            plot_value: current.plot_value - previous.plot_value,
+            xp: current.xp - previous.xp,
+            dark_dollars: current.dark_dollars - previous.dark_dollars,
+        });
+    }
+
+    let time_played_secs = areas.iter().map(|a| a.time_played_secs).sum::<f64>();
+    let dark_dollars_earned = deltas.iter().map(|d| d.dark_dollars.max(0)).sum::<i32>();
+
+    SessionStats {
+        deltas,
+        areas,
+        time_played_secs,
+        dark_dollars_earned,
+        dark_dollars_per_hour: (time_played_secs > 0.0)
+            .then(|| dark_dollars_earned as f64 * 3600.0 / time_played_secs),
+        longest_gap: longest_gap.filter(|gap| gap.time_played_secs > 0.0),
+    }
+}
+
+fn csv_field(value: &str) -> CompactString {
+    if value.contains([',', '"', '\n']) {
+        format_compact!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.into()
+    }
+}
+
+impl SessionStats {
+    /// The deltas of every commit, one line each.
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("commit,date,room,area,time_played_secs,plot_value,xp,dark_dollars\n");
+        for delta in &self.deltas {
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{}",
+                delta.commit,
+                delta.date,
+                csv_field(&delta.room),
+                csv_field(&delta.area),
+                delta.time_played_secs,
+                delta.plot_value,
+                delta.xp,
+                delta.dark_dollars
+            )
+            .unwrap();
+        }
+        csv
+    }
+
+    /// Draws the time played per area as a bar chart, followed by the totals.
+    pub fn chart(&self, width: usize) -> String {
+        let mut chart = String::new();
+        let label_width = self.areas.iter().map(|a| a.area.len()).max().unwrap_or(0);
+        let max_secs = self.areas.iter().map(|a| a.time_played_secs).fold(0.0, f64::max);
+
+        for area in &self.areas {
+            let bar = if max_secs > 0.0 {
+                (area.time_played_secs / max_secs * width as f64).round() as usize
+            } else {
+                0
+            };
+            writeln!(
+                chart,
+                "{:<label_width$} |{:<width$}| {} ({} saves)",
+                area.area,
+                "#".repeat(bar),
+                display_time_played(area.time_played_secs as u64),
+                area.saves
+            )
+            .unwrap();
+        }
+
+        writeln!(chart, "Total: {}", display_time_played(self.time_played_secs as u64)).unwrap();
+        match self.dark_dollars_per_hour {
+            Some(rate) => writeln!(
+                chart,
+                "D$ earned: {} ({:.0} per hour)",
+                self.dark_dollars_earned, rate
+            ),
+            None => writeln!(chart, "D$ earned: {}", self.dark_dollars_earned),
+        }
+        .unwrap();
+        if let Some(gap) = &self.longest_gap {
+            writeln!(
+                chart,
+                "Longest gap between saves: {} ({} -> {})",
+                display_time_played(gap.time_played_secs as u64),
+                gap.from_commit,
+                gap.to_commit
+            )
+            .unwrap();
+        }
+        chart
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(commit: &str, room_id: i32, minutes: u64, dark_dollars: i32) -> SaveSnapshot {
+        SaveSnapshot {
+            commit: commit.into(),
+            date: "2025-06-01T12:00:00+02:00".into(),
+            room_id: RoomIndex(room_id),
+            time_played: Duration::from_secs(minutes * 60),
+            plot_value: 0.0,
+            xp: 0,
+            dark_dollars,
+        }
+    }
+
+    #[test]
+    fn test_session_stats() {
+        let stats = session_stats(&[
+            snapshot("a", 10283, 0, 0),
+            snapshot("b", 10330, 30, 100),
+            snapshot("c", 10330, 20, 50),
+            snapshot("d", 10330, 55, 80),
+            snapshot("e", 20006, 60, 200),
+        ]);
+
+        assert_eq!(stats.deltas.len(), 4);
+        assert_eq!(stats.deltas[1].time_played_secs, -600.0);
+        assert_eq!(stats.deltas[1].dark_dollars, -50);
+        assert_eq!(
+            stats.areas,
+            vec![
+                AreaTime {
+                    area: "Field of Hopes and Dreams".into(),
+                    time_played_secs: 3900.0,
+                    saves: 3,
+                },
+                AreaTime {
+                    area: "Chapter 2".into(),
+                    time_played_secs: 300.0,
+                    saves: 1,
+                },
+            ]
+        );
+        assert_eq!(stats.dark_dollars_earned, 250);
+        assert_eq!(stats.dark_dollars_per_hour, Some(250.0 * 3600.0 / 4200.0));
+        assert_eq!(stats.longest_gap.as_ref().unwrap().to_commit, "d");
+
+        let csv = stats.to_csv();
+        assert_eq!(csv.lines().count(), 5);
+        assert!(csv.contains("\nc,2025-06-01T12:00:00+02:00,"));
+        assert!(stats.chart(10).starts_with("Field of Hopes and Dreams |##########| 1h05m00s"));
+    }
+}
@@ -0,0 +1,149 @@
+//! Combines a party member's base stats with their equipment, like the in-game party menu.
+
+use crate::gamedata::armors::ArmorId;
+use crate::gamedata::equipment::StatBonus;
+use crate::gamedata::party_members::PartyMember;
+use crate::savefile::{ItemStats, SaveData, Stats};
+
+/// Which equipment slot the first three `item_stats` blocks cache the stats of. The fourth
+/// block isn't tied to a slot.
+pub const ITEM_STAT_SLOTS: [&str; 3] = ["weapon", "armor1", "armor2"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectiveStats {
+    pub member: PartyMember,
+    pub hp: i32,
+    pub max_hp: i32,
+    pub attack: i32,
+    pub defense: i32,
+    pub magic: i32,
+    pub guts: i32,
+    /// Bonus TP gained from grazing, summed over all equipment.
+    pub graze_amount: i32,
+    /// Bonus graze area, summed over all equipment.
+    pub graze_size: i32,
+    /// Damage reduction by element ID, summed over all equipment.
+    pub resistances: Vec<(i32, f64)>,
+}
+
+impl ItemStats {
+    /// The attack, defense and magic this slot adds.
+    pub fn bonus(&self) -> StatBonus {
+        StatBonus::new(self.attack, self.defense, self.magic)
+    }
+}
+
+impl Stats {
+    /// Computes the stats shown in the party menu from the base stats and the stats the game
+    /// cached for the equipped items.
+    pub fn effective(&self, member: PartyMember) -> EffectiveStats {
+        let bonus = self
+            .item_stats
+            .iter()
+            .fold(StatBonus::default(), |sum, item| sum + item.bonus());
+
+        let mut resistances: Vec<(i32, f64)> = Vec::new();
+        for item in self.item_stats.iter().filter(|item| item.item_element != 0) {
+            let item_amount = item.item_element_amount.value();
+            match resistances.iter_mut().find(|(element, _)| *element == item.item_element) {
+                Some((_, amount)) => *amount += item_amount,
+                None => resistances.push((item.item_element, item_amount)),
+            }
+        }
+
+        EffectiveStats {
+            member,
+            hp: self.hp,
+            max_hp: self.max_hp,
+            attack: self.attack + bonus.attack,
+            defense: self.defense + bonus.defense,
+            magic: self.magic + bonus.magic,
+            guts: self.guts,
+            graze_amount: self.item_stats.iter().map(|item| item.graze_amount).sum(),
+            graze_size: self.item_stats.iter().map(|item| item.graze_size).sum(),
+            resistances,
+        }
+    }
+
+    /// The stats the gamedata tables say each equipped item should add, in the order of
+    /// [`ITEM_STAT_SLOTS`]. `None` where the item isn't known.
+    pub fn expected_item_bonuses(&self) -> [Option<StatBonus>; 3] {
+        let weapon = (self.weapon.is_empty() || self.weapon.info().is_some())
+            .then(|| self.weapon.bonus());
+        let armor = |armor: ArmorId| {
+            (armor.is_empty() || armor.info().is_some()).then(|| armor.bonus())
+        };
+        [weapon, armor(self.armor1), armor(self.armor2)]
+    }
+}
+
+/// Effective stats of every stat block that belongs to a party member, in member order.
+///
+/// Stat blocks are indexed by character ID, so block 0 is skipped.
+pub fn effective_stats(save: &SaveData) -> Vec<EffectiveStats> {
+    let context = save.context();
+    save.stats
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, stats)| (PartyMember(i as i32), stats))
+        .filter(|(member, _)| member.name(&context).is_ok())
+        .map(|(member, stats)| stats.effective(member))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamedata::weapons::WeaponId;
+
+    #[test]
+    fn test_effective_stats() {
+        let mut stats = Stats {
+            attack: 10,
+            defense: 2,
+            weapon: WeaponId(5), // Spookysword
+            ..Stats::default()
+        };
+        stats.item_stats[0].attack = 2;
+        stats.item_stats[1].defense = 1;
+        stats.item_stats[1].graze_size = 10;
+        stats.item_stats[1].item_element = 6;
+        stats.item_stats[1].item_element_amount.set(0.5);
+        stats.item_stats[2].item_element = 6;
+        stats.item_stats[2].item_element_amount.set(0.25);
+
+        let effective = stats.effective(PartyMember::KRIS);
+        assert_eq!(effective.attack, 12);
+        assert_eq!(effective.defense, 3);
+        assert_eq!(effective.graze_size, 10);
+        assert_eq!(effective.resistances, vec![(6, 0.75)]);
+
+        assert_eq!(
+            stats.expected_item_bonuses(),
+            [Some(StatBonus::new(2, 0, 0)), Some(StatBonus::default()), Some(StatBonus::default())]
+        );
+    }
+
+    #[test]
+    fn test_effective_stats_members() {
+        let save = SaveData::builder(2)
+            .stats(PartyMember::NOELLE, |stats| stats.max_hp = 90)
+            .build();
+        let members = effective_stats(&save);
+        let members = members.iter().map(|e| (e.member, e.max_hp)).collect::<Vec<_>>();
+        assert_eq!(
+            members,
+            [
+                (PartyMember::KRIS, 0),
+                (PartyMember::SUSIE, 0),
+                (PartyMember::RALSEI, 0),
+                (PartyMember::NOELLE, 90)
+            ]
+        );
+
+        // Chapter 1 has no block for Noelle
+        let save = SaveData::builder(1).build();
+        assert_eq!(effective_stats(&save).last().unwrap().member, PartyMember::RALSEI);
+    }
+}
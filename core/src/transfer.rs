@@ -0,0 +1,130 @@
+//! Simulates how a chapter imports the completion save of the previous one.
+//!
+//! When a chapter is finished, the game writes a completion save (see
+//! [`is_completion_slot`](crate::gamedata::is_completion_slot)). Starting the next chapter from
+//! that slot loads it with the previous chapter's layout and then resets everything that
+//! belongs to the previous chapter's ending. Names, money, experience, stats, equipment,
+//! inventories, light world state and flags carry over.
+//!
+//! This is an approximation. The chapters' load scripts aren't decompiled under `research/`
+//! yet, so the starting room and party come from playing the game, and flags the next chapter
+//! sets or clears when it starts aren't simulated at all. Use
+//! [`is_simulated_start`] to tell such a save apart from one the game wrote.
+
+use crate::gamedata::party_members::PartyMember;
+use crate::gamedata::rooms::{RoomIndex, start_room};
+use crate::layout::{SaveField, SaveLayout};
+use crate::savefile::{SaveData, Stats};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum TransferError {
+    #[error("chapter {0} has no next chapter to transfer to")]
+    NoNextChapter(i32),
+}
+
+/// Where and with whom the game starts a chapter loaded from a completion save.
+struct ChapterStart {
+    room_id: RoomIndex,
+    party: [PartyMember; 3],
+    is_darkworld: bool,
+}
+
+fn chapter_start(chapter: i32) -> Option<ChapterStart> {
+    match chapter {
+        // Kris wakes up in their room, alone.
+        2 | 4 => Some(ChapterStart {
+            room_id: start_room(chapter),
+            party: [PartyMember(1), PartyMember(0), PartyMember(0)],
+            is_darkworld: false,
+        }),
+        // Everyone is pulled into the TV World right away.
+        3 => Some(ChapterStart {
+            room_id: start_room(chapter),
+            party: [PartyMember(1), PartyMember(2), PartyMember(3)],
+            is_darkworld: true,
+        }),
+        _ => None,
+    }
+}
+
+/// Produces the state the next chapter starts with when it loads the given completion save.
+///
+/// The save is widened to the [layout](SaveLayout) of the next chapter. Stat blocks are indexed
+/// by character ID and chapter 1 stops at Ralsei's block 3, so from chapter 1 an empty block 4
+/// is added for Noelle (the game fills it in when she joins). The weapon and armor lists grow to
+/// 48 entries and an empty storage is added. Everyone is healed, the tension is reset and the
+/// plot starts over.
+///
+/// This is an approximation, see the [module docs](self). Flags are copied unchanged.
+pub fn next_chapter_start<'a>(completion: &SaveData<'a>) -> Result<SaveData<'a>, TransferError> {
+    let chapter = completion.chapter + 1;
+    let start = chapter_start(chapter).ok_or(TransferError::NoNextChapter(completion.chapter))?;
+
+    let mut save = completion.clone();
+    save.chapter = chapter;
+    save.party = start.party;
+    save.is_darkworld = start.is_darkworld;
+    save.room_id = start.room_id;
+    save.plot_value.set(0.0);
+    save.tension.set(0.0);
+    save.inv = 0;
+    save.invc = 0;
+
+    let layout = SaveLayout::for_chapter(chapter);
+    save.stats.resize_with(layout.count(SaveField::Stats), Stats::default);
+    for stats in &mut save.stats {
+        stats.hp = stats.max_hp;
+    }
+
+    save.weapons.resize(layout.count(SaveField::Weapons), Default::default());
+    save.armors.resize(layout.count(SaveField::Armors), Default::default());
+    let storage_slots = layout.count(SaveField::Storage);
+    if storage_slots > 0 {
+        save.storage.get_or_insert_default().resize(storage_slots, Default::default());
+    }
+
+    save.lightworld_stats.hp = save.lightworld_stats.max_hp;
+
+    Ok(save)
+}
+
+/// Whether `save` is exactly what [`next_chapter_start`] makes of `completion`, meaning it was
+/// most likely produced by the simulation instead of the game.
+pub fn is_simulated_start(save: &SaveData, completion: &SaveData) -> bool {
+    next_chapter_start(completion).is_ok_and(|start| start.to_lines() == save.to_lines())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chapter_1_to_2() {
+        let mut lines = vec!["0"; 10318];
+        lines[0] = "Kris";
+        lines[10] = "150"; // D$
+        let save = SaveData::read(1, &lines).unwrap();
+
+        let next = next_chapter_start(&save).unwrap();
+
+        assert_eq!(next.chapter, 2);
+        assert_eq!(next.true_name, "Kris");
+        assert_eq!(next.dark_dollars, 150);
+        assert_eq!(next.stats.len(), 5);
+        assert_eq!(next.weapons.len(), 48);
+        assert_eq!(next.storage.as_ref().map(|s| s.len()), Some(72));
+        assert_eq!(next.room_id, RoomIndex(20028));
+        assert_eq!(next.to_lines().len(), 3055);
+        assert!(is_simulated_start(&next, &save));
+        assert!(!is_simulated_start(&save, &save));
+    }
+
+    #[test]
+    fn test_last_chapter() {
+        let lines = vec!["0"; 3055];
+        let save = SaveData::read(4, &lines).unwrap();
+
+        assert_eq!(next_chapter_start(&save).err(), Some(TransferError::NoNextChapter(4)));
+    }
+}
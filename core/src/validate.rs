@@ -0,0 +1,290 @@
+//! Sanity checks for saves, to catch broken merges and hand edits before the game loads them.
+
+use crate::gamedata::equipment::StatBonus;
+use crate::gamedata::party_members::PartyMember;
+use crate::gamedata::{GameContext, LookupError};
+use crate::savefile::SaveData;
+use crate::stats::ITEM_STAT_SLOTS;
+use compact_str::{CompactString, format_compact};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Issue {
+    NegativeDarkDollars(i32),
+    HpAboveMax {
+        member: PartyMember,
+        hp: i32,
+        max_hp: i32,
+    },
+    CannotEquip {
+        member: PartyMember,
+        equipment: CompactString,
+    },
+    /// An ID that isn't in the gamedata tables, or can't exist in the save's chapter.
+    InvalidId {
+        location: CompactString,
+        error: LookupError,
+    },
+    InvalidPartyMember {
+        slot: usize,
+        member: PartyMember,
+    },
+    DuplicatePartyMember(PartyMember),
+    DuplicateKeyItem(CompactString),
+    RoomNotInChapter {
+        room: CompactString,
+        chapter: i32,
+    },
+    /// The item stats the game cached for an equipment slot don't match the equipped item.
+    StaleItemStats {
+        member: PartyMember,
+        slot: &'static str,
+        cached: StatBonus,
+        expected: StatBonus,
+    },
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::NegativeDarkDollars(amount) => write!(f, "negative D$ ({amount})"),
+            Issue::HpAboveMax { member, hp, max_hp } => {
+                write!(f, "{member} has more HP than their max ({hp}/{max_hp})")
+            }
+            Issue::CannotEquip { member, equipment } => {
+                write!(f, "{member} can't equip {equipment}")
+            }
+            Issue::InvalidId { location, error } => write!(f, "{location}: {error}"),
+            Issue::InvalidPartyMember { slot, member } => {
+                write!(f, "party slot {slot} holds invalid member {}", member.0)
+            }
+            Issue::DuplicatePartyMember(member) => write!(f, "{member} is in the party twice"),
+            Issue::DuplicateKeyItem(name) => write!(f, "key item {name} is held more than once"),
+            Issue::RoomNotInChapter { room, chapter } => {
+                write!(f, "room {room} doesn't belong to chapter {chapter}")
+            }
+            Issue::StaleItemStats {
+                member,
+                slot,
+                cached,
+                expected,
+            } => write!(f, "{member}'s {slot} adds {cached}, but the equipped item gives {expected}"),
+        }
+    }
+}
+
+/// Checks every ID in a list, reporting the first problem with each entry.
+fn check_ids<T: Copy>(
+    issues: &mut Vec<Issue>,
+    ids: &[T],
+    name: &str,
+    lookup: impl Fn(T) -> Result<&'static str, LookupError>,
+) {
+    for (i, &id) in ids.iter().enumerate() {
+        if let Err(error) = lookup(id) {
+            issues.push(Issue::InvalidId {
+                location: format_compact!("{name}[{i}]"),
+                error,
+            });
+        }
+    }
+}
+
+/// Finds all inconsistencies in a save. An empty list means the save looks fine.
+pub fn validate(save: &SaveData) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let context = save.context();
+
+    if save.dark_dollars < 0 {
+        issues.push(Issue::NegativeDarkDollars(save.dark_dollars));
+    }
+
+    check_party(&mut issues, save, &context);
+    check_stats(&mut issues, save, &context);
+
+    check_ids(&mut issues, &save.inventory, "inventory", |id| id.name(&context));
+    check_ids(&mut issues, &save.key_items, "key_items", |id| id.name(&context));
+    check_ids(&mut issues, &save.weapons, "weapons", |id| id.name(&context));
+    check_ids(&mut issues, &save.armors, "armors", |id| id.name(&context));
+    if let Some(storage) = &save.storage {
+        check_ids(&mut issues, storage, "storage", |id| id.name(&context));
+    }
+    check_ids(&mut issues, &save.lightworld_items, "lightworld_items", |id| {
+        id.name(&context)
+    });
+    check_ids(&mut issues, &save.lightworld_phone, "lightworld_phone", |id| {
+        // Empty phone slots are 0, which isn't a contact
+        if id.is_empty() { Ok("") } else { id.name(&context) }
+    });
+
+    let mut seen_key_items = Vec::new();
+    for &key_item in save.key_items.iter().filter(|id| !id.is_empty()) {
+        if seen_key_items.contains(&key_item) {
+            issues.push(Issue::DuplicateKeyItem(key_item.display_in(&context)));
+        } else {
+            seen_key_items.push(key_item);
+        }
+    }
+
+    if save.room_id.chapter() != save.chapter {
+        issues.push(Issue::RoomNotInChapter {
+            room: save.room_id.to_string().into(),
+            chapter: save.chapter,
+        });
+    } else if save.room_id.id_name().is_none() {
+        issues.push(Issue::InvalidId {
+            location: "room_id".into(),
+            error: LookupError::Unknown {
+                kind: "room",
+                id: save.room_id.0,
+            },
+        });
+    }
+
+    issues
+}
+
+fn check_party(issues: &mut Vec<Issue>, save: &SaveData, context: &GameContext) {
+    let mut seen = Vec::new();
+
+    for (slot, &member) in save.party.iter().enumerate() {
+        if member.is_empty() {
+            continue;
+        }
+
+        if member.name(context).is_err() {
+            issues.push(Issue::InvalidPartyMember { slot, member });
+        } else if seen.contains(&member) {
+            issues.push(Issue::DuplicatePartyMember(member));
+        } else {
+            seen.push(member);
+        }
+    }
+}
+
+fn check_stats(issues: &mut Vec<Issue>, save: &SaveData, context: &GameContext) {
+    // Stat blocks are indexed by character ID, like `global.hp[charid]` in the game. Block 0
+    // is unused, Noelle's block only exists from chapter 2 on.
+    for (i, stats) in save.stats.iter().enumerate().skip(1) {
+        let member = PartyMember(i as i32);
+        if member.name(context).is_err() {
+            continue;
+        }
+
+        if stats.hp > stats.max_hp {
+            issues.push(Issue::HpAboveMax {
+                member,
+                hp: stats.hp,
+                max_hp: stats.max_hp,
+            });
+        }
+
+        let location = |slot: &str| format_compact!("{member}'s {slot}");
+
+        match stats.weapon.name(context) {
+            Err(error) => issues.push(Issue::InvalidId {
+                location: location("weapon"),
+                error,
+            }),
+            // Without metadata there's no telling who can hold it.
+            Ok(name) if stats.weapon.info().is_some() && !stats.weapon.can_equip(member) => {
+                issues.push(Issue::CannotEquip {
+                    member,
+                    equipment: name.into(),
+                })
+            }
+            Ok(_) => {}
+        }
+
+        for (slot, armor) in [("armor1", stats.armor1), ("armor2", stats.armor2)] {
+            match armor.name(context) {
+                Err(error) => issues.push(Issue::InvalidId {
+                    location: location(slot),
+                    error,
+                }),
+                Ok(name) if armor.info().is_some() && !armor.can_equip(member) => {
+                    issues.push(Issue::CannotEquip {
+                        member,
+                        equipment: name.into(),
+                    })
+                }
+                Ok(_) => {}
+            }
+        }
+
+        check_ids(issues, &stats.spells, &location("spells"), |id| id.name(context));
+
+        let expected = stats.expected_item_bonuses();
+        for ((slot, item_stats), expected) in ITEM_STAT_SLOTS.iter().zip(&stats.item_stats).zip(expected) {
+            let cached = item_stats.bonus();
+            if let Some(expected) = expected.filter(|&expected| expected != cached) {
+                issues.push(Issue::StaleItemStats {
+                    member,
+                    slot,
+                    cached,
+                    expected,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamedata::armors::ArmorId;
+    use crate::gamedata::key_items::KeyItemId;
+    use crate::gamedata::rooms::RoomIndex;
+    use crate::gamedata::weapons::WeaponId;
+
+    #[test]
+    fn test_validate() {
+        let lines = vec!["0"; 3055];
+        let mut save = SaveData::read(2, &lines).unwrap();
+        save.room_id = RoomIndex(20028);
+        assert_eq!(validate(&save), vec![]);
+
+        // Block 0 is unused, so nothing in it is checked
+        save.stats[0].weapon = WeaponId(500);
+        assert_eq!(validate(&save), vec![]);
+
+        save.dark_dollars = -5;
+        save.party[1] = PartyMember(9);
+        save.stats[1].hp = 10;
+        save.stats[1].weapon = WeaponId(2); // Mane Ax
+        save.stats[2].armor1 = ArmorId(500);
+        save.key_items[0] = KeyItemId(1);
+        save.key_items[1] = KeyItemId(1);
+        save.room_id = RoomIndex(10283);
+
+        let issues = validate(&save);
+        assert!(issues.contains(&Issue::NegativeDarkDollars(-5)));
+        assert!(issues.contains(&Issue::InvalidPartyMember {
+            slot: 1,
+            member: PartyMember(9)
+        }));
+        assert!(issues.contains(&Issue::HpAboveMax {
+            member: PartyMember::KRIS,
+            hp: 10,
+            max_hp: 0
+        }));
+        assert!(issues.contains(&Issue::CannotEquip {
+            member: PartyMember::KRIS,
+            equipment: "Mane Ax".into()
+        }));
+        assert!(issues.contains(&Issue::InvalidId {
+            location: "Susie's armor1".into(),
+            error: LookupError::Unknown { kind: "armor", id: 500 }
+        }));
+        assert!(issues.contains(&Issue::DuplicateKeyItem("Cell Phone".into())));
+        assert!(matches!(issues.last(), Some(Issue::RoomNotInChapter { chapter: 2, .. })));
+
+        save.stats[1].weapon = WeaponId(5); // Spookysword
+        assert!(validate(&save).contains(&Issue::StaleItemStats {
+            member: PartyMember::KRIS,
+            slot: "weapon",
+            cached: StatBonus::default(),
+            expected: StatBonus::new(2, 0, 0),
+        }));
+    }
+}
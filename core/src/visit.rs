@@ -0,0 +1,296 @@
+//! Walks the values of a save one field at a time, with the path and type of each value.
+//!
+//! [`Visit`] is derived for [`SaveData`](crate::savefile::SaveData) and the structs in it, so
+//! code that handles every field the same way, like diffing two saves, can be written once
+//! instead of listing each field:
+//!
+//! ```
+//! use deltakit::savefile::SaveData;
+//! use deltakit::visit::diff;
+//!
+//! let before = SaveData::builder(2).build();
+//! let after = SaveData::builder(2).dark_dollars(120).build();
+//! let changes = diff(&before, &after);
+//! assert_eq!(changes.len(), 1);
+//! assert_eq!(changes[0].to_string(), "dark_dollars: 0 -> 120");
+//! ```
+//!
+//! Structs derive it with `#[derive(Visit)]`, visiting their fields in declaration order.
+//! Fields marked `#[visit(skip)]` are left out.
+
+use crate::gamedata::rooms::RoomIndex;
+use crate::real::GmReal;
+use crate::savefile::display_time_played;
+use compact_str::{CompactString, ToCompactString};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+pub use deltakit_derive::Visit;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    Field(&'static str),
+    Index(usize),
+}
+
+/// Where a value is in a save, like `stats[1].item_stats[0].attack`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct FieldPath(Vec<PathSegment>);
+
+impl FieldPath {
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+
+    pub fn push_field(&mut self, name: &'static str) {
+        self.0.push(PathSegment::Field(name));
+    }
+
+    pub fn push_index(&mut self, index: usize) {
+        self.0.push(PathSegment::Index(index));
+    }
+
+    pub fn pop(&mut self) {
+        self.0.pop();
+    }
+
+    /// The name of the top-level field, like `stats` for `stats[1].hp`.
+    pub fn root(&self) -> Option<&'static str> {
+        match self.0.first() {
+            Some(PathSegment::Field(name)) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl Display for FieldPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Field(name) if i == 0 => f.write_str(name)?,
+                PathSegment::Field(name) => write!(f, ".{name}")?,
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A single value of a save, with its type.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FieldValue<'a> {
+    Str(&'a str),
+    Int(i32),
+    Bool(bool),
+    Real(&'a GmReal),
+    /// An ID from the gamedata tables, with its name if it's known.
+    Id {
+        kind: &'static str,
+        id: i32,
+        name: Option<&'static str>,
+    },
+    Duration(Duration),
+}
+
+impl Display for FieldValue<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldValue::Str(text) => write!(f, "{text:?}"),
+            FieldValue::Int(value) => write!(f, "{value}"),
+            FieldValue::Bool(value) => write!(f, "{value}"),
+            FieldValue::Real(value) => write!(f, "{value}"),
+            FieldValue::Id { id, name: Some(name), .. } => write!(f, "{id} ({name})"),
+            FieldValue::Id { id, name: None, .. } => write!(f, "{id}"),
+            FieldValue::Duration(duration) => {
+                f.write_str(&display_time_played(duration.as_secs()))
+            }
+        }
+    }
+}
+
+/// Receives every value of a visited struct.
+pub trait Visitor {
+    fn visit_value(&mut self, path: &FieldPath, value: FieldValue<'_>);
+}
+
+impl<F: FnMut(&FieldPath, FieldValue<'_>)> Visitor for F {
+    fn visit_value(&mut self, path: &FieldPath, value: FieldValue<'_>) {
+        self(path, value)
+    }
+}
+
+/// Something whose values can be walked by a [`Visitor`].
+pub trait Visit {
+    /// Visits the values under `path`, leaving `path` as it was.
+    fn visit_with(&self, path: &mut FieldPath, visitor: &mut dyn Visitor);
+
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        self.visit_with(&mut FieldPath::default(), visitor);
+    }
+}
+
+macro_rules! visit_leaf {
+    ($type:ty, $value:ident => $field_value:expr) => {
+        impl Visit for $type {
+            fn visit_with(&self, path: &mut FieldPath, visitor: &mut dyn Visitor) {
+                let $value = self;
+                visitor.visit_value(path, $field_value);
+            }
+        }
+    };
+}
+
+visit_leaf!(i32, value => FieldValue::Int(*value));
+visit_leaf!(bool, value => FieldValue::Bool(*value));
+visit_leaf!(&str, value => FieldValue::Str(value));
+visit_leaf!(Cow<'_, str>, value => FieldValue::Str(value));
+visit_leaf!(CompactString, value => FieldValue::Str(value));
+visit_leaf!(GmReal, value => FieldValue::Real(value));
+visit_leaf!(Duration, value => FieldValue::Duration(*value));
+visit_leaf!(RoomIndex, room => FieldValue::Id {
+    kind: RoomIndex::KIND,
+    id: room.0,
+    name: room.name(),
+});
+
+impl<T: Visit> Visit for [T] {
+    fn visit_with(&self, path: &mut FieldPath, visitor: &mut dyn Visitor) {
+        for (index, value) in self.iter().enumerate() {
+            path.push_index(index);
+            value.visit_with(path, visitor);
+            path.pop();
+        }
+    }
+}
+
+impl<T: Visit, const N: usize> Visit for [T; N] {
+    fn visit_with(&self, path: &mut FieldPath, visitor: &mut dyn Visitor) {
+        self.as_slice().visit_with(path, visitor);
+    }
+}
+
+impl<T: Visit> Visit for Vec<T> {
+    fn visit_with(&self, path: &mut FieldPath, visitor: &mut dyn Visitor) {
+        self.as_slice().visit_with(path, visitor);
+    }
+}
+
+impl<T: Visit + ?Sized> Visit for Box<T> {
+    fn visit_with(&self, path: &mut FieldPath, visitor: &mut dyn Visitor) {
+        (**self).visit_with(path, visitor);
+    }
+}
+
+/// `None` has no values to visit.
+impl<T: Visit> Visit for Option<T> {
+    fn visit_with(&self, path: &mut FieldPath, visitor: &mut dyn Visitor) {
+        if let Some(value) = self {
+            value.visit_with(path, visitor);
+        }
+    }
+}
+
+/// A value that differs between two saves. A value that only one of them has, like the
+/// storage between chapter 1 and 2, is `None` in the other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    pub path: FieldPath,
+    pub before: Option<CompactString>,
+    pub after: Option<CompactString>,
+}
+
+impl Display for FieldDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let before = self.before.as_deref().unwrap_or("(none)");
+        let after = self.after.as_deref().unwrap_or("(none)");
+        write!(f, "{}: {} -> {}", self.path, before, after)
+    }
+}
+
+/// Lists the values that changed from `before` to `after`, in visiting order.
+pub fn diff<T: Visit + ?Sized>(before: &T, after: &T) -> Vec<FieldDiff> {
+    let mut before_values = Vec::new();
+    before.visit(&mut |path: &FieldPath, value: FieldValue| {
+        before_values.push((path.clone(), value.to_compact_string()));
+    });
+    let mut after_values = Vec::new();
+    after.visit(&mut |path: &FieldPath, value: FieldValue| {
+        after_values.push((path.clone(), value.to_compact_string()));
+    });
+
+    let mut after_by_path = after_values
+        .iter()
+        .map(|(path, value)| (path, value))
+        .collect::<HashMap<_, _>>();
+    let mut diffs = Vec::new();
+    for (path, before) in &before_values {
+        match after_by_path.remove(path) {
+            Some(after) if after == before => {}
+            after => diffs.push(FieldDiff {
+                path: path.clone(),
+                before: Some(before.clone()),
+                after: after.cloned(),
+            }),
+        }
+    }
+    for (path, after) in &after_values {
+        if after_by_path.contains_key(path) {
+            diffs.push(FieldDiff {
+                path: path.clone(),
+                before: None,
+                after: Some(after.clone()),
+            });
+        }
+    }
+    diffs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamedata::party_members::PartyMember;
+    use crate::gamedata::weapons::WeaponId;
+    use crate::savefile::SaveData;
+
+    #[test]
+    fn test_visit_paths() {
+        let save = SaveData::builder(2).build();
+        let mut paths = Vec::new();
+        save.visit(&mut |path: &FieldPath, value: FieldValue| {
+            if path.root() != Some("flags") {
+                paths.push(format!("{path} = {value}"));
+            }
+        });
+
+        assert_eq!(paths[0], "chapter = 2");
+        assert_eq!(paths[1], "true_name = \"Kris\"");
+        assert!(paths.contains(&"party[0] = 1 (Kris)".to_string()));
+        assert!(paths.contains(&"stats[4].item_stats[3].item_element_amount = 0".to_string()));
+        assert!(paths.contains(&"storage[71] = 0 (---)".to_string()));
+        assert!(paths.contains(&"lightworld_stats.max_hp = 20".to_string()));
+        assert_eq!(paths.last().unwrap(), "time_played = 0");
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = SaveData::builder(2).build();
+        let after = SaveData::builder(2)
+            .equip(PartyMember::KRIS, WeaponId(5), Default::default())
+            .raw_flag(7, 1.0)
+            .build();
+
+        let diffs = diff(&before, &after);
+        let diffs = diffs.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert!(diffs.contains(&"stats[1].weapon: 0 (---) -> 5 (Spookysword)".to_string()));
+        assert!(diffs.contains(&"flags[7]: 0 -> 1".to_string()));
+        assert!(diff(&after, &after.clone()).is_empty());
+
+        // Chapter 1 has fewer stat blocks and no storage
+        let chapter1 = SaveData::builder(1).build();
+        let diffs = diff(&chapter1, &before);
+        assert_eq!(diffs[0].to_string(), "chapter: 1 -> 2");
+        assert!(diffs.iter().any(|d| d.before.is_none() && d.path.root() == Some("storage")));
+    }
+}
@@ -0,0 +1,103 @@
+use clap::Parser;
+use deltakit::gamedata::parse_filename;
+use deltakit::history::{bisect, list_revisions, read_revision, Revision};
+use deltakit::init;
+use deltakit::query::Expr;
+use deltakit::savefile::SaveData;
+use log::{error, info, warn};
+use std::process;
+
+#[derive(Parser, Debug)]
+#[command()]
+struct Args {
+    /// Save file to search the history of, either relative to the current directory or absolute.
+    #[arg(short, long)]
+    slot: String,
+    /// Condition to look for, e.g. `flags[1646] == 1` or `inventory contains Glowshard`.
+    #[arg(short, long)]
+    when: String,
+    /// Chapter number. If not specified, will try to parse from filename.
+    #[arg(short, long)]
+    chapter: Option<i32>,
+    /// Check every revision instead of bisecting.
+    ///
+    /// Useful when the condition can become false again later.
+    #[arg(short, long)]
+    linear: bool,
+}
+
+fn check(revision: &Revision, path: &str, chapter: i32, query: &Expr) -> Option<bool> {
+    let content = match read_revision(revision, path) {
+        Ok(content) => content,
+        Err(err) => {
+            warn!("{}: skipping, {}", revision.short_commit, err);
+            return None;
+        }
+    };
+    let save = match SaveData::from_bytes(chapter, &content) {
+        Ok(save) => save,
+        Err(err) => {
+            warn!("{}: skipping, failed to parse save: {}", revision.short_commit, err);
+            return None;
+        }
+    };
+
+    match query.matches(&save) {
+        Ok(result) => {
+            let verdict = if result { "matches" } else { "doesn't match" };
+            info!("{}: {}", revision.short_commit, verdict);
+            Some(result)
+        }
+        Err(err) => {
+            error!("Failed to evaluate condition: {}", err);
+            process::exit(255);
+        }
+    }
+}
+
+fn main() {
+    init();
+
+    let cli = Args::parse();
+
+    let path = &cli.slot;
+    let chapter_id = cli.chapter.unwrap_or_else(|| parse_filename(path).0);
+
+    if chapter_id < 1 {
+        error!("Could not determine the chapter of {}, use -c|--chapter", path);
+        process::exit(255);
+    }
+
+    let query = Expr::parse(&cli.when).unwrap_or_else(|err| {
+        error!("Invalid condition: {}", err);
+        process::exit(255);
+    });
+
+    let revisions = list_revisions(path).unwrap_or_else(|err| {
+        error!("Failed to read history of {}: {}", path, err);
+        process::exit(255);
+    });
+
+    info!("Searching {} revisions of {}.", revisions.len(), path);
+
+    let check_index = |i: usize| check(&revisions[i], path, chapter_id, &query);
+
+    let found = if cli.linear {
+        (0..revisions.len()).find(|&i| check_index(i) == Some(true))
+    } else {
+        bisect(revisions.len(), check_index)
+    };
+
+    match found {
+        Some(i) => {
+            let revision = &revisions[i];
+            println!("{} is the first matching commit", revision.commit);
+            println!("Date: {}", revision.date);
+            println!("    {}", revision.subject);
+        }
+        None => {
+            info!("No commit matches the condition.");
+            process::exit(1);
+        }
+    }
+}
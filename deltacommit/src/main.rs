@@ -1,10 +1,11 @@
-use clap::{arg, Parser};
-use deltakit::gamedata::parse_filename;
-use deltakit::gamedata::rooms::try_get_room_name;
+use clap::Parser;
+use deltakit::gamedata::{completion_slot_for, parse_filename};
 use deltakit::iter::{IterExt, SingleError};
 use deltakit::savefile::SaveData;
 use deltakit::init;
-use log::{error, info};
+use deltakit::transfer::is_simulated_start;
+use log::{error, info, warn};
+use std::path::Path;
 use std::process::Command;
 use std::{process, str};
 
@@ -77,19 +78,20 @@ fn main() {
         }
     }
 
-    let (filename, chapter, _save) = status.unwrap();
+    let (filename, chapter, save) = status.unwrap();
 
     let save_data = std::fs::read_to_string(filename).unwrap();
     let save_lines = save_data.lines().collect::<Vec<_>>();
 
     let save_info = SaveData::read(chapter, &save_lines).unwrap();
 
+    warn_if_simulated(filename, chapter, save, &save_info);
+
     let room = if let Some(ref room) = cli.room { room } else {
-        let room_id = save_info.room_id;
-        let room_name = try_get_room_name(room_id / 10000, room_id % 10000);
+        let room_name = save_info.room_name();
 
         if room_name.is_none() {
-            error!("Failed to find room name for room ID {}", room_id);
+            error!("Failed to find room name for room ID {}", save_info.room_id);
             info!("Hint: You can use -r|--room to specify the room name manually");
             process::exit(255);
         }
@@ -97,14 +99,7 @@ fn main() {
         room_name.unwrap()
     };
 
-    let time_played_secs = save_info.time_played.as_secs();
-    let time_played_h = time_played_secs / 3600;
-    let time_played_m = (time_played_secs % 3600) / 60;
-    let time_played_s = time_played_secs % 60;
-    let time_played = format!(
-        "{}h{:02}m{:02}s",
-        time_played_h, time_played_m, time_played_s
-    );
+    let time_played = save_info.display_time_played();
 
     let mut custom_message = cli.message;
     if !custom_message.is_empty() {
@@ -141,3 +136,23 @@ fn main() {
         room, time_played
     );
 }
+
+/// Warns when the save is the simulated start of its chapter made from the previous chapter's
+/// completion save next to it, since that simulation is only an approximation.
+fn warn_if_simulated(filename: &str, chapter: i32, slot: i32, save_info: &SaveData) {
+    let Some(completion_slot) = completion_slot_for(slot) else { return };
+    let completion_name = format!("filech{}_{}", chapter - 1, completion_slot);
+    let completion_path = Path::new(filename).with_file_name(completion_name);
+
+    let Ok(completion_data) = std::fs::read(&completion_path) else { return };
+    let Ok(completion) = SaveData::from_bytes(chapter - 1, &completion_data) else { return };
+
+    if is_simulated_start(save_info, &completion) {
+        warn!(
+            "{} looks like it was made by simulating the transfer from {}, which only approximates \
+             the game's load logic",
+            filename,
+            completion_path.display()
+        );
+    }
+}
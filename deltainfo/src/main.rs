@@ -1,35 +1,303 @@
-use deltakit::gamedata::parse_filename;
-use deltakit::savefile::SaveData;
+use deltakit::checklist::Checklist;
+use deltakit::gamedata::recruits::RecruitStatus;
+use deltakit::gamedata::{is_completion_slot, parse_filename};
+use deltakit::savefile::{SaveData, SaveSummary};
+use deltakit::stats::effective_stats;
 use deltakit::init;
-use log::info;
+use log::{error, info, warn};
 use clap::Parser;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process;
 
 #[derive(Parser, Debug)]
 #[command()]
 struct Args {
-    /// Save file to parse
+    /// Save file to parse, or a directory to summarize all saves in
     file: String,
     /// Chapter number. If not specified, will try to parse from filename.
+    ///
+    /// For a directory, only lists the saves of this chapter.
     #[arg(short, long)]
     chapter: Option<i32>,
     /// Print debug save info instead of basic information.
     #[arg(short, long)]
     debug: bool,
+    /// Print a JSON summary instead.
+    #[arg(long)]
+    json: bool,
+    /// Print the party sheet with everyone's effective stats.
+    #[arg(short, long)]
+    party: bool,
+    /// Print the recruit progress of every recruitable enemy of the chapter.
+    #[arg(short, long)]
+    recruits: bool,
+    /// Print which collectibles and milestones of the chapter the save has.
+    #[arg(long)]
+    checklist: bool,
+    /// Checklist definition to use instead of the built-in one for the chapter.
+    #[arg(long, value_name = "FILE")]
+    checklist_file: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SlotSummary {
+    path: String,
+    slot: i32,
+    completion: bool,
+    #[serde(flatten)]
+    summary: SaveSummary,
+}
+
+/// Recursively finds all `filechN_M` files, skipping hidden directories like `.git`.
+fn find_saves(dir: &Path, output: &mut Vec<(PathBuf, i32, i32)>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            warn!("Failed to read directory {}: {}", dir.display(), err);
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+
+        if path.is_dir() {
+            if !file_name.starts_with('.') {
+                find_saves(&path, output);
+            }
+            continue;
+        }
+
+        let (chapter, slot) = parse_filename(&file_name);
+        if chapter > 0 {
+            output.push((path, chapter, slot));
+        }
+    }
+}
+
+fn summarize_directory(dir: &Path, chapter: Option<i32>, json: bool) {
+    let mut saves = Vec::new();
+    find_saves(dir, &mut saves);
+    if let Some(chapter) = chapter {
+        saves.retain(|&(_, save_chapter, _)| save_chapter == chapter);
+    }
+    saves.sort_by(|(path_a, chapter_a, slot_a), (path_b, chapter_b, slot_b)| {
+        (chapter_a, slot_a, path_a).cmp(&(chapter_b, slot_b, path_b))
+    });
+
+    let summaries = saves
+        .into_iter()
+        .filter_map(|(path, chapter, slot)| {
+            let file_content = std::fs::read(&path)
+                .inspect_err(|err| warn!("Failed to read {}: {}", path.display(), err))
+                .ok()?;
+            let save = SaveData::from_bytes(chapter, &file_content)
+                .inspect_err(|err| warn!("Failed to parse {}: {}", path.display(), err))
+                .ok()?;
+
+            Some(SlotSummary {
+                path: path.display().to_string(),
+                slot,
+                completion: is_completion_slot(slot),
+                summary: save.summary(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summaries).unwrap());
+        return;
+    }
+
+    let mut last_chapter = 0;
+
+    for slot in &summaries {
+        let summary = &slot.summary;
+
+        if summary.chapter != last_chapter {
+            if last_chapter != 0 {
+                println!();
+            }
+            println!("Chapter {}", summary.chapter);
+            println!(
+                "{:<4} {:<28} {:>3} {:>6} {:>6} {:>11}  {:<24} File",
+                "Slot", "Room", "LV", "D$", "Plot", "Played", "Party"
+            );
+            last_chapter = summary.chapter;
+        }
+
+        let slot_label = if slot.completion {
+            format!("{}*", slot.slot)
+        } else {
+            slot.slot.to_string()
+        };
+
+        println!(
+            "{:<4} {:<28} {:>3} {:>6} {:>6} {:>11}  {:<24} {}",
+            slot_label,
+            summary.room,
+            summary.level,
+            summary.dark_dollars,
+            summary.plot_value,
+            summary.display_time_played(),
+            summary.party.join(", "),
+            slot.path,
+        );
+    }
+
+    if summaries.iter().any(|slot| slot.completion) {
+        println!();
+        println!("* completion save, imported by the next chapter");
+    }
+}
+
+/// Prints the stats shown in the in-game party menu for every stat block.
+/// Current party members are marked with `*`.
+fn print_party_sheet(save: &SaveData) {
+    println!(
+        "{:<8} {:>9} {:>3} {:>3} {:>3}  {:<7} {:<14} {:<28} Resist",
+        "Member", "HP", "AT", "DF", "MG", "Graze", "Weapon", "Armor"
+    );
+
+    let context = save.context();
+    for effective in effective_stats(save) {
+        let stats = &save.stats[effective.member.0 as usize];
+        let marker = if save.party.contains(&effective.member) { "*" } else { "" };
+        let resistances = effective
+            .resistances
+            .iter()
+            .map(|(element, amount)| format!("{element}:{amount}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let line = format!(
+            "{:<8} {:>9} {:>3} {:>3} {:>3}  {:<7} {:<14} {:<28} {}",
+            format!("{}{}", effective.member, marker),
+            format!("{}/{}", effective.hp, effective.max_hp),
+            effective.attack,
+            effective.defense,
+            effective.magic,
+            format!("+{}/+{}", effective.graze_amount, effective.graze_size),
+            stats.weapon.name(&context).unwrap_or("?"),
+            format!(
+                "{}, {}",
+                stats.armor1.name(&context).unwrap_or("?"),
+                stats.armor2.name(&context).unwrap_or("?")
+            ),
+            resistances,
+        );
+        println!("{}", line.trim_end());
+    }
+}
+
+fn print_recruits(save: &SaveData, json: bool) {
+    let recruits = save.recruits();
+
+    if json {
+        #[derive(Serialize)]
+        struct Recruit {
+            name: &'static str,
+            #[serde(flatten)]
+            status: RecruitStatus,
+        }
+
+        let recruits = recruits
+            .iter()
+            .map(|&(info, status)| Recruit {
+                name: info.name,
+                status,
+            })
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::to_string_pretty(&recruits).unwrap());
+        return;
+    }
+
+    if recruits.is_empty() {
+        println!("No recruit data for chapter {}", save.chapter);
+        return;
+    }
+
+    let recruited = recruits.iter().filter(|(_, status)| *status == RecruitStatus::Recruited);
+    let lost = recruits.iter().filter(|(_, status)| *status == RecruitStatus::Lost);
+    println!(
+        "Recruits ({}/{} recruited, {} lost):",
+        recruited.count(),
+        recruits.len(),
+        lost.count()
+    );
+    for (info, status) in &recruits {
+        println!("{:<13} {}", info.name, status);
+    }
+}
+
+fn print_checklist(save: &SaveData, checklist_file: Option<&str>, json: bool) {
+    let checklist = match checklist_file {
+        Some(path) => {
+            let content = std::fs::read_to_string(path).unwrap_or_else(|err| {
+                error!("Failed to read {}: {}", path, err);
+                process::exit(255);
+            });
+            Checklist::parse(&content).unwrap_or_else(|err| {
+                error!("Invalid checklist {}: {}", path, err);
+                process::exit(255);
+            })
+        }
+        None => Checklist::builtin(save.chapter).unwrap_or_else(|| {
+            error!("No checklist for chapter {}", save.chapter);
+            process::exit(255);
+        }),
+    };
+
+    if checklist.chapter != save.chapter {
+        warn!(
+            "Checking a chapter {} save against the chapter {} checklist",
+            save.chapter, checklist.chapter
+        );
+    }
+
+    let report = checklist.check(save);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else {
+        print!("{}", report);
+    }
 }
 
 fn main() {
     init();
-    
+
     let cli = Args::parse();
 
     let path = &cli.file;
-    let file_content = std::fs::read_to_string(path).unwrap();
-    let file_lines = file_content.lines().collect::<Vec<_>>();
+
+    if Path::new(path).is_dir() {
+        summarize_directory(Path::new(path), cli.chapter, cli.json);
+        return;
+    }
+
+    let file_content = std::fs::read(path).unwrap_or_else(|err| {
+        error!("Failed to read {}: {}", path, err);
+        process::exit(255);
+    });
 
     let chapter_id = cli.chapter.unwrap_or_else(|| parse_filename(path).0);
-    let metadata = SaveData::read(chapter_id, &file_lines).unwrap();
-    
-    if cli.debug {
+    let metadata = SaveData::from_bytes(chapter_id, &file_content).unwrap_or_else(|err| {
+        error!("Failed to parse {}: {}", path, err);
+        process::exit(255);
+    });
+
+    if cli.checklist || cli.checklist_file.is_some() {
+        print_checklist(&metadata, cli.checklist_file.as_deref(), cli.json);
+    } else if cli.recruits {
+        print_recruits(&metadata, cli.json);
+    } else if cli.json {
+        println!("{}", serde_json::to_string_pretty(&metadata.summary()).unwrap());
+    } else if cli.party {
+        print_party_sheet(&metadata);
+    } else if cli.debug {
         info!("{:#?}", metadata);
     } else {
         info!("{}", metadata.display_info());
@@ -0,0 +1,92 @@
+use clap::Parser;
+use deltakit::gamedata::{is_completion_slot, parse_filename};
+use deltakit::ini::{INI_FILE_NAME, IniFile, SlotSummary, slot_section};
+use deltakit::init;
+use deltakit::savefile::SaveData;
+use log::{error, info, warn};
+use std::path::PathBuf;
+use std::process;
+
+#[derive(Parser, Debug)]
+#[command()]
+struct Args {
+    /// Save directory containing the `filechN_M` files and `dr.ini`.
+    #[arg(default_value = ".")]
+    dir: PathBuf,
+    /// Print the new file instead of writing it.
+    #[arg(short, long)]
+    print: bool,
+}
+
+/// Regenerates the slot summaries in `dr.ini` from the saves next to it, so the file select
+/// screen matches the saves after merging or editing them.
+fn main() {
+    init();
+
+    let cli = Args::parse();
+    let ini_path = cli.dir.join(INI_FILE_NAME);
+
+    let mut ini = match std::fs::read_to_string(&ini_path) {
+        Ok(content) => IniFile::parse(&content).unwrap_or_else(|err| {
+            error!("Failed to parse {}: {}", ini_path.display(), err);
+            process::exit(255);
+        }),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => IniFile::default(),
+        Err(err) => {
+            error!("Failed to read {}: {}", ini_path.display(), err);
+            process::exit(255);
+        }
+    };
+
+    let entries = std::fs::read_dir(&cli.dir).unwrap_or_else(|err| {
+        error!("Failed to read directory {}: {}", cli.dir.display(), err);
+        process::exit(255);
+    });
+
+    let mut saves = entries
+        .flatten()
+        .filter_map(|entry| {
+            let (chapter, slot) = parse_filename(&entry.file_name().to_string_lossy());
+            (chapter > 0).then(|| (entry.path(), chapter, slot))
+        })
+        .collect::<Vec<_>>();
+    saves.sort_by_key(|(_, chapter, slot)| (*chapter, *slot));
+
+    for (path, chapter, slot) in saves {
+        // The file select screen doesn't show completion saves, so they have no summary.
+        if is_completion_slot(slot) {
+            continue;
+        }
+
+        let file_content = match std::fs::read(&path) {
+            Ok(content) => content,
+            Err(err) => {
+                warn!("Failed to read {}: {}", path.display(), err);
+                continue;
+            }
+        };
+        let save = match SaveData::from_bytes(chapter, &file_content) {
+            Ok(save) => save,
+            Err(err) => {
+                warn!("Failed to parse {}: {}", path.display(), err);
+                continue;
+            }
+        };
+
+        let summary = SlotSummary::from_save(&save);
+        if ini.slot_summary(chapter, slot).as_ref() != Some(&summary) {
+            info!("Updating [{}] from {}", slot_section(chapter, slot), path.display());
+            ini.set_slot_summary(chapter, slot, &summary);
+        }
+    }
+
+    if cli.print {
+        print!("{}", ini);
+        return;
+    }
+
+    if let Err(err) = std::fs::write(&ini_path, ini.write()) {
+        error!("Failed to write {}: {}", ini_path.display(), err);
+        process::exit(255);
+    }
+}
@@ -0,0 +1,81 @@
+use clap::Parser;
+use deltakit::gamedata::{is_completion_slot, parse_filename};
+use deltakit::ini::{INI_FILE_NAME, IniFile, check_slot};
+use deltakit::init;
+use deltakit::savefile::SaveData;
+use log::{error, info, warn};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process;
+
+#[derive(Parser, Debug)]
+#[command()]
+struct Args {
+    /// Save files to check.
+    #[arg(required = true)]
+    files: Vec<String>,
+    /// Chapter number. If not specified, will try to parse from filenames.
+    #[arg(short, long)]
+    chapter: Option<i32>,
+}
+
+/// Reads the `dr.ini` next to a save, if there is one.
+fn read_ini(dir: &Path) -> Option<IniFile> {
+    let path = dir.join(INI_FILE_NAME);
+    let content = std::fs::read_to_string(&path).ok()?;
+    IniFile::parse(&content)
+        .inspect_err(|err| warn!("Failed to parse {}: {}", path.display(), err))
+        .ok()
+}
+
+/// Prints every inconsistency found in the saves, including slot summaries in `dr.ini` that
+/// don't match them.
+///
+/// Exits with 1 if any save has issues, so it can run before committing or after merging.
+fn main() {
+    init();
+
+    let cli = Args::parse();
+
+    let mut issue_count = 0;
+    let mut ini_files: HashMap<PathBuf, Option<IniFile>> = HashMap::new();
+
+    for path in &cli.files {
+        let (file_chapter, slot) = parse_filename(path);
+        let chapter_id = cli.chapter.unwrap_or(file_chapter);
+
+        let file_content = std::fs::read_to_string(path).unwrap_or_else(|err| {
+            error!("Failed to read {}: {}", path, err);
+            process::exit(255);
+        });
+        let file_lines = file_content.lines().collect::<Vec<_>>();
+
+        let save = SaveData::read(chapter_id, &file_lines).unwrap_or_else(|err| {
+            error!("Failed to parse {}: {}", path, err);
+            process::exit(255);
+        });
+
+        for issue in save.validate() {
+            println!("{}: {}", path, issue);
+            issue_count += 1;
+        }
+
+        // Only saves named like the game names them have a summary
+        if file_chapter == 0 || is_completion_slot(slot) {
+            continue;
+        }
+        let dir = Path::new(path).parent().unwrap_or(Path::new("")).to_path_buf();
+        let ini = ini_files.entry(dir.clone()).or_insert_with(|| read_ini(&dir));
+        if let Some(ini) = ini {
+            for mismatch in check_slot(ini, chapter_id, slot, &save) {
+                println!("{}: {}", path, mismatch);
+                issue_count += 1;
+            }
+        }
+    }
+
+    if issue_count > 0 {
+        info!("Found {} issues.", issue_count);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,146 @@
+use clap::{Parser, ValueEnum};
+use deltakit::gamedata::parse_filename;
+use deltakit::history::{Revision, detect_anomalies, list_revisions, read_revision};
+use deltakit::init;
+use deltakit::savefile::SaveData;
+use deltakit::sessions::{SaveSnapshot, session_stats};
+use deltakit::visit::diff;
+use log::{error, warn};
+use std::process;
+
+#[derive(Parser, Debug)]
+#[command()]
+struct Args {
+    /// Save file to follow, either relative to the current directory or absolute.
+    file: String,
+    /// Chapter number. If not specified, will try to parse from filename.
+    #[arg(short, long)]
+    chapter: Option<i32>,
+    /// Only print revisions with anomalies.
+    #[arg(short, long)]
+    anomalies: bool,
+    /// Also print the fields each commit changed.
+    #[arg(short, long)]
+    diff: bool,
+    /// Print play session statistics instead of the revisions.
+    #[arg(short, long, value_enum)]
+    stats: Option<StatsFormat>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum StatsFormat {
+    /// Time played per area as a bar chart, with the totals.
+    Chart,
+    /// The changes of every commit as CSV.
+    Csv,
+    /// Everything as JSON.
+    Json,
+}
+
+fn print_stats(revisions: &[Revision], path: &str, chapter_id: i32, format: StatsFormat) {
+    let snapshots = revisions
+        .iter()
+        .filter_map(|revision| {
+            let content = read_revision(revision, path)
+                .inspect_err(|err| warn!("{}: {}", revision.short_commit, err))
+                .ok()?;
+            let save = SaveData::from_bytes(chapter_id, &content)
+                .inspect_err(|err| {
+                    warn!("{}: failed to parse save: {}", revision.short_commit, err)
+                })
+                .ok()?;
+            Some(SaveSnapshot::new(revision, &save))
+        })
+        .collect::<Vec<_>>();
+
+    let stats = session_stats(&snapshots);
+    match format {
+        StatsFormat::Chart => print!("{}", stats.chart(40)),
+        StatsFormat::Csv => print!("{}", stats.to_csv()),
+        StatsFormat::Json => println!("{}", serde_json::to_string_pretty(&stats).unwrap()),
+    }
+}
+
+fn main() {
+    init();
+
+    let cli = Args::parse();
+
+    let path = &cli.file;
+    let chapter_id = cli.chapter.unwrap_or_else(|| parse_filename(path).0);
+
+    if chapter_id < 1 {
+        error!("Could not determine the chapter of {}, use -c|--chapter", path);
+        process::exit(255);
+    }
+
+    let revisions = list_revisions(path).unwrap_or_else(|err| {
+        error!("Failed to read history of {}: {}", path, err);
+        process::exit(255);
+    });
+
+    if revisions.is_empty() {
+        error!("No commits found for {}", path);
+        process::exit(255);
+    }
+
+    if let Some(format) = cli.stats {
+        print_stats(&revisions, path, chapter_id, format);
+        return;
+    }
+
+    println!(
+        "{:<9} {:<10} {:>6} {:<28} {:>3} {:>6} {:>11}  Party",
+        "Commit", "Date", "Plot", "Room", "LV", "D$", "Played"
+    );
+
+    // Read into owned saves so the previous one can be kept for the next commit
+    let mut previous: Option<SaveData<'static>> = None;
+
+    for revision in &revisions {
+        let content = match read_revision(revision, path) {
+            Ok(content) => content,
+            Err(err) => {
+                warn!("{}: {}", revision.short_commit, err);
+                continue;
+            }
+        };
+        let save = match SaveData::from_reader(chapter_id, content.as_slice()) {
+            Ok(save) => save,
+            Err(err) => {
+                warn!("{}: failed to parse save: {}", revision.short_commit, err);
+                continue;
+            }
+        };
+
+        let anomalies = previous
+            .as_ref()
+            .map(|previous| detect_anomalies(previous, &save))
+            .unwrap_or_default();
+
+        if !cli.anomalies || !anomalies.is_empty() {
+            println!(
+                "{:<9} {:<10} {:>6} {:<28} {:>3} {:>6} {:>11}  {}",
+                revision.short_commit,
+                revision.date.get(..10).unwrap_or(&revision.date),
+                save.plot_value,
+                save.room_name().unwrap_or("?"),
+                save.level,
+                save.dark_dollars,
+                save.display_time_played(),
+                save.display_party(),
+            );
+
+            for anomaly in &anomalies {
+                println!("          ! {}", anomaly);
+            }
+            if let Some(previous) = previous.as_ref().filter(|_| cli.diff) {
+                for change in diff(previous, &save) {
+                    println!("          {}", change);
+                }
+            }
+        }
+
+        previous = Some(save);
+    }
+}
@@ -1,7 +1,8 @@
 use deltakit::gamedata::parse_filename;
 use deltakit::init;
+use deltakit::layout::SaveLayout;
 use deltakit::savefile::SaveData;
-use log::{error, info};
+use log::{error, info, warn};
 use std::env::args;
 use std::process;
 use deltakit::merging::{merge_savefiles, MergeResult};
@@ -72,11 +73,33 @@ fn main() {
     let merged_content = merge_strings.join("\r\n");
 
     // Write the result back to the ours file
-    std::fs::write(ours, merged_content).expect("Failed to write merge result to file");
+    std::fs::write(ours, &merged_content).expect("Failed to write merge result to file");
 
     info!("Successfully wrote merge result to {}", ours);
 
+    if !has_conflicts {
+        let merged_lines = merged_content.lines().collect::<Vec<_>>();
+        match SaveData::read(chapter_id, &merged_lines) {
+            Ok(merged) => {
+                for issue in merged.validate() {
+                    warn!("Merged save: {}", issue);
+                }
+            }
+            Err(err) => warn!("Failed to parse merged save: {}", err),
+        }
+    }
+
     if has_conflicts {
+        let layout = SaveLayout::for_chapter(chapter_id);
+        for (line, result) in merge_result.iter().enumerate() {
+            if matches!(result, MergeResult::Conflict { .. }) {
+                match layout.field_at(line) {
+                    Some((field, index)) => warn!("Conflict in {field}[{index}] (line {})", line + 1),
+                    None => warn!("Conflict on line {}", line + 1),
+                }
+            }
+        }
+
         info!("Merge conflicts detected. Exiting with code 1 to notify git.");
         process::exit(1);
     } else {
@@ -0,0 +1,76 @@
+use clap::Parser;
+use deltakit::gamedata::parse_filename;
+use deltakit::init;
+use deltakit::query::{Expr, Value};
+use deltakit::savefile::SaveData;
+use log::error;
+use std::process;
+
+#[derive(Parser, Debug)]
+#[command()]
+struct Args {
+    /// Expression to evaluate, e.g. `lv >= 3 and party contains Noelle`.
+    expression: String,
+    /// Save files to evaluate the expression on.
+    #[arg(required = true)]
+    files: Vec<String>,
+    /// Chapter number. If not specified, will try to parse from filenames.
+    #[arg(short, long)]
+    chapter: Option<i32>,
+    /// Only print the files the expression is true for.
+    #[arg(short, long)]
+    filter: bool,
+}
+
+/// Prints the value of the expression for each save.
+///
+/// Exits with 1 if the expression was false for any of them, so it can be used in CI checks.
+fn main() {
+    init();
+
+    let cli = Args::parse();
+
+    let expr = Expr::parse(&cli.expression).unwrap_or_else(|err| {
+        error!("Invalid expression: {}", err);
+        process::exit(255);
+    });
+
+    let mut all_true = true;
+
+    for path in &cli.files {
+        let chapter_id = cli.chapter.unwrap_or_else(|| parse_filename(path).0);
+        if chapter_id < 1 {
+            error!("Could not determine the chapter of {}, use -c|--chapter", path);
+            process::exit(255);
+        }
+
+        let file_content = std::fs::read(path).unwrap_or_else(|err| {
+            error!("Failed to read {}: {}", path, err);
+            process::exit(255);
+        });
+
+        let save = SaveData::from_bytes(chapter_id, &file_content).unwrap_or_else(|err| {
+            error!("Failed to parse {}: {}", path, err);
+            process::exit(255);
+        });
+
+        let value = expr.evaluate(&save).unwrap_or_else(|err| {
+            error!("Failed to evaluate expression on {}: {}", path, err);
+            process::exit(255);
+        });
+
+        if value == Value::Bool(false) {
+            all_true = false;
+        }
+
+        if !cli.filter {
+            println!("{}: {}", path, value);
+        } else if value == Value::Bool(true) {
+            println!("{}", path);
+        }
+    }
+
+    if !all_true {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,75 @@
+//! Derive macros for deltakit.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Field, Fields, parse_macro_input};
+
+/// Derives `deltakit::visit::Visit` for a struct with named fields, visiting each field under
+/// its name in declaration order.
+///
+/// Fields marked `#[visit(skip)]` aren't visited.
+#[proc_macro_derive(Visit, attributes(visit))]
+pub fn derive_visit(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_visit(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand_visit(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(&input.ident, "Visit can only be derived for structs"));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Visit can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut visits = Vec::new();
+    for field in &fields.named {
+        if is_skipped(field)? {
+            continue;
+        }
+        let ident = field.ident.as_ref().expect("named fields have names");
+        let name = ident.unraw().to_string();
+        visits.push(quote! {
+            path.push_field(#name);
+            ::deltakit::visit::Visit::visit_with(&self.#ident, path, visitor);
+            path.pop();
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::deltakit::visit::Visit for #name #ty_generics #where_clause {
+            fn visit_with(
+                &self,
+                path: &mut ::deltakit::visit::FieldPath,
+                visitor: &mut dyn ::deltakit::visit::Visitor,
+            ) {
+                #(#visits)*
+            }
+        }
+    })
+}
+
+fn is_skipped(field: &Field) -> syn::Result<bool> {
+    let mut skip = false;
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("visit")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown visit attribute"))
+            }
+        })?;
+    }
+    Ok(skip)
+}